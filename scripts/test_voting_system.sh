@@ -6,11 +6,12 @@ CURRENT_DIR=$(git rev-parse --show-toplevel)/scripts
 
 VOTING_SYSTEM_ID=$(cat $CURRENT_DIR/.contracts/voting_system_id)
 EXTERNAL_DATA_PROVIDER_ID=$(cat $CURRENT_DIR/.contracts/external_data_provider_id)
+ADMIN=$(cat $CURRENT_DIR/.config/public_key)
 
 source $(git rev-parse --show-toplevel)/scripts/invoke_function.sh
 
 echo "[*] invoking initialize"
-invoke_function $VOTING_SYSTEM_ID initialize
+invoke_function $VOTING_SYSTEM_ID initialize --admin $ADMIN
 
 echo "[*] invoking add_layer"
 invoke_function $VOTING_SYSTEM_ID add_layer
@@ -35,18 +36,33 @@ invoke_function $VOTING_SYSTEM_ID add_neuron --layer_id 1 --neuron Dummy
 echo "[*] invoking get_neural_governance"
 invoke_function $VOTING_SYSTEM_ID get_neural_governance
 
+echo "[*] invoking create_round"
+invoke_function $VOTING_SYSTEM_ID create_round
+
 echo "[*] invoking add_submission"
-invoke_function $VOTING_SYSTEM_ID add_submission --submission_id submission001
+invoke_function $VOTING_SYSTEM_ID add_submission --submission_id submission001 --title Submission001 --category Infrastructure --requested_budget 10000 --proposer $ADMIN
+
+echo "[*] invoking register_voter user001"
+invoke_function $VOTING_SYSTEM_ID register_voter --voter_id user001 --voter_address $ADMIN
+
+echo "[*] invoking open_round"
+invoke_function $VOTING_SYSTEM_ID open_round
 
 echo "[*] invoking vote user001 submission001 Yes"
-invoke_function $VOTING_SYSTEM_ID vote --voter_id user001 --submission_id submission001 --vote Yes
+invoke_function $VOTING_SYSTEM_ID vote_str --voter_id user001 --submission_id submission001 --vote Yes
 
-# external data provider has to be added and immediately used because we use temporary storage there
-echo "[*] invoking mock_sample_data"
-invoke_function $EXTERNAL_DATA_PROVIDER_ID mock_sample_data
+# the ballots and the external data provider's data are persistent entries which are extended whenever
+# they are written, so they outlive this script (extend_round_ttl / extend_dataset_ttl keep them alive longer)
+echo "[*] invoking initialize (external data provider)"
+invoke_function $EXTERNAL_DATA_PROVIDER_ID initialize --owner $ADMIN
+echo "[*] invoking set_user_reputation_category user001 Excellent"
+invoke_function $EXTERNAL_DATA_PROVIDER_ID set_user_reputation_category --caller $ADMIN --user_id user001 --reputation_category Excellent
 
 echo "[*] invoking set_external_data_provider"
 invoke_function $VOTING_SYSTEM_ID set_external_data_provider --external_data_provider_address $EXTERNAL_DATA_PROVIDER_ID
 
+echo "[*] invoking close_round"
+invoke_function $VOTING_SYSTEM_ID close_round
+
 echo "[*] invoking tally"
 invoke_function $VOTING_SYSTEM_ID tally
@@ -0,0 +1,78 @@
+use soroban_sdk::{
+  testutils::{Address as _, MockAuth, MockAuthInvoke},
+  Address, Env, IntoVal, String,
+};
+
+use crate::{
+  types::{ExampleNeuronError, DEFAULT_NEURON_VOTE},
+  ExampleNeuron, ExampleNeuronClient,
+};
+
+fn initialize_example_neuron(env: &Env) -> ExampleNeuronClient {
+  env.mock_all_auths();
+
+  let example_neuron_id = env.register_contract(None, ExampleNeuron);
+  let example_neuron_client = ExampleNeuronClient::new(&env, &example_neuron_id);
+  example_neuron_client.initialize(&Address::generate(env));
+
+  example_neuron_client
+}
+
+#[test]
+pub fn test_oracle_function() {
+  let env = Env::default();
+  let example_neuron_client = initialize_example_neuron(&env);
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let submission_id = String::from_slice(&env, "submission001");
+
+  assert!(
+    example_neuron_client.oracle_function(&voter_id_1, &submission_id, &0) == DEFAULT_NEURON_VOTE
+  );
+
+  example_neuron_client.set_voter_vote(&voter_id_1, &2500);
+  assert!(example_neuron_client.oracle_function(&voter_id_1, &submission_id, &0) == 2500);
+  assert!(
+    example_neuron_client.oracle_function(&voter_id_2, &submission_id, &0) == DEFAULT_NEURON_VOTE
+  );
+
+  example_neuron_client.set_default_vote(&0);
+  assert!(example_neuron_client.oracle_function(&voter_id_2, &submission_id, &1000) == 0);
+  assert!(example_neuron_client.oracle_function(&voter_id_1, &submission_id, &1000) == 2500);
+
+  // a negative vote is a penalty
+  example_neuron_client.set_voter_vote(&voter_id_2, &-500);
+  assert!(example_neuron_client.oracle_function(&voter_id_2, &submission_id, &0) == -500);
+}
+
+#[test]
+pub fn test_owner_access_control() {
+  let env = Env::default();
+  let example_neuron_client = initialize_example_neuron(&env);
+
+  let outsider = Address::generate(&env);
+  let voter_id = String::from_slice(&env, "user001");
+  let submission_id = String::from_slice(&env, "submission001");
+
+  assert!(
+    example_neuron_client.try_initialize(&outsider)
+      == Err(Ok(ExampleNeuronError::AlreadyInitialized))
+  );
+
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &example_neuron_client.address,
+      fn_name: "set_voter_vote",
+      args: (voter_id.clone(), 5000_i128).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(example_neuron_client
+    .try_set_voter_vote(&voter_id, &5000)
+    .is_err());
+  assert!(
+    example_neuron_client.oracle_function(&voter_id, &submission_id, &0) == DEFAULT_NEURON_VOTE
+  );
+}
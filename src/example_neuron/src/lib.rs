@@ -0,0 +1,98 @@
+#![no_std]
+
+pub mod types;
+
+// An example of a neuron deployed as a separate contract. Any contract which exposes
+// `oracle_function(voter_id, submission_id, previous_layer_vote) -> SignedDecimalNumber` can be
+// added to a layer of the Voting System with `add_external_neuron`.
+// This one returns the vote assigned to the voter by the owner, or the default vote. A negative
+// vote is a penalty.
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String};
+use types::{ExampleNeuronError, SignedDecimalNumber, DEFAULT_NEURON_VOTE};
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+  // storage type: instance
+  // Address - can assign the votes
+  Owner,
+  // storage type: instance
+  // SignedDecimalNumber - vote of the voters without an assigned vote
+  DefaultVote,
+  // storage type: persistent
+  // SignedDecimalNumber - user_id => vote assigned to the user
+  VoterVote(String),
+}
+
+#[contract]
+pub struct ExampleNeuron;
+
+#[contractimpl]
+impl ExampleNeuron {
+  pub fn initialize(env: Env, owner: Address) -> Result<(), ExampleNeuronError> {
+    if env.storage().instance().has(&DataKey::Owner) {
+      return Err(ExampleNeuronError::AlreadyInitialized);
+    }
+    env.storage().instance().set(&DataKey::Owner, &owner);
+    Ok(())
+  }
+
+  pub fn get_owner(env: Env) -> Result<Address, ExampleNeuronError> {
+    env
+      .storage()
+      .instance()
+      .get(&DataKey::Owner)
+      .ok_or(ExampleNeuronError::OwnerNotSet)
+  }
+
+  fn require_owner(env: &Env) -> Result<(), ExampleNeuronError> {
+    let owner = ExampleNeuron::get_owner(env.clone())?;
+    owner.require_auth();
+    Ok(())
+  }
+
+  pub fn set_default_vote(env: Env, vote: SignedDecimalNumber) -> Result<(), ExampleNeuronError> {
+    ExampleNeuron::require_owner(&env)?;
+    env.storage().instance().set(&DataKey::DefaultVote, &vote);
+    Ok(())
+  }
+
+  pub fn get_default_vote(env: Env) -> SignedDecimalNumber {
+    env
+      .storage()
+      .instance()
+      .get(&DataKey::DefaultVote)
+      .unwrap_or(DEFAULT_NEURON_VOTE)
+  }
+
+  pub fn set_voter_vote(
+    env: Env,
+    voter_id: String,
+    vote: SignedDecimalNumber,
+  ) -> Result<(), ExampleNeuronError> {
+    ExampleNeuron::require_owner(&env)?;
+    env
+      .storage()
+      .persistent()
+      .set(&DataKey::VoterVote(voter_id), &vote);
+    Ok(())
+  }
+
+  // the interface required by the Voting System, it must not fail for unknown voters
+  pub fn oracle_function(
+    env: Env,
+    voter_id: String,
+    _submission_id: String,
+    _previous_layer_vote: SignedDecimalNumber,
+  ) -> SignedDecimalNumber {
+    env
+      .storage()
+      .persistent()
+      .get(&DataKey::VoterVote(voter_id))
+      .unwrap_or(ExampleNeuron::get_default_vote(env))
+  }
+}
+
+#[cfg(test)]
+mod example_neuron_test;
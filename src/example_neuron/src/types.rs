@@ -0,0 +1,15 @@
+use soroban_sdk::contracterror;
+
+// raw decimal number with 3 decimal places which can be negative, -1500 is -1.5
+pub type SignedDecimalNumber = i128;
+
+// returned for voters without an assigned vote until the owner changes it
+pub const DEFAULT_NEURON_VOTE: SignedDecimalNumber = 1000;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum ExampleNeuronError {
+  UnknownError = 0,
+  AlreadyInitialized = 1,
+  OwnerNotSet = 2,
+}
@@ -0,0 +1,131 @@
+use crate::{
+  events,
+  types::{Dataset, DATA_TTL_EXTEND_TO, DATA_TTL_THRESHOLD},
+  DataKey,
+};
+use soroban_sdk::{Env, IntoVal, Map, String, TryFromVal, Val, Vec};
+
+// the value of every user is kept in its own persistent entry, so reading the data of one user
+// doesn't load the whole dataset; the users of each dataset are indexed to allow reading it at once
+
+pub fn extend_ttl(env: &Env, key: &DataKey) {
+  env
+    .storage()
+    .persistent()
+    .extend_ttl(key, DATA_TTL_THRESHOLD, DATA_TTL_EXTEND_TO);
+}
+
+fn get_users_index(env: &Env, dataset: Dataset) -> Map<String, ()> {
+  env
+    .storage()
+    .persistent()
+    .get(&DataKey::DatasetUsers(dataset))
+    .unwrap_or(Map::new(env))
+}
+
+fn set_users_index(env: &Env, dataset: Dataset, users: &Map<String, ()>) {
+  let key = DataKey::DatasetUsers(dataset);
+  if users.is_empty() {
+    env.storage().persistent().remove(&key);
+    return;
+  }
+  env.storage().persistent().set(&key, users);
+  extend_ttl(env, &key);
+}
+
+pub fn get<V: TryFromVal<Env, Val>>(env: &Env, dataset: Dataset, user_id: String) -> Option<V> {
+  env
+    .storage()
+    .persistent()
+    .get(&DataKey::UserData(dataset, user_id))
+}
+
+// Map<UserUUID, value>
+pub fn get_all<V: IntoVal<Env, Val> + TryFromVal<Env, Val>>(
+  env: &Env,
+  dataset: Dataset,
+) -> Map<String, V> {
+  let mut result: Map<String, V> = Map::new(env);
+  for user_id in get_users(env, dataset) {
+    if let Some(value) = get(env, dataset, user_id.clone()) {
+      result.set(user_id, value);
+    }
+  }
+  result
+}
+
+pub fn get_users(env: &Env, dataset: Dataset) -> Vec<String> {
+  get_users_index(env, dataset).keys()
+}
+
+pub fn set<V: IntoVal<Env, Val>>(env: &Env, dataset: Dataset, user_id: String, value: &V) {
+  let key = DataKey::UserData(dataset, user_id.clone());
+  env.storage().persistent().set(&key, value);
+  extend_ttl(env, &key);
+
+  let mut users = get_users_index(env, dataset);
+  if !users.contains_key(user_id.clone()) {
+    users.set(user_id.clone(), ());
+    set_users_index(env, dataset, &users);
+  }
+  events::dataset_updated(env, dataset, user_id);
+}
+
+pub fn remove(env: &Env, dataset: Dataset, user_id: String) {
+  let mut users = get_users_index(env, dataset);
+  if !users.contains_key(user_id.clone()) {
+    return;
+  }
+  env
+    .storage()
+    .persistent()
+    .remove(&DataKey::UserData(dataset, user_id.clone()));
+  users.remove(user_id.clone());
+  set_users_index(env, dataset, &users);
+  events::dataset_updated(env, dataset, user_id);
+}
+
+// replaces the whole dataset, users which are not in the new values are removed
+pub fn replace<V: IntoVal<Env, Val> + TryFromVal<Env, Val>>(
+  env: &Env,
+  dataset: Dataset,
+  values: Map<String, V>,
+) {
+  for user_id in get_users(env, dataset) {
+    if !values.contains_key(user_id.clone()) {
+      env
+        .storage()
+        .persistent()
+        .remove(&DataKey::UserData(dataset, user_id));
+    }
+  }
+
+  let mut users: Map<String, ()> = Map::new(env);
+  for (user_id, value) in values {
+    let key = DataKey::UserData(dataset, user_id.clone());
+    env.storage().persistent().set(&key, &value);
+    extend_ttl(env, &key);
+    users.set(user_id, ());
+  }
+  set_users_index(env, dataset, &users);
+  events::dataset_replaced(env, dataset, users.keys());
+}
+
+// only live entries can be extended, archived ones have to be restored first
+pub fn extend_users_ttl(env: &Env, dataset: Dataset, user_ids: Vec<String>) {
+  for user_id in user_ids {
+    let key = DataKey::UserData(dataset, user_id);
+    if env.storage().persistent().has(&key) {
+      extend_ttl(env, &key);
+    }
+  }
+}
+
+pub fn extend_dataset_ttl(env: &Env, dataset: Dataset) {
+  let key = DataKey::DatasetUsers(dataset);
+  if !env.storage().persistent().has(&key) {
+    return;
+  }
+  extend_ttl(env, &key);
+  extend_users_ttl(env, dataset, get_users(env, dataset));
+}
@@ -0,0 +1,45 @@
+use crate::types::{Dataset, PageRankMode};
+use soroban_sdk::{symbol_short, Address, Env, String, Vec};
+
+// Every event has two Symbol topics: (category, action). The data is a tuple described below.
+//
+// ("dataset", "updated")   (dataset, user_id) - the value of a single user has been set
+// ("dataset", "replaced")  (dataset, users_ids) - the whole dataset has been replaced, users_ids are the users in the new dataset
+//                          (RoundBonusMap is not stored per user, so users_ids is always empty for it)
+// ("writer", "added")      (dataset, writer)
+// ("writer", "removed")    (dataset, writer)
+// ("pagerank", "mode")     PageRankMode - the mode of the page rank calculations started from now on
+
+pub fn dataset_updated(env: &Env, dataset: Dataset, user_id: String) {
+  env.events().publish(
+    (symbol_short!("dataset"), symbol_short!("updated")),
+    (dataset, user_id),
+  );
+}
+
+pub fn dataset_replaced(env: &Env, dataset: Dataset, users_ids: Vec<String>) {
+  env.events().publish(
+    (symbol_short!("dataset"), symbol_short!("replaced")),
+    (dataset, users_ids),
+  );
+}
+
+pub fn writer_added(env: &Env, dataset: Dataset, writer: Address) {
+  env.events().publish(
+    (symbol_short!("writer"), symbol_short!("added")),
+    (dataset, writer),
+  );
+}
+
+pub fn writer_removed(env: &Env, dataset: Dataset, writer: Address) {
+  env.events().publish(
+    (symbol_short!("writer"), symbol_short!("removed")),
+    (dataset, writer),
+  );
+}
+
+pub fn page_rank_mode_set(env: &Env, mode: PageRankMode) {
+  env
+    .events()
+    .publish((symbol_short!("pagerank"), symbol_short!("mode")), mode);
+}
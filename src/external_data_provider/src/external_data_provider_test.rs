@@ -1,21 +1,41 @@
 use soroban_decimal_numbers::DecimalNumberWrapper;
-use soroban_sdk::{vec, Env, Map, String, Vec};
-
-use crate::{ExternalDataProvider, ExternalDataProviderClient, ReputationCategory};
+use soroban_sdk::{
+  symbol_short,
+  testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+  vec, Address, Env, IntoVal, Map, String, Val, Vec,
+};
+
+use crate::{
+  types::{
+    Dataset, ExternalDataProviderError, PageRankMode, PageRankParams, PageRankStatus,
+    DATA_TTL_EXTEND_TO,
+  },
+  DataKey, ExternalDataProvider, ExternalDataProviderClient, ReputationCategory,
+};
 
 fn initialize_external_data_provider(env: &Env) -> ExternalDataProviderClient {
+  env.mock_all_auths();
+
   let external_data_provider_id = env.register_contract(None, ExternalDataProvider);
   let external_data_provider_client =
     ExternalDataProviderClient::new(&env, &external_data_provider_id);
+  external_data_provider_client.initialize(&Address::generate(env));
 
   external_data_provider_client
 }
 
+// the event published last, as returned by env.events().all()
+fn last_event(env: &Env) -> Vec<(Address, Vec<Val>, Val)> {
+  let events = env.events().all();
+  events.slice(events.len() - 1..)
+}
+
 #[test]
 pub fn test_reputation() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
@@ -60,20 +80,36 @@ pub fn test_reputation() {
 
   let mut reputation_categories = external_data_provider_client.get_reputation_categories();
   reputation_categories.set(user_id_1.clone(), ReputationCategory::Poor);
-  external_data_provider_client.set_user_reputation_categories(&reputation_categories);
+  external_data_provider_client.set_user_reputation_categories(&owner, &reputation_categories);
 
   let reputation = external_data_provider_client.get_user_reputation_category(&user_id_1);
   assert!(reputation == ReputationCategory::Poor);
 
-  external_data_provider_client
-    .set_user_reputation_category(&user_id_1, &String::from_slice(&env, "Good"));
+  external_data_provider_client.set_user_reputation_category(
+    &owner,
+    &user_id_1,
+    &String::from_slice(&env, "Good"),
+  );
   let reputation = external_data_provider_client.get_user_reputation_category(&user_id_1);
   assert!(reputation == ReputationCategory::Good);
 
+  // unknown categories are rejected instead of being stored as Uncategorized
+  assert!(
+    external_data_provider_client.try_set_user_reputation_category(
+      &owner,
+      &user_id_1,
+      &String::from_slice(&env, "Great"),
+    ) == Err(Ok(ExternalDataProviderError::UnknownReputationCategory))
+  );
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_1)
+      == ReputationCategory::Good
+  );
+
   let mut users_reputation_categories: Map<String, String> = Map::new(&env);
   users_reputation_categories.set(user_id_1.clone(), String::from_slice(&env, "Poor"));
   users_reputation_categories.set(user_id_2.clone(), String::from_slice(&env, "Good"));
-  external_data_provider_client.set_users_rep_categories(&users_reputation_categories);
+  external_data_provider_client.set_users_rep_categories(&owner, &users_reputation_categories);
 
   assert!(
     external_data_provider_client.get_user_reputation_category(&user_id_1)
@@ -89,7 +125,8 @@ pub fn test_reputation() {
   for (user_id, rep_category) in users_reputation_categories {
     users_reputation_categories_vec.push_back((user_id.clone(), rep_category.clone()));
   }
-  external_data_provider_client.set_users_rep_categories_vec(&users_reputation_categories_vec);
+  external_data_provider_client
+    .set_users_rep_categories_vec(&owner, &users_reputation_categories_vec);
   assert!(
     external_data_provider_client.get_user_reputation_category(&user_id_1)
       == ReputationCategory::Poor
@@ -105,6 +142,7 @@ pub fn test_history() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
@@ -121,7 +159,7 @@ pub fn test_history() {
   let mut round_bonus_map = external_data_provider_client.get_round_bonus_map();
   assert!(round_bonus_map.len() == 4);
   round_bonus_map.set(5, (0, 400));
-  external_data_provider_client.set_round_bonus_map(&round_bonus_map);
+  external_data_provider_client.set_round_bonus_map(&owner, &round_bonus_map);
   let fetched_bonus_map = external_data_provider_client.get_round_bonus_map();
   assert!(fetched_bonus_map.len() == 5);
 
@@ -130,7 +168,7 @@ pub fn test_history() {
   for (round, bonus) in round_bonus_map {
     round_bonus_map_vec.push_back((round.clone(), DecimalNumberWrapper::from(bonus).as_raw()));
   }
-  external_data_provider_client.set_round_bonus_map_vec(&round_bonus_map_vec);
+  external_data_provider_client.set_round_bonus_map_vec(&owner, &round_bonus_map_vec);
   let fetched_bonus_map2 = external_data_provider_client.get_round_bonus_map();
   assert!(fetched_bonus_map == fetched_bonus_map2);
 
@@ -140,8 +178,11 @@ pub fn test_history() {
       .len()
       == 2
   );
-  external_data_provider_client
-    .set_user_prior_voting_history(&user_id_2, &Vec::from_slice(&env.clone(), &[1, 3, 4]));
+  external_data_provider_client.set_user_prior_voting_history(
+    &owner,
+    &user_id_2,
+    &Vec::from_slice(&env.clone(), &[1, 3, 4]),
+  );
   assert!(
     external_data_provider_client
       .get_prior_voting_history()
@@ -153,19 +194,80 @@ pub fn test_history() {
     external_data_provider_client.get_user_prior_voting_history(&user_id_1)
       == Vec::from_slice(&env, &[2, 3])
   );
-  external_data_provider_client
-    .set_user_prior_voting_history(&user_id_1, &Vec::from_slice(&env, &[3, 4]));
+  external_data_provider_client.set_user_prior_voting_history(
+    &owner,
+    &user_id_1,
+    &Vec::from_slice(&env, &[3, 4]),
+  );
   assert!(
     external_data_provider_client.get_user_prior_voting_history(&user_id_1)
       == Vec::from_slice(&env, &[3, 4])
   );
 }
 
+#[test]
+pub fn test_expertise() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_3 = String::from_slice(&env, "user003");
+  let infrastructure = String::from_slice(&env, "Infrastructure");
+  let education = String::from_slice(&env, "Education");
+
+  assert!(external_data_provider_client
+    .get_user_expertise(&user_id_1)
+    .is_empty());
+
+  external_data_provider_client.mock_sample_data();
+  assert!(external_data_provider_client.get_expertise().len() == 2);
+  assert!(
+    external_data_provider_client.get_user_expertise(&user_id_1)
+      == vec![&env, infrastructure.clone()]
+  );
+
+  external_data_provider_client.set_user_expertise(
+    &owner,
+    &user_id_3,
+    &vec![&env, education.clone(), infrastructure.clone()],
+  );
+  assert!(external_data_provider_client.get_expertise().len() == 3);
+  assert!(
+    external_data_provider_client.get_user_expertise(&user_id_3)
+      == vec![&env, education.clone(), infrastructure.clone()]
+  );
+
+  // replaces the whole dataset
+  external_data_provider_client.set_users_expertise(
+    &owner,
+    &Map::from_array(&env, [(user_id_3.clone(), vec![&env, education.clone()])]),
+  );
+  assert!(external_data_provider_client.get_expertise().len() == 1);
+  assert!(external_data_provider_client
+    .get_user_expertise(&user_id_1)
+    .is_empty());
+
+  // expertise is curated separately from the other datasets
+  let curator = Address::generate(&env);
+  external_data_provider_client.add_writer(&Dataset::Expertise, &curator);
+  external_data_provider_client.set_user_expertise(&curator, &user_id_1, &vec![&env, education]);
+  assert!(
+    external_data_provider_client.try_set_user_prior_voting_history(
+      &curator,
+      &user_id_1,
+      &vec![&env, 1]
+    ) == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+}
+
 #[test]
 pub fn test_delegation_rank() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
@@ -191,7 +293,7 @@ pub fn test_delegation_rank() {
   assert!(ranks.get(user_id_2.clone()).unwrap() == 2);
   assert!(ranks.get(user_id_3.clone()).unwrap() == 3);
 
-  external_data_provider_client.set_delegation_rank_for_user(&user_id_1.clone(), &15);
+  external_data_provider_client.set_delegation_rank_for_user(&owner, &user_id_1.clone(), &15);
 
   let ranks = external_data_provider_client.get_delegation_ranks_for_users(&vec![
     &env,
@@ -205,7 +307,7 @@ pub fn test_delegation_rank() {
   let mut users_ranks: Map<String, u32> = Map::new(&env);
   users_ranks.set(user_id_1.clone(), 3);
   users_ranks.set(user_id_99.clone(), 6);
-  external_data_provider_client.set_users_delegation_ranks(&users_ranks);
+  external_data_provider_client.set_users_delegation_ranks(&owner, &users_ranks);
 
   let ranks = external_data_provider_client.get_delegation_ranks_for_users(&vec![
     &env,
@@ -221,7 +323,7 @@ pub fn test_delegation_rank() {
   for (user_id, rank) in users_ranks {
     users_ranks_vec.push_back((user_id.clone(), rank.clone()));
   }
-  external_data_provider_client.set_users_delegation_ranks_vec(&users_ranks_vec);
+  external_data_provider_client.set_users_delegation_ranks_vec(&owner, &users_ranks_vec);
   let ranks = external_data_provider_client.get_delegation_ranks_for_users(&vec![
     &env,
     user_id_1.clone(),
@@ -269,6 +371,7 @@ pub fn test_set_trust_map() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
@@ -278,7 +381,7 @@ pub fn test_set_trust_map() {
     user_id_1.clone(),
     Map::from_array(&env, [(user_id_2.clone(), ())]),
   );
-  external_data_provider_client.set_trust_map(&new_trust_map);
+  external_data_provider_client.set_trust_map(&owner, &new_trust_map);
 
   let trust_map = external_data_provider_client.get_trust_map();
   let user_1_map = trust_map.get(user_id_1.clone());
@@ -293,6 +396,7 @@ pub fn test_page_rank() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
@@ -328,60 +432,340 @@ pub fn test_page_rank() {
     user_id_4.clone(),
     Map::from_array(&env, [(user_id_1.clone(), ())]),
   );
-  external_data_provider_client.set_trust_map(&new_trust_map);
+  external_data_provider_client.set_trust_map(&owner, &new_trust_map);
+
+  // the result can only be overwritten by its writers
+  assert!(
+    external_data_provider_client.try_calculate_page_rank(&Address::generate(&env))
+      == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+  assert!(external_data_provider_client
+    .get_page_rank_results()
+    .is_empty());
 
-  external_data_provider_client.calculate_page_rank();
+  external_data_provider_client.calculate_page_rank(&owner);
   let results = external_data_provider_client.get_page_rank_results();
 
-  assert!(results.get(user_id_1.clone()).unwrap() == (0, 415));
-  assert!(results.get(user_id_2.clone()).unwrap() == (0, 303));
-  assert!(results.get(user_id_3.clone()).unwrap() == (0, 213));
-  assert!(results.get(user_id_4.clone()).unwrap() == (0, 37));
+  assert!(results.get(user_id_1.clone()).unwrap() == 429_208_863);
+  assert!(results.get(user_id_2.clone()).unwrap() == 313_377_193);
+  assert!(results.get(user_id_3.clone()).unwrap() == 219_913_928);
+  assert!(results.get(user_id_4.clone()).unwrap() == 37_500_000);
 
   // values above might change if you modify the algorithm but the stuff below should always remain the same
   assert!(results.get(user_id_1.clone()).unwrap() > results.get(user_id_2.clone()).unwrap());
   assert!(results.get(user_id_2.clone()).unwrap() > results.get(user_id_3.clone()).unwrap());
   assert!(results.get(user_id_3.clone()).unwrap() > results.get(user_id_4.clone()).unwrap());
 
-  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_1) == (0, 415));
-
-  external_data_provider_client.set_page_rank_result(&Map::from_array(
-    &env,
-    [
-      (user_id_1.clone(), (0, 100)),
-      (user_id_2.clone(), (0, 200)),
-      (user_id_3.clone(), (0, 300)),
-      (user_id_4.clone(), (0, 400)),
-    ],
-  ));
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_1) == 429_208_863);
+
+  external_data_provider_client.set_page_rank_result(
+    &owner,
+    &Map::from_array(
+      &env,
+      [
+        (user_id_1.clone(), (0, 100)),
+        (user_id_2.clone(), (0, 200)),
+        (user_id_3.clone(), (0, 300)),
+        (user_id_4.clone(), (0, 400)),
+      ],
+    ),
+  );
 
   assert!(
     external_data_provider_client
       .get_page_rank_results()
       .get(user_id_1.clone())
       .unwrap()
-      == (0, 100)
+      == 100_000_000
+  );
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_2) == 200_000_000);
+
+  external_data_provider_client.set_page_rank_result_vec(
+    &owner,
+    &Vec::from_array(
+      &env,
+      [
+        (user_id_1.clone(), 100),
+        (user_id_2.clone(), 200),
+        (user_id_3.clone(), 300),
+        (user_id_4.clone(), 400),
+      ],
+    ),
   );
-  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_2) == (0, 200));
-
-  external_data_provider_client.set_page_rank_result_vec(&Vec::from_array(
-    &env,
-    [
-      (user_id_1.clone(), 100),
-      (user_id_2.clone(), 200),
-      (user_id_3.clone(), 300),
-      (user_id_4.clone(), 400),
-    ],
-  ));
 
   assert!(
     external_data_provider_client
       .get_page_rank_results()
       .get(user_id_1.clone())
       .unwrap()
-      == (0, 100)
+      == 100_000_000
+  );
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_2) == 200_000_000);
+}
+
+#[test]
+pub fn test_page_rank_with_params() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let outsider = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+  let user_id_3 = String::from_slice(&env, "user003");
+  /*
+  1->2
+  2->1
+  2->3
+
+  3 - trusts noone
+  */
+  external_data_provider_client.set_trust_map_for_user_vec(
+    &owner,
+    &user_id_1,
+    &Vec::from_array(&env, [user_id_2.clone()]),
+  );
+  external_data_provider_client.set_trust_map_for_user_vec(
+    &owner,
+    &user_id_2,
+    &Vec::from_array(&env, [user_id_1.clone(), user_id_3.clone()]),
+  );
+
+  let results =
+    external_data_provider_client.calculate_page_rank_with_params(&owner, &100, &(0, 850), &1_000);
+  assert!(results.get(user_id_1.clone()).unwrap() == 303_191_420);
+  assert!(results.get(user_id_2.clone()).unwrap() == 393_617_142);
+  assert!(results.get(user_id_3.clone()).unwrap() == 303_191_420);
+  assert!(external_data_provider_client.get_page_rank_results() == results);
+
+  // without damping the trust doesn't matter
+  external_data_provider_client.calculate_page_rank_with_params(&owner, &100, &(0, 0), &0);
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_2) == 333_333_333);
+
+  assert!(
+    external_data_provider_client.try_calculate_page_rank_with_params(&owner, &100, &(1, 500), &0)
+      == Err(Ok(ExternalDataProviderError::InvalidPageRankParams))
+  );
+  assert!(
+    external_data_provider_client.try_calculate_page_rank_with_params(
+      &outsider,
+      &100,
+      &(0, 850),
+      &0
+    ) == Err(Ok(ExternalDataProviderError::Unauthorized))
   );
-  assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id_2) == (0, 200));
+}
+
+#[test]
+pub fn test_resumable_page_rank() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let outsider = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+  let user_id_3 = String::from_slice(&env, "user003");
+  let user_id_4 = String::from_slice(&env, "user004");
+  let mut new_trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+  new_trust_map.set(
+    user_id_1.clone(),
+    Map::from_array(&env, [(user_id_2.clone(), ()), (user_id_3.clone(), ())]),
+  );
+  new_trust_map.set(
+    user_id_2.clone(),
+    Map::from_array(&env, [(user_id_1.clone(), ())]),
+  );
+  new_trust_map.set(
+    user_id_3.clone(),
+    Map::from_array(&env, [(user_id_1.clone(), ()), (user_id_4.clone(), ())]),
+  );
+  external_data_provider_client.set_trust_map(&owner, &new_trust_map);
+
+  let params = PageRankParams {
+    iterations: 1000,
+    damping_factor: (0, 850),
+    tolerance: 1_000,
+  };
+  assert!(external_data_provider_client
+    .get_page_rank_status()
+    .is_none());
+  assert!(
+    external_data_provider_client.try_step_page_rank(&5)
+      == Err(Ok(ExternalDataProviderError::PageRankNotStarted))
+  );
+  assert!(
+    external_data_provider_client.try_start_page_rank(&outsider, &params)
+      == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+  assert!(
+    external_data_provider_client.try_start_page_rank(
+      &owner,
+      &PageRankParams {
+        tolerance: -1,
+        ..params.clone()
+      }
+    ) == Err(Ok(ExternalDataProviderError::InvalidPageRankParams))
+  );
+
+  let status = external_data_provider_client.start_page_rank(&owner, &params);
+  assert!(
+    status
+      == PageRankStatus {
+        params: params.clone(),
+        users_count: 4,
+        iteration: 0,
+        last_delta: None,
+        finished: false,
+        started_by: owner.clone(),
+      }
+  );
+  assert!(external_data_provider_client.get_page_rank_status() == Some(status));
+  assert!(
+    external_data_provider_client.try_finalize_page_rank(&owner)
+      == Err(Ok(ExternalDataProviderError::PageRankNotFinished))
+  );
+
+  let status = external_data_provider_client.step_page_rank(&5);
+  assert!(status.iteration == 5);
+  assert!(status.last_delta.unwrap() > params.tolerance);
+  assert!(!status.finished);
+
+  // the keeper keeps stepping until it's finished, every step continues where the last one ended
+  let mut steps = 1;
+  while !external_data_provider_client.step_page_rank(&5).finished {
+    steps += 1;
+  }
+  let status = external_data_provider_client
+    .get_page_rank_status()
+    .unwrap();
+  assert!(status.last_delta.unwrap() <= params.tolerance);
+  assert!(status.iteration > 5 && status.iteration <= (steps + 1) * 5);
+  // stepping a finished calculation doesn't change it
+  assert!(external_data_provider_client.step_page_rank(&5) == status);
+
+  // only the writer who started it can store the result
+  let writer = Address::generate(&env);
+  external_data_provider_client.add_writer(&Dataset::PageRankResult, &writer);
+  for caller in [outsider.clone(), writer] {
+    assert!(
+      external_data_provider_client.try_finalize_page_rank(&caller)
+        == Err(Ok(ExternalDataProviderError::Unauthorized))
+    );
+  }
+
+  let results = external_data_provider_client.finalize_page_rank(&owner);
+  assert!(external_data_provider_client.get_page_rank_results() == results);
+  assert!(external_data_provider_client
+    .get_page_rank_status()
+    .is_none());
+  assert!(
+    external_data_provider_client.try_finalize_page_rank(&owner)
+      == Err(Ok(ExternalDataProviderError::PageRankNotStarted))
+  );
+
+  // the same as calculating it at once
+  assert!(
+    external_data_provider_client.calculate_page_rank_with_params(
+      &owner,
+      &params.iterations,
+      &params.damping_factor,
+      &params.tolerance
+    ) == results
+  );
+}
+
+#[test]
+pub fn test_personalized_page_rank() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let curator = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+  let sybil_id_1 = String::from_slice(&env, "sybil001");
+  let sybil_id_2 = String::from_slice(&env, "sybil002");
+  /*
+  1->2
+  2->1
+  sybil1->sybil2
+  sybil2->sybil1
+  */
+  let mut new_trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+  new_trust_map.set(
+    user_id_1.clone(),
+    Map::from_array(&env, [(user_id_2.clone(), ())]),
+  );
+  new_trust_map.set(
+    user_id_2.clone(),
+    Map::from_array(&env, [(user_id_1.clone(), ())]),
+  );
+  new_trust_map.set(
+    sybil_id_1.clone(),
+    Map::from_array(&env, [(sybil_id_2.clone(), ())]),
+  );
+  new_trust_map.set(
+    sybil_id_2.clone(),
+    Map::from_array(&env, [(sybil_id_1.clone(), ())]),
+  );
+  external_data_provider_client.set_trust_map(&owner, &new_trust_map);
+
+  assert!(external_data_provider_client.get_page_rank_mode() == PageRankMode::Standard);
+  let results = external_data_provider_client.calculate_page_rank(&owner);
+  assert!(results.get(sybil_id_1.clone()).unwrap() == 250_000_000);
+
+  external_data_provider_client.set_page_rank_mode(&PageRankMode::Personalized);
+  assert!(external_data_provider_client.get_page_rank_mode() == PageRankMode::Personalized);
+  assert!(
+    external_data_provider_client.try_calculate_page_rank(&owner)
+      == Err(Ok(ExternalDataProviderError::NoTrustAnchors))
+  );
+
+  // the anchors are curated separately from the trust map
+  external_data_provider_client.add_writer(&Dataset::TrustAnchors, &curator);
+  // only the owner chooses the mode, not even a writer of the page rank results
+  external_data_provider_client.add_writer(&Dataset::PageRankResult, &curator);
+  env.mock_auths(&[MockAuth {
+    address: &curator,
+    invoke: &MockAuthInvoke {
+      contract: &external_data_provider_client.address,
+      fn_name: "set_page_rank_mode",
+      args: (PageRankMode::Standard,).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(external_data_provider_client
+    .try_set_page_rank_mode(&PageRankMode::Standard)
+    .is_err());
+  env.mock_all_auths();
+  assert!(external_data_provider_client.get_page_rank_mode() == PageRankMode::Personalized);
+  external_data_provider_client
+    .set_trust_anchors(&curator, &Vec::from_array(&env, [user_id_1.clone()]));
+  assert!(
+    external_data_provider_client.get_trust_anchors() == Vec::from_array(&env, [user_id_1.clone()])
+  );
+
+  let results = external_data_provider_client.calculate_page_rank(&owner);
+  assert!(results.get(user_id_1.clone()).unwrap() == 540_540_742);
+  assert!(results.get(user_id_2.clone()).unwrap() == 459_459_252);
+  assert!(results.get(sybil_id_1.clone()).unwrap() == 0);
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&sybil_id_2) == 0);
+
+  // the resumable calculation uses the mode too
+  external_data_provider_client.start_page_rank(
+    &owner,
+    &PageRankParams {
+      iterations: 1000,
+      damping_factor: (0, 850),
+      tolerance: 1_000,
+    },
+  );
+  while !external_data_provider_client.step_page_rank(&10).finished {}
+  assert!(external_data_provider_client.finalize_page_rank(&owner) == results);
 }
 
 #[test]
@@ -389,11 +773,13 @@ pub fn test_set_trust_map_for_user() {
   let env = Env::default();
 
   let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
 
   let user_id_1 = String::from_slice(&env, "user001");
   let user_id_2 = String::from_slice(&env, "user002");
 
   external_data_provider_client.set_trust_map_for_user(
+    &owner,
     &user_id_1,
     &Map::from_array(&env, [(user_id_2.clone(), ())]),
   );
@@ -406,8 +792,11 @@ pub fn test_set_trust_map_for_user() {
   assert!(user_2_map.is_none());
 
   // test set_trust_map_for_user_vec
-  external_data_provider_client
-    .set_trust_map_for_user_vec(&user_id_1, &Vec::from_array(&env, [user_id_2.clone()]));
+  external_data_provider_client.set_trust_map_for_user_vec(
+    &owner,
+    &user_id_1,
+    &Vec::from_array(&env, [user_id_2.clone()]),
+  );
 
   let trust_map = external_data_provider_client.get_trust_map();
   let user_1_map = trust_map.get(user_id_1.clone());
@@ -416,3 +805,400 @@ pub fn test_set_trust_map_for_user() {
   assert!(user_1_map == Some(Map::from_array(&env, [(user_id_2.clone(), ()),])));
   assert!(user_2_map.is_none());
 }
+
+#[test]
+pub fn test_trust_weights() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let outsider = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+  let user_id_3 = String::from_slice(&env, "user003");
+
+  // 1 trusts 3 three times as much as 2, 2 and 3 trust 1
+  let weights = Map::from_array(
+    &env,
+    [(user_id_2.clone(), (0, 500)), (user_id_3.clone(), (1, 500))],
+  );
+  assert!(
+    external_data_provider_client.set_trust_weights_for_user(&owner, &user_id_1, &weights)
+      == weights
+  );
+  assert!(
+    external_data_provider_client.get_trust_map_for_user(&user_id_1)
+      == Map::from_array(&env, [(user_id_2.clone(), ()), (user_id_3.clone(), ())])
+  );
+  external_data_provider_client.set_trust_map_for_user_vec(
+    &owner,
+    &user_id_2,
+    &Vec::from_array(&env, [user_id_1.clone()]),
+  );
+  external_data_provider_client.set_trust_weights_for_user_vec(
+    &owner,
+    &user_id_3,
+    &Vec::from_array(&env, [(user_id_1.clone(), 2000)]),
+  );
+  assert!(
+    external_data_provider_client.get_trust_weights_for_user(&user_id_3)
+      == Map::from_array(&env, [(user_id_1.clone(), (2, 0))])
+  );
+
+  let results = external_data_provider_client.calculate_page_rank(&owner);
+  assert!(results.get(user_id_1.clone()).unwrap() == 486_486_688);
+  assert!(results.get(user_id_2.clone()).unwrap() == 153_378_323);
+  assert!(results.get(user_id_3.clone()).unwrap() == 360_134_969);
+
+  // the unweighted setters give every trusted user the weight 1
+  external_data_provider_client.set_trust_map_for_user(
+    &owner,
+    &user_id_1,
+    &Map::from_array(&env, [(user_id_2.clone(), ()), (user_id_3.clone(), ())]),
+  );
+  assert!(
+    external_data_provider_client.get_trust_weights_for_user(&user_id_1)
+      == Map::from_array(
+        &env,
+        [(user_id_2.clone(), (1, 0)), (user_id_3.clone(), (1, 0))]
+      )
+  );
+  let results = external_data_provider_client.calculate_page_rank(&owner);
+  assert!(results.get(user_id_2.clone()).unwrap() == results.get(user_id_3.clone()).unwrap());
+
+  assert!(
+    external_data_provider_client.try_set_trust_weights_for_user(
+      &owner,
+      &user_id_1,
+      &Map::from_array(&env, [(user_id_2.clone(), (0, 0))])
+    ) == Err(Ok(ExternalDataProviderError::InvalidTrustWeight))
+  );
+  assert!(
+    external_data_provider_client.try_set_trust_weights_for_user(
+      &owner,
+      &user_id_1,
+      &Map::from_array(&env, [(user_id_2.clone(), (0, 1000))])
+    ) == Err(Ok(ExternalDataProviderError::InvalidTrustWeight))
+  );
+  assert!(
+    external_data_provider_client.get_trust_weights_for_user(&user_id_1)
+      == Map::from_array(
+        &env,
+        [(user_id_2.clone(), (1, 0)), (user_id_3.clone(), (1, 0))]
+      )
+  );
+  assert!(
+    external_data_provider_client.try_set_trust_weights_for_user_vec(
+      &outsider,
+      &user_id_1,
+      &Vec::from_array(&env, [(user_id_2.clone(), 1000)])
+    ) == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+}
+
+#[test]
+pub fn test_writer_roles() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let curator = Address::generate(&env);
+  let outsider = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+  let good = String::from_slice(&env, "Good");
+
+  assert!(
+    external_data_provider_client.try_initialize(&outsider)
+      == Err(Ok(ExternalDataProviderError::AlreadyInitialized))
+  );
+
+  // the reputation curator can only touch the reputation dataset
+  external_data_provider_client.add_writer(&Dataset::Reputation, &curator);
+  assert!(
+    external_data_provider_client.get_writers(&Dataset::Reputation)
+      == Vec::from_array(&env, [curator.clone()])
+  );
+  assert!(external_data_provider_client
+    .get_writers(&Dataset::TrustMap)
+    .is_empty());
+
+  external_data_provider_client.set_user_reputation_category(&curator, &user_id_1, &good);
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_1)
+      == ReputationCategory::Good
+  );
+  assert!(
+    external_data_provider_client.try_set_trust_map_for_user_vec(
+      &curator,
+      &user_id_1,
+      &Vec::from_array(&env, [user_id_2.clone()])
+    ) == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+  assert!(
+    external_data_provider_client.try_set_user_reputation_category(&outsider, &user_id_2, &good)
+      == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+
+  // the curator's address can't be used without their signature
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &external_data_provider_client.address,
+      fn_name: "set_user_reputation_category",
+      args: (curator.clone(), user_id_2.clone(), good.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(external_data_provider_client
+    .try_set_user_reputation_category(&curator, &user_id_2, &good)
+    .is_err());
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_2)
+      == ReputationCategory::Uncategorized
+  );
+
+  // only the owner manages the writers
+  env.mock_auths(&[MockAuth {
+    address: &curator,
+    invoke: &MockAuthInvoke {
+      contract: &external_data_provider_client.address,
+      fn_name: "add_writer",
+      args: (Dataset::TrustMap, curator.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(external_data_provider_client
+    .try_add_writer(&Dataset::TrustMap, &curator)
+    .is_err());
+
+  env.mock_all_auths();
+  external_data_provider_client.remove_writer(&Dataset::Reputation, &curator);
+  assert!(
+    external_data_provider_client.try_set_user_reputation_category(&curator, &user_id_2, &good)
+      == Err(Ok(ExternalDataProviderError::Unauthorized))
+  );
+  // the owner can write every dataset
+  external_data_provider_client.set_user_reputation_category(&owner, &user_id_2, &good);
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_2)
+      == ReputationCategory::Good
+  );
+}
+
+#[test]
+pub fn test_persistent_datasets() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+
+  external_data_provider_client.set_user_reputation_category(
+    &owner,
+    &user_id_1,
+    &String::from_slice(&env, "Good"),
+  );
+  external_data_provider_client.set_user_reputation_category(
+    &owner,
+    &user_id_2,
+    &String::from_slice(&env, "Poor"),
+  );
+
+  // every user has its own persistent entry
+  env.as_contract(&external_data_provider_client.address, || {
+    assert!(
+      env
+        .storage()
+        .persistent()
+        .get(&DataKey::UserData(Dataset::Reputation, user_id_1.clone()))
+        == Some(ReputationCategory::Good)
+    );
+    assert!(env
+      .storage()
+      .persistent()
+      .has(&DataKey::UserData(Dataset::Reputation, user_id_2.clone())));
+  });
+  assert!(
+    external_data_provider_client
+      .get_reputation_categories()
+      .len()
+      == 2
+  );
+
+  // setting the whole dataset drops the users who are not in it anymore
+  external_data_provider_client.set_user_reputation_categories(
+    &owner,
+    &Map::from_array(&env, [(user_id_1.clone(), ReputationCategory::Excellent)]),
+  );
+  assert!(
+    external_data_provider_client
+      .get_reputation_categories()
+      .len()
+      == 1
+  );
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_2)
+      == ReputationCategory::Uncategorized
+  );
+  env.as_contract(&external_data_provider_client.address, || {
+    assert!(!env
+      .storage()
+      .persistent()
+      .has(&DataKey::UserData(Dataset::Reputation, user_id_2.clone())));
+  });
+
+  // the data survives as long as its TTL is extended
+  external_data_provider_client.extend_dataset_ttl(&Dataset::Reputation);
+  env
+    .ledger()
+    .with_mut(|li| li.sequence_number = DATA_TTL_EXTEND_TO - 1);
+  external_data_provider_client.extend_dataset_ttl(&Dataset::Reputation);
+  external_data_provider_client
+    .extend_users_ttl(&Dataset::Reputation, &vec![&env, user_id_1.clone()]);
+  env
+    .ledger()
+    .with_mut(|li| li.sequence_number = 2 * DATA_TTL_EXTEND_TO - 2);
+  assert!(
+    external_data_provider_client.get_user_reputation_category(&user_id_1)
+      == ReputationCategory::Excellent
+  );
+  assert!(
+    external_data_provider_client
+      .get_reputation_categories()
+      .len()
+      == 1
+  );
+}
+
+#[test]
+pub fn test_events() {
+  let env = Env::default();
+
+  let external_data_provider_client = initialize_external_data_provider(&env);
+  let owner = external_data_provider_client.get_owner();
+  let contract_id = external_data_provider_client.address.clone();
+  let writer = Address::generate(&env);
+
+  let user_id_1 = String::from_slice(&env, "user001");
+  let user_id_2 = String::from_slice(&env, "user002");
+
+  external_data_provider_client.add_writer(&Dataset::TrustMap, &writer);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("writer"), symbol_short!("added")).into_val(&env),
+          (Dataset::TrustMap, writer.clone()).into_val(&env),
+        )
+      ]
+  );
+
+  external_data_provider_client.set_delegation_rank_for_user(&owner, &user_id_1, &3);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("dataset"), symbol_short!("updated")).into_val(&env),
+          (Dataset::DelegationRanks, user_id_1.clone()).into_val(&env),
+        )
+      ]
+  );
+
+  external_data_provider_client.set_trust_map(
+    &writer,
+    &Map::from_array(
+      &env,
+      [
+        (
+          user_id_1.clone(),
+          Map::from_array(&env, [(user_id_2.clone(), ())]),
+        ),
+        (
+          user_id_2.clone(),
+          Map::from_array(&env, [(user_id_1.clone(), ())]),
+        ),
+      ],
+    ),
+  );
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("dataset"), symbol_short!("replaced")).into_val(&env),
+          (
+            Dataset::TrustMap,
+            vec![&env, user_id_1.clone(), user_id_2.clone()]
+          )
+            .into_val(&env),
+        )
+      ]
+  );
+
+  // every update of page rank results is published as well
+  external_data_provider_client.calculate_page_rank(&owner);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("dataset"), symbol_short!("replaced")).into_val(&env),
+          (
+            Dataset::PageRankResult,
+            vec![&env, user_id_1.clone(), user_id_2.clone()]
+          )
+            .into_val(&env),
+        )
+      ]
+  );
+
+  external_data_provider_client.set_page_rank_mode(&PageRankMode::Personalized);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("pagerank"), symbol_short!("mode")).into_val(&env),
+          PageRankMode::Personalized.into_val(&env),
+        )
+      ]
+  );
+
+  external_data_provider_client.set_round_bonus_map_vec(&owner, &vec![&env, (1, 100)]);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("dataset"), symbol_short!("replaced")).into_val(&env),
+          (Dataset::RoundBonusMap, Vec::<String>::new(&env)).into_val(&env),
+        )
+      ]
+  );
+
+  external_data_provider_client.remove_writer(&Dataset::TrustMap, &writer);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id,
+          (symbol_short!("writer"), symbol_short!("removed")).into_val(&env),
+          (Dataset::TrustMap, writer).into_val(&env),
+        )
+      ]
+  );
+}
@@ -1,229 +1,232 @@
 #![no_std]
 #![allow(non_upper_case_globals)]
 
+mod datasets;
+mod events;
 mod page_rank;
 pub mod types;
 
-use page_rank::Rank;
+use page_rank::{PageRankGraph, Rank};
 
 // This contract's going to be responsible for fetching the data from any external resources
 
 use soroban_decimal_numbers::DecimalNumberWrapper;
-use soroban_sdk::{contract, contractimpl, contracttype, vec, Env, Map, String, Vec};
-use types::{reputation_category_from_str, ReputationCategory};
+use soroban_sdk::{
+  contract, contractimpl, contracttype, vec, Address, Env, IntoVal, Map, String, TryFromVal, Val,
+  Vec,
+};
+use types::{
+  reputation_category_from_str, Dataset, ExternalDataProviderError, PageRankDecimal, PageRankMode,
+  PageRankParams, PageRankStatus, PageRankValue, ReputationCategory, DATA_TTL_EXTEND_TO,
+  DATA_TTL_THRESHOLD,
+};
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
-  // storage type: instance
-  // Map<UserUUID, ReputationCategory> - users to their categories
-  Reputation,
-  // storage type: instance
-  // Map<UserUUID, Vec<u32>> - users to the vector of rounds they participated in
-  PriorVotingHistory,
+  // storage type: persistent
+  // (Dataset, UserUUID) => value of the dataset for the user:
+  // Reputation - ReputationCategory
+  // PriorVotingHistory - Vec<u32>, rounds the user participated in
+  // DelegationRanks - u32
+  // TrustMap - Map<UserUUID, ()>, users trusted by the user
+  // PageRankResult - PageRankValue, a raw PageRankDecimal number (9 decimal places)
+  // Expertise - Vec<String>, submission categories the user is an expert in
+  // TrustAnchors - (), the user is an anchor of personalized page rank
+  // TrustWeights - Map<UserUUID, DecimalNumber>, weights of the users trusted by the user, only
+  // if they are set with set_trust_weights_for_user, otherwise every weight is 1
+  UserData(Dataset, String),
+  // storage type: persistent
+  // Map<UserUUID, ()> - users who have a value in the dataset
+  DatasetUsers(Dataset),
   // storage type: instance
   // Map<u32, DecimalNumber> - (connected to PRIOR_VOTING_HISTORY) rounds to their bonus (for participation)
   RoundBonusMap,
   // storage type: instance
-  // Map<UserUUID, u32> - users to their delegation rank
-  DelegationRanks,
+  // Address - can write every dataset and manage the writers
+  Owner,
   // storage type: instance
-  // Map<String, Map<String, ()>> - users to their delegation rank
-  TrustMap,
+  // Map<Dataset, Map<Address, ()>> - datasets to the addresses allowed to write them
+  Writers,
   // storage type: instance
-  // (u32, u32)
-  PageRankResult,
+  // PageRankMode - Standard unless it's set
+  PageRankMode,
+  // storage type: persistent
+  // PageRankStatus - the page rank calculation in progress, see `start_page_rank`
+  PageRankStatus,
+  // storage type: persistent
+  // PageRankGraph - the trust graph of the calculation in progress, indexed when it was started
+  PageRankGraph,
+  // storage type: persistent
+  // Vec<PageRankValue> - the ranks after the last iteration, in the order of the graph's users
+  PageRankRanks,
 }
 
 #[contract]
 pub struct ExternalDataProvider;
 
-impl ExternalDataProvider {}
-
 #[contractimpl]
 impl ExternalDataProvider {
-  fn generate_username(env: &Env, n: u32) -> String {
-    match n {
-      1 => String::from_slice(&env, "user001"),
-      2 => String::from_slice(&env, "user002"),
-      3 => String::from_slice(&env, "user003"),
-      4 => String::from_slice(&env, "user004"),
-      5 => String::from_slice(&env, "user005"),
-      6 => String::from_slice(&env, "user006"),
-      7 => String::from_slice(&env, "user007"),
-      8 => String::from_slice(&env, "user008"),
-      9 => String::from_slice(&env, "user009"),
-      _ => String::from_slice(&env, "userXXX"),
+  pub fn initialize(env: Env, owner: Address) -> Result<(), ExternalDataProviderError> {
+    if env.storage().instance().has(&DataKey::Owner) {
+      return Err(ExternalDataProviderError::AlreadyInitialized);
     }
+    env.storage().instance().set(&DataKey::Owner, &owner);
+    Ok(())
   }
 
-  pub fn mock_data_assigned_reputation(env: Env) {
-    let mut reputation_map: Map<String, ReputationCategory> = Map::new(&env);
-    reputation_map.set(
-      ExternalDataProvider::generate_username(&env, 1),
-      ReputationCategory::Excellent,
-    );
-    reputation_map.set(
-      ExternalDataProvider::generate_username(&env, 2),
-      ReputationCategory::VeryGood,
-    );
-    reputation_map.set(
-      ExternalDataProvider::generate_username(&env, 3),
-      ReputationCategory::Good,
-    );
-    reputation_map.set(
-      ExternalDataProvider::generate_username(&env, 4),
-      ReputationCategory::Average,
-    );
-    reputation_map.set(
-      ExternalDataProvider::generate_username(&env, 5),
-      ReputationCategory::Poor,
-    );
+  pub fn get_owner(env: Env) -> Result<Address, ExternalDataProviderError> {
     env
       .storage()
       .instance()
-      .set(&DataKey::Reputation, &reputation_map);
+      .get(&DataKey::Owner)
+      .ok_or(ExternalDataProviderError::OwnerNotSet)
   }
 
-  pub fn mock_prior_voting_history(env: Env) {
-    // for prior history neuron
-    let mut voting_history_set: Map<String, Vec<u32>> = Map::new(&env);
-    voting_history_set.set(
-      ExternalDataProvider::generate_username(&env, 1),
-      vec![&env, 2, 3],
-    );
-    voting_history_set.set(
-      ExternalDataProvider::generate_username(&env, 3),
-      vec![&env, 2, 3, 4],
-    );
+  fn require_owner(env: &Env) -> Result<(), ExternalDataProviderError> {
+    let owner = ExternalDataProvider::get_owner(env.clone())?;
+    owner.require_auth();
+    Ok(())
+  }
+
+  // the caller has to be either the owner or a writer of the dataset
+  fn require_writer(
+    env: &Env,
+    caller: &Address,
+    dataset: Dataset,
+  ) -> Result<(), ExternalDataProviderError> {
+    caller.require_auth();
+    if *caller == ExternalDataProvider::get_owner(env.clone())? {
+      return Ok(());
+    }
+    let is_writer = ExternalDataProvider::get_all_writers(env.clone())
+      .get(dataset)
+      .unwrap_or(Map::new(env))
+      .contains_key(caller.clone());
+    if !is_writer {
+      return Err(ExternalDataProviderError::Unauthorized);
+    }
+    Ok(())
+  }
+
+  fn get_all_writers(env: Env) -> Map<Dataset, Map<Address, ()>> {
     env
       .storage()
       .instance()
-      .set(&DataKey::PriorVotingHistory, &voting_history_set);
+      .get(&DataKey::Writers)
+      .unwrap_or(Map::new(&env))
   }
 
-  pub fn mock_round_bonus_map(env: Env) {
-    let mut round_bonus_map: Map<u32, (u32, u32)> = Map::new(&env);
-    round_bonus_map.set(1, (0, 0));
-    round_bonus_map.set(2, (0, 100));
-    round_bonus_map.set(3, (0, 200));
-    round_bonus_map.set(4, (0, 300));
+  pub fn get_writers(env: Env, dataset: Dataset) -> Vec<Address> {
+    ExternalDataProvider::get_all_writers(env.clone())
+      .get(dataset)
+      .unwrap_or(Map::new(&env))
+      .keys()
+  }
+
+  pub fn add_writer(
+    env: Env,
+    dataset: Dataset,
+    writer: Address,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_owner(&env)?;
+    let mut all_writers = ExternalDataProvider::get_all_writers(env.clone());
+    let mut dataset_writers = all_writers.get(dataset).unwrap_or(Map::new(&env));
+    dataset_writers.set(writer.clone(), ());
+    all_writers.set(dataset, dataset_writers);
     env
       .storage()
       .instance()
-      .set(&DataKey::RoundBonusMap, &round_bonus_map);
+      .set(&DataKey::Writers, &all_writers);
+    events::writer_added(&env, dataset, writer);
+    Ok(())
   }
 
-  pub fn mock_delegation_ranks(env: Env) {
-    let mut delegation_ranks: Map<String, u32> = Map::new(&env);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 1), 1);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 2), 2);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 3), 3);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 4), 4);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 5), 5);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 6), 6);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 7), 7);
-    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 8), 8);
+  pub fn remove_writer(
+    env: Env,
+    dataset: Dataset,
+    writer: Address,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_owner(&env)?;
+    let mut all_writers = ExternalDataProvider::get_all_writers(env.clone());
+    let mut dataset_writers = all_writers.get(dataset).unwrap_or(Map::new(&env));
+    dataset_writers.remove(writer.clone());
+    all_writers.set(dataset, dataset_writers);
     env
       .storage()
       .instance()
-      .set(&DataKey::DelegationRanks, &delegation_ranks);
-  }
-
-  pub fn mock_trust_map(env: Env) {
-    let user001 = ExternalDataProvider::generate_username(&env, 1);
-    let user002 = ExternalDataProvider::generate_username(&env, 2);
-    let user003 = ExternalDataProvider::generate_username(&env, 3);
-    let user004 = ExternalDataProvider::generate_username(&env, 4);
-    // for trust graph neuron
-    let mut trust_map: Map<String, Map<String, ()>> = Map::new(&env);
-    trust_map.set(
-      user001.clone(),
-      // map[&env, user002.clone(), user004.clone()],
-      Map::from_array(&env, [(user002.clone(), ()), (user004.clone(), ())]),
-    );
-    trust_map.set(
-      user002.clone(),
-      Map::from_array(&env, [(user001.clone(), ())]),
-    );
-    trust_map.set(
-      user003.clone(),
-      Map::from_array(&env, [(user001.clone(), ()), (user002.clone(), ())]),
-    );
-    trust_map.set(
-      user004.clone(),
-      Map::from_array(&env, [(user003.clone(), ())]),
-    );
-    env.storage().instance().set(&DataKey::TrustMap, &trust_map);
-  }
-
-  pub fn mock_sample_data(env: Env) {
-    ExternalDataProvider::mock_data_assigned_reputation(env.clone());
-    ExternalDataProvider::mock_prior_voting_history(env.clone());
-    ExternalDataProvider::mock_round_bonus_map(env.clone());
-    ExternalDataProvider::mock_delegation_ranks(env.clone());
-    ExternalDataProvider::mock_trust_map(env.clone());
+      .set(&DataKey::Writers, &all_writers);
+    events::writer_removed(&env, dataset, writer);
+    Ok(())
   }
 
   // for assigned reputation neuron
   pub fn get_reputation_categories(env: Env) -> Map<String, ReputationCategory> {
-    env
-      .storage()
-      .instance()
-      .get(&DataKey::Reputation)
-      .unwrap_or(Map::new(&env))
+    datasets::get_all(&env, Dataset::Reputation)
   }
 
   pub fn get_user_reputation_category(env: Env, user_id: String) -> ReputationCategory {
-    ExternalDataProvider::get_reputation_categories(env.clone())
-      .get(user_id)
-      .unwrap_or(ReputationCategory::Uncategorized)
+    datasets::get(&env, Dataset::Reputation, user_id).unwrap_or(ReputationCategory::Uncategorized)
   }
 
-  pub fn set_user_reputation_categories(env: Env, reputation_map: Map<String, ReputationCategory>) {
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::Reputation, &reputation_map);
+  pub fn set_user_reputation_categories(
+    env: Env,
+    caller: Address,
+    reputation_map: Map<String, ReputationCategory>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Reputation)?;
+    datasets::replace(&env, Dataset::Reputation, reputation_map);
+    Ok(())
   }
 
-  pub fn set_user_reputation_category(env: Env, user_id: String, reputation_category: String) {
-    let mut reputation_categories = ExternalDataProvider::get_reputation_categories(env.clone());
-    reputation_categories.set(
+  pub fn set_user_reputation_category(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    reputation_category: String,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Reputation)?;
+    datasets::set(
+      &env,
+      Dataset::Reputation,
       user_id,
-      reputation_category_from_str(&env, reputation_category),
+      &reputation_category_from_str(&env, reputation_category)?,
     );
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::Reputation, &reputation_categories);
+    Ok(())
   }
 
-  pub fn set_users_rep_categories(env: Env, users_reputation_categories: Map<String, String>) {
-    let mut all_reputation_categories =
-      ExternalDataProvider::get_reputation_categories(env.clone());
+  pub fn set_users_rep_categories(
+    env: Env,
+    caller: Address,
+    users_reputation_categories: Map<String, String>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Reputation)?;
     for (user_id, category) in users_reputation_categories {
-      all_reputation_categories.set(user_id, reputation_category_from_str(&env, category));
+      datasets::set(
+        &env,
+        Dataset::Reputation,
+        user_id,
+        &reputation_category_from_str(&env, category)?,
+      );
     }
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::Reputation, &all_reputation_categories);
+    Ok(())
   }
 
   pub fn set_users_rep_categories_vec(
     env: Env,
+    caller: Address,
     users_reputation_categories: Vec<(String, String)>,
-  ) {
-    let mut all_reputation_categories =
-      ExternalDataProvider::get_reputation_categories(env.clone());
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Reputation)?;
     for (user_id, category) in users_reputation_categories {
-      all_reputation_categories.set(user_id, reputation_category_from_str(&env, category));
+      datasets::set(
+        &env,
+        Dataset::Reputation,
+        user_id,
+        &reputation_category_from_str(&env, category)?,
+      );
     }
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::Reputation, &all_reputation_categories);
+    Ok(())
   }
 
   pub fn get_reputation_score(reputation_category: ReputationCategory) -> (u32, u32) {
@@ -251,26 +254,27 @@ impl ExternalDataProvider {
 
   // for prior history neuron
   pub fn get_prior_voting_history(env: Env) -> Map<String, Vec<u32>> {
-    env
-      .storage()
-      .instance()
-      .get(&DataKey::PriorVotingHistory)
-      .unwrap_or(Map::new(&env))
+    datasets::get_all(&env, Dataset::PriorVotingHistory)
   }
 
   pub fn get_user_prior_voting_history(env: Env, user_id: String) -> Vec<u32> {
-    let voting_history_set: Map<String, Vec<u32>> =
-      ExternalDataProvider::get_prior_voting_history(env.clone());
-    voting_history_set.get(user_id).unwrap_or(vec![&env])
+    datasets::get(&env, Dataset::PriorVotingHistory, user_id).unwrap_or(vec![&env])
   }
 
-  pub fn set_user_prior_voting_history(env: Env, user_id: String, new_voting_history: Vec<u32>) {
-    let mut voting_history = ExternalDataProvider::get_prior_voting_history(env.clone());
-    voting_history.set(user_id, new_voting_history);
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::PriorVotingHistory, &voting_history);
+  pub fn set_user_prior_voting_history(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    new_voting_history: Vec<u32>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PriorVotingHistory)?;
+    datasets::set(
+      &env,
+      Dataset::PriorVotingHistory,
+      user_id,
+      &new_voting_history,
+    );
+    Ok(())
   }
 
   pub fn get_round_bonus_map(env: Env) -> Map<u32, (u32, u32)> {
@@ -282,14 +286,26 @@ impl ExternalDataProvider {
     round_bonus_map
   }
 
-  pub fn set_round_bonus_map(env: Env, round_bonus_map: Map<u32, (u32, u32)>) {
+  pub fn set_round_bonus_map(
+    env: Env,
+    caller: Address,
+    round_bonus_map: Map<u32, (u32, u32)>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::RoundBonusMap)?;
     env
       .storage()
       .instance()
       .set(&DataKey::RoundBonusMap, &round_bonus_map);
+    events::dataset_replaced(&env, Dataset::RoundBonusMap, Vec::new(&env));
+    Ok(())
   }
 
-  pub fn set_round_bonus_map_vec(env: Env, round_bonus_map: Vec<(u32, u32)>) {
+  pub fn set_round_bonus_map_vec(
+    env: Env,
+    caller: Address,
+    round_bonus_map: Vec<(u32, u32)>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::RoundBonusMap)?;
     let mut round_bonus_map_converted = Map::new(&env);
     for (key, val) in round_bonus_map {
       round_bonus_map_converted.set(key, DecimalNumberWrapper::from(val).as_tuple());
@@ -298,153 +314,605 @@ impl ExternalDataProvider {
       .storage()
       .instance()
       .set(&DataKey::RoundBonusMap, &round_bonus_map_converted);
+    events::dataset_replaced(&env, Dataset::RoundBonusMap, Vec::new(&env));
+    Ok(())
   }
 
   // for delegation
   pub fn get_delegation_ranks(env: Env) -> Map<String, u32> {
-    env
-      .storage()
-      .instance()
-      .get(&DataKey::DelegationRanks)
-      .unwrap_or(Map::new(&env))
+    datasets::get_all(&env, Dataset::DelegationRanks)
   }
 
   pub fn get_delegation_ranks_for_users(env: Env, users_ids: Vec<String>) -> Map<String, u32> {
-    let delegation_ranks = ExternalDataProvider::get_delegation_ranks(env.clone());
     let mut result: Map<String, u32> = Map::new(&env);
     for user_id in users_ids {
-      result.set(user_id.clone(), delegation_ranks.get(user_id).unwrap_or(0));
+      result.set(
+        user_id.clone(),
+        datasets::get(&env, Dataset::DelegationRanks, user_id).unwrap_or(0),
+      );
     }
     result
   }
 
-  pub fn set_delegation_rank_for_user(env: Env, user_id: String, new_rank: u32) {
-    let mut delegation_ranks = ExternalDataProvider::get_delegation_ranks(env.clone());
-    delegation_ranks.set(user_id, new_rank);
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::DelegationRanks, &delegation_ranks);
+  pub fn set_delegation_rank_for_user(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    new_rank: u32,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::DelegationRanks)?;
+    datasets::set(&env, Dataset::DelegationRanks, user_id, &new_rank);
+    Ok(())
   }
 
-  pub fn set_users_delegation_ranks(env: Env, users_ranks: Map<String, u32>) {
-    let mut all_ranks = ExternalDataProvider::get_delegation_ranks(env.clone());
+  pub fn set_users_delegation_ranks(
+    env: Env,
+    caller: Address,
+    users_ranks: Map<String, u32>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::DelegationRanks)?;
     for (user_id, new_rank) in users_ranks {
-      all_ranks.set(user_id, new_rank);
+      datasets::set(&env, Dataset::DelegationRanks, user_id, &new_rank);
     }
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::DelegationRanks, &all_ranks);
+    Ok(())
   }
 
-  pub fn set_users_delegation_ranks_vec(env: Env, users_ranks: Vec<(String, u32)>) {
-    let mut all_ranks = ExternalDataProvider::get_delegation_ranks(env.clone());
+  pub fn set_users_delegation_ranks_vec(
+    env: Env,
+    caller: Address,
+    users_ranks: Vec<(String, u32)>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::DelegationRanks)?;
     for (user_id, new_rank) in users_ranks {
-      all_ranks.set(user_id, new_rank);
+      datasets::set(&env, Dataset::DelegationRanks, user_id, &new_rank);
     }
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::DelegationRanks, &all_ranks);
+    Ok(())
   }
 
   // for trust graph neuron
   pub fn get_trust_map(env: Env) -> Map<String, Map<String, ()>> {
-    env
-      .storage()
-      .instance()
-      .get(&DataKey::TrustMap)
-      .unwrap_or(Map::new(&env))
+    datasets::get_all(&env, Dataset::TrustMap)
+  }
+
+  pub fn get_trust_map_for_user(env: Env, user_id: String) -> Map<String, ()> {
+    datasets::get(&env, Dataset::TrustMap, user_id).unwrap_or(Map::new(&env))
   }
 
-  pub fn set_trust_map(env: Env, trust_map: Map<String, Map<String, ()>>) {
-    env.storage().instance().set(&DataKey::TrustMap, &trust_map);
+  pub fn set_trust_map(
+    env: Env,
+    caller: Address,
+    trust_map: Map<String, Map<String, ()>>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::TrustMap)?;
+    datasets::replace(&env, Dataset::TrustMap, trust_map);
+    // every trusted user gets the weight 1
+    if !datasets::get_users(&env, Dataset::TrustWeights).is_empty() {
+      datasets::replace(
+        &env,
+        Dataset::TrustWeights,
+        Map::<String, (u32, u32)>::new(&env),
+      );
+    }
+    Ok(())
   }
 
   pub fn set_trust_map_for_user(
     env: Env,
+    caller: Address,
     user_id: String,
     user_trust_map: Map<String, ()>,
-  ) -> Map<String, ()> {
-    let mut trust_map = ExternalDataProvider::get_trust_map(env.clone());
-
-    trust_map.set(user_id.clone(), user_trust_map);
-
-    env.storage().instance().set(&DataKey::TrustMap, &trust_map);
-    ExternalDataProvider::get_trust_map(env.clone())
-      .get(user_id.clone())
-      .unwrap_or(Map::new(&env))
+  ) -> Result<Map<String, ()>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::TrustMap)?;
+    datasets::set(&env, Dataset::TrustMap, user_id.clone(), &user_trust_map);
+    datasets::remove(&env, Dataset::TrustWeights, user_id.clone());
+    Ok(ExternalDataProvider::get_trust_map_for_user(env, user_id))
   }
 
   pub fn set_trust_map_for_user_vec(
     env: Env,
+    caller: Address,
     user_id: String,
     user_trust_map: Vec<String>,
-  ) -> Map<String, ()> {
-    let mut trust_map = ExternalDataProvider::get_trust_map(env.clone());
-
-    let mut new_map = Map::new(&env);
+  ) -> Result<Map<String, ()>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::TrustMap)?;
+    let mut new_map: Map<String, ()> = Map::new(&env);
     for item in user_trust_map {
       new_map.set(item, ());
     }
 
-    trust_map.set(user_id.clone(), new_map);
+    datasets::set(&env, Dataset::TrustMap, user_id.clone(), &new_map);
+    datasets::remove(&env, Dataset::TrustWeights, user_id.clone());
+    Ok(ExternalDataProvider::get_trust_map_for_user(env, user_id))
+  }
 
-    env.storage().instance().set(&DataKey::TrustMap, &trust_map);
-    ExternalDataProvider::get_trust_map(env.clone())
-      .get(user_id.clone())
-      .unwrap_or(Map::new(&env))
+  // the users trusted by the user and how much, the weight is 1 unless it's set
+  pub fn get_trust_weights_for_user(env: Env, user_id: String) -> Map<String, (u32, u32)> {
+    if let Some(weights) = datasets::get(&env, Dataset::TrustWeights, user_id.clone()) {
+      return weights;
+    }
+    let mut weights: Map<String, (u32, u32)> = Map::new(&env);
+    for (trusted_user_id, _) in ExternalDataProvider::get_trust_map_for_user(env, user_id) {
+      weights.set(trusted_user_id, (1, 0));
+    }
+    weights
+  }
+
+  // the user passes their page rank to the trusted users proportionally to the weights, the
+  // unweighted setters above give every trusted user the weight 1
+  pub fn set_trust_weights_for_user(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    user_trust_weights: Map<String, (u32, u32)>,
+  ) -> Result<Map<String, (u32, u32)>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::TrustMap)?;
+    let mut new_map: Map<String, ()> = Map::new(&env);
+    for (trusted_user_id, weight) in user_trust_weights.iter() {
+      // the fractional part is in thousandths, so (0, 1500) is not a valid weight
+      if weight == (0, 0) || weight.1 >= 1000 {
+        return Err(ExternalDataProviderError::InvalidTrustWeight);
+      }
+      new_map.set(trusted_user_id, ());
+    }
+
+    datasets::set(&env, Dataset::TrustMap, user_id.clone(), &new_map);
+    datasets::set(
+      &env,
+      Dataset::TrustWeights,
+      user_id.clone(),
+      &user_trust_weights,
+    );
+    Ok(ExternalDataProvider::get_trust_weights_for_user(
+      env, user_id,
+    ))
+  }
+
+  // the weights are raw decimal numbers, 1500 is 1.5
+  pub fn set_trust_weights_for_user_vec(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    user_trust_weights: Vec<(String, u32)>,
+  ) -> Result<Map<String, (u32, u32)>, ExternalDataProviderError> {
+    let mut new_map: Map<String, (u32, u32)> = Map::new(&env);
+    for (trusted_user_id, weight) in user_trust_weights {
+      new_map.set(
+        trusted_user_id,
+        DecimalNumberWrapper::from(weight).as_tuple(),
+      );
+    }
+    ExternalDataProvider::set_trust_weights_for_user(env, caller, user_id, new_map)
+  }
+
+  // for category expertise neuron
+  pub fn get_expertise(env: Env) -> Map<String, Vec<String>> {
+    datasets::get_all(&env, Dataset::Expertise)
+  }
+
+  pub fn get_user_expertise(env: Env, user_id: String) -> Vec<String> {
+    datasets::get(&env, Dataset::Expertise, user_id).unwrap_or(vec![&env])
+  }
+
+  pub fn set_user_expertise(
+    env: Env,
+    caller: Address,
+    user_id: String,
+    categories: Vec<String>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Expertise)?;
+    datasets::set(&env, Dataset::Expertise, user_id, &categories);
+    Ok(())
+  }
+
+  // Map<UserUUID, categories>
+  pub fn set_users_expertise(
+    env: Env,
+    caller: Address,
+    users_expertise: Map<String, Vec<String>>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::Expertise)?;
+    datasets::replace(&env, Dataset::Expertise, users_expertise);
+    Ok(())
   }
 
   // for page rank
-  pub fn get_page_rank_results(env: Env) -> Map<String, (u32, u32)> {
+  pub fn get_page_rank_mode(env: Env) -> PageRankMode {
     env
       .storage()
       .instance()
-      .get(&DataKey::PageRankResult)
-      .unwrap_or(Map::new(&env))
+      .get(&DataKey::PageRankMode)
+      .unwrap_or(PageRankMode::Standard)
+  }
+
+  // applies to the calculations started after it's changed
+  pub fn set_page_rank_mode(env: Env, mode: PageRankMode) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_owner(&env)?;
+    env.storage().instance().set(&DataKey::PageRankMode, &mode);
+    events::page_rank_mode_set(&env, mode);
+    Ok(())
+  }
+
+  // the users personalized page rank flows from, e.g. verified members
+  pub fn get_trust_anchors(env: Env) -> Vec<String> {
+    datasets::get_users(&env, Dataset::TrustAnchors)
+  }
+
+  pub fn set_trust_anchors(
+    env: Env,
+    caller: Address,
+    users_ids: Vec<String>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::TrustAnchors)?;
+    let mut anchors: Map<String, ()> = Map::new(&env);
+    for user_id in users_ids {
+      anchors.set(user_id, ());
+    }
+    datasets::replace(&env, Dataset::TrustAnchors, anchors);
+    Ok(())
+  }
+
+  // the trust graph with the anchors of the page rank mode
+  fn trust_graph(env: &Env) -> Result<Rank, ExternalDataProviderError> {
+    let trust_map = ExternalDataProvider::get_trust_map(env.clone());
+    let trust_weights: Map<String, Map<String, (u32, u32)>> =
+      datasets::get_all(env, Dataset::TrustWeights);
+    let mut rank = Rank::from_pages(env, trust_map, trust_weights)?;
+    if ExternalDataProvider::get_page_rank_mode(env.clone()) == PageRankMode::Personalized {
+      let anchors = ExternalDataProvider::get_trust_anchors(env.clone());
+      if anchors.is_empty() {
+        return Err(ExternalDataProviderError::NoTrustAnchors);
+      }
+      for anchor in anchors {
+        rank.add_anchor(anchor);
+      }
+    }
+    Ok(rank)
+  }
+
+  // the ranks are raw PageRankDecimal numbers, 9 decimal places are kept so the ranks of big
+  // graphs are not rounded to 0
+  pub fn get_page_rank_results(env: Env) -> Map<String, PageRankValue> {
+    datasets::get_all(&env, Dataset::PageRankResult)
+  }
+
+  pub fn get_page_rank_result_for_user(env: Env, user_id: String) -> PageRankValue {
+    datasets::get(&env, Dataset::PageRankResult, user_id).unwrap_or(0)
+  }
+
+  pub fn set_page_rank_result(
+    env: Env,
+    caller: Address,
+    new_result: Map<String, (u32, u32)>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    let mut page_ranks: Map<String, PageRankValue> = Map::new(&env);
+    for (user_id, page_rank) in new_result {
+      page_ranks.set(user_id, PageRankDecimal::from_decimal(page_rank)?.as_raw());
+    }
+    ExternalDataProvider::store_page_rank_result(env, page_ranks);
+    Ok(())
+  }
+
+  fn store_page_rank_result(env: Env, new_result: Map<String, PageRankValue>) {
+    datasets::replace(&env, Dataset::PageRankResult, new_result);
+  }
+
+  pub fn set_page_rank_result_vec(
+    env: Env,
+    caller: Address,
+    new_result: Vec<(String, u32)>,
+  ) -> Result<(), ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    let mut new_map: Map<String, PageRankValue> = Map::new(&env);
+    for (node, value) in new_result {
+      let value = DecimalNumberWrapper::from(value).as_tuple();
+      new_map.set(node, PageRankDecimal::from_decimal(value)?.as_raw());
+    }
+
+    ExternalDataProvider::store_page_rank_result(env, new_map);
+    Ok(())
+  }
+
+  // the ranks are calculated and stored with 9 decimal places
+  // in the Personalized mode the trust anchors have to be set
+  pub fn calculate_page_rank(
+    env: Env,
+    caller: Address,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    ExternalDataProvider::store_calculated_page_rank(env.clone(), |rank| rank.calculate(&env))
+  }
+
+  // iterations is the maximum, the calculation stops once the ranks change by at most tolerance
+  // in total, tolerance is a raw PageRankDecimal number (1_000 is 0.000001)
+  pub fn calculate_page_rank_with_params(
+    env: Env,
+    caller: Address,
+    iterations: u32,
+    damping_factor: (u32, u32),
+    tolerance: i128,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    ExternalDataProvider::store_calculated_page_rank(env.clone(), |rank| {
+      rank.calculate_custom_params(&env, iterations, damping_factor, tolerance)
+    })
+  }
+
+  fn store_calculated_page_rank(
+    env: Env,
+    calculate: impl FnOnce(&Rank) -> Result<Map<String, PageRankValue>, ExternalDataProviderError>,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    let page_ranks = calculate(&ExternalDataProvider::trust_graph(&env)?)?;
+    ExternalDataProvider::store_page_rank_result(env, page_ranks.clone());
+    Ok(page_ranks)
+  }
+
+  // the page rank of a big trust graph doesn't fit one transaction, so it can be calculated in
+  // steps: start_page_rank indexes the trust graph, step_page_rank runs a few iterations at a time
+  // and finalize_page_rank stores the result once it's finished
+  // starting again discards the calculation in progress
+  pub fn start_page_rank(
+    env: Env,
+    caller: Address,
+    params: PageRankParams,
+  ) -> Result<PageRankStatus, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    page_rank::validate_params(params.damping_factor, params.tolerance)?;
+
+    let graph = ExternalDataProvider::trust_graph(&env)?.graph(&env)?;
+    let ranks = graph.initial_ranks(&env)?;
+    let status = PageRankStatus {
+      users_count: graph.users_count(),
+      iteration: 0,
+      last_delta: None,
+      finished: graph.users_count() == 0 || params.iterations == 0,
+      params,
+      started_by: caller,
+    };
+
+    ExternalDataProvider::set_page_rank_entry(&env, DataKey::PageRankGraph, &graph);
+    ExternalDataProvider::set_page_rank_entry(&env, DataKey::PageRankRanks, &ranks);
+    ExternalDataProvider::set_page_rank_entry(&env, DataKey::PageRankStatus, &status);
+    Ok(status)
+  }
+
+  // anyone can run the iterations (e.g. an off-chain keeper), the params are set by start_page_rank
+  pub fn step_page_rank(
+    env: Env,
+    max_iterations: u32,
+  ) -> Result<PageRankStatus, ExternalDataProviderError> {
+    let mut status = ExternalDataProvider::get_page_rank_status(env.clone())
+      .ok_or(ExternalDataProviderError::PageRankNotStarted)?;
+    if status.finished {
+      return Ok(status);
+    }
+    let graph: PageRankGraph =
+      ExternalDataProvider::get_page_rank_entry(&env, DataKey::PageRankGraph)?;
+    let mut ranks: Vec<PageRankValue> =
+      ExternalDataProvider::get_page_rank_entry(&env, DataKey::PageRankRanks)?;
+    let damping_factor =
+      page_rank::validate_params(status.params.damping_factor, status.params.tolerance)?;
+
+    for _ in 0..max_iterations {
+      let (new_ranks, delta) = graph.iterate(&env, &ranks, damping_factor)?;
+      ranks = new_ranks;
+      status.iteration += 1;
+      status.last_delta = Some(delta);
+      if delta <= status.params.tolerance || status.iteration >= status.params.iterations {
+        status.finished = true;
+        break;
+      }
+    }
+
+    // the graph doesn't change between the steps, it only has to stay alive
+    datasets::extend_ttl(&env, &DataKey::PageRankGraph);
+    ExternalDataProvider::set_page_rank_entry(&env, DataKey::PageRankRanks, &ranks);
+    ExternalDataProvider::set_page_rank_entry(&env, DataKey::PageRankStatus, &status);
+    Ok(status)
+  }
+
+  // only the writer who started the calculation can store its result
+  pub fn finalize_page_rank(
+    env: Env,
+    caller: Address,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    ExternalDataProvider::require_writer(&env, &caller, Dataset::PageRankResult)?;
+    let status = ExternalDataProvider::get_page_rank_status(env.clone())
+      .ok_or(ExternalDataProviderError::PageRankNotStarted)?;
+    if status.started_by != caller {
+      return Err(ExternalDataProviderError::Unauthorized);
+    }
+    if !status.finished {
+      return Err(ExternalDataProviderError::PageRankNotFinished);
+    }
+    let graph: PageRankGraph =
+      ExternalDataProvider::get_page_rank_entry(&env, DataKey::PageRankGraph)?;
+    let ranks: Vec<PageRankValue> =
+      ExternalDataProvider::get_page_rank_entry(&env, DataKey::PageRankRanks)?;
+
+    let storage = env.storage().persistent();
+    storage.remove(&DataKey::PageRankStatus);
+    storage.remove(&DataKey::PageRankGraph);
+    storage.remove(&DataKey::PageRankRanks);
+
+    let page_ranks = graph.ranks_map(&env, &ranks);
+    ExternalDataProvider::store_page_rank_result(env, page_ranks.clone());
+    Ok(page_ranks)
+  }
+
+  pub fn get_page_rank_status(env: Env) -> Option<PageRankStatus> {
+    env.storage().persistent().get(&DataKey::PageRankStatus)
+  }
+
+  fn get_page_rank_entry<V: TryFromVal<Env, Val>>(
+    env: &Env,
+    key: DataKey,
+  ) -> Result<V, ExternalDataProviderError> {
+    env
+      .storage()
+      .persistent()
+      .get(&key)
+      .ok_or(ExternalDataProviderError::PageRankNotStarted)
   }
 
-  pub fn get_page_rank_result_for_user(env: Env, user_id: String) -> (u32, u32) {
-    ExternalDataProvider::get_page_rank_results(env.clone())
-      .get(user_id)
-      .unwrap_or((0, 0))
+  fn set_page_rank_entry<V: IntoVal<Env, Val>>(env: &Env, key: DataKey, value: &V) {
+    env.storage().persistent().set(&key, value);
+    datasets::extend_ttl(env, &key);
   }
 
-  pub fn set_page_rank_result(env: Env, new_result: Map<String, (u32, u32)>) {
+  // persistent data expires unless its TTL is extended, anyone can extend it (e.g. before the next round)
+  // archived entries can't be extended by the contract, they have to be restored with a RestoreFootprint operation first
+  pub fn extend_dataset_ttl(env: Env, dataset: Dataset) {
     env
       .storage()
       .instance()
-      .set(&DataKey::PageRankResult, &new_result);
+      .extend_ttl(DATA_TTL_THRESHOLD, DATA_TTL_EXTEND_TO);
+    datasets::extend_dataset_ttl(&env, dataset);
   }
 
-  pub fn set_page_rank_result_vec(env: Env, new_result: Vec<(String, u32)>) {
-    let mut new_map: Map<String, (u32, u32)> = Map::new(&env);
-    for (node, value) in new_result {
-      new_map.set(node, DecimalNumberWrapper::from(value).as_tuple());
+  pub fn extend_users_ttl(env: Env, dataset: Dataset, users_ids: Vec<String>) {
+    env
+      .storage()
+      .instance()
+      .extend_ttl(DATA_TTL_THRESHOLD, DATA_TTL_EXTEND_TO);
+    datasets::extend_users_ttl(&env, dataset, users_ids);
+  }
+}
+
+// sample data used in tests, it is not a part of the release build
+#[cfg(any(test, feature = "testutils"))]
+#[contractimpl]
+impl ExternalDataProvider {
+  fn generate_username(env: &Env, n: u32) -> String {
+    match n {
+      1 => String::from_slice(&env, "user001"),
+      2 => String::from_slice(&env, "user002"),
+      3 => String::from_slice(&env, "user003"),
+      4 => String::from_slice(&env, "user004"),
+      5 => String::from_slice(&env, "user005"),
+      6 => String::from_slice(&env, "user006"),
+      7 => String::from_slice(&env, "user007"),
+      8 => String::from_slice(&env, "user008"),
+      9 => String::from_slice(&env, "user009"),
+      _ => String::from_slice(&env, "userXXX"),
     }
+  }
+
+  pub fn mock_data_assigned_reputation(env: Env) {
+    let mut reputation_map: Map<String, ReputationCategory> = Map::new(&env);
+    reputation_map.set(
+      ExternalDataProvider::generate_username(&env, 1),
+      ReputationCategory::Excellent,
+    );
+    reputation_map.set(
+      ExternalDataProvider::generate_username(&env, 2),
+      ReputationCategory::VeryGood,
+    );
+    reputation_map.set(
+      ExternalDataProvider::generate_username(&env, 3),
+      ReputationCategory::Good,
+    );
+    reputation_map.set(
+      ExternalDataProvider::generate_username(&env, 4),
+      ReputationCategory::Average,
+    );
+    reputation_map.set(
+      ExternalDataProvider::generate_username(&env, 5),
+      ReputationCategory::Poor,
+    );
+    datasets::replace(&env, Dataset::Reputation, reputation_map);
+  }
+
+  pub fn mock_prior_voting_history(env: Env) {
+    // for prior history neuron
+    let mut voting_history_set: Map<String, Vec<u32>> = Map::new(&env);
+    voting_history_set.set(
+      ExternalDataProvider::generate_username(&env, 1),
+      vec![&env, 2, 3],
+    );
+    voting_history_set.set(
+      ExternalDataProvider::generate_username(&env, 3),
+      vec![&env, 2, 3, 4],
+    );
+    datasets::replace(&env, Dataset::PriorVotingHistory, voting_history_set);
+  }
 
+  pub fn mock_round_bonus_map(env: Env) {
+    let mut round_bonus_map: Map<u32, (u32, u32)> = Map::new(&env);
+    round_bonus_map.set(1, (0, 0));
+    round_bonus_map.set(2, (0, 100));
+    round_bonus_map.set(3, (0, 200));
+    round_bonus_map.set(4, (0, 300));
     env
       .storage()
       .instance()
-      .set(&DataKey::PageRankResult, &new_map);
+      .set(&DataKey::RoundBonusMap, &round_bonus_map);
   }
 
-  pub fn calculate_page_rank(env: Env) -> Map<String, (u32, u32)> {
-    let trust_map = ExternalDataProvider::get_trust_map(env.clone());
+  pub fn mock_delegation_ranks(env: Env) {
+    let mut delegation_ranks: Map<String, u32> = Map::new(&env);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 1), 1);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 2), 2);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 3), 3);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 4), 4);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 5), 5);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 6), 6);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 7), 7);
+    delegation_ranks.set(ExternalDataProvider::generate_username(&env, 8), 8);
+    datasets::replace(&env, Dataset::DelegationRanks, delegation_ranks);
+  }
 
-    let page_rank_result = match trust_map.len() {
-      0 => Map::new(&env),
-      _ => {
-        let rank = Rank::from_pages(&env, trust_map);
-        rank.calculate(&env)
-      }
-    };
+  pub fn mock_trust_map(env: Env) {
+    let user001 = ExternalDataProvider::generate_username(&env, 1);
+    let user002 = ExternalDataProvider::generate_username(&env, 2);
+    let user003 = ExternalDataProvider::generate_username(&env, 3);
+    let user004 = ExternalDataProvider::generate_username(&env, 4);
+    // for trust graph neuron
+    let mut trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+    trust_map.set(
+      user001.clone(),
+      // map[&env, user002.clone(), user004.clone()],
+      Map::from_array(&env, [(user002.clone(), ()), (user004.clone(), ())]),
+    );
+    trust_map.set(
+      user002.clone(),
+      Map::from_array(&env, [(user001.clone(), ())]),
+    );
+    trust_map.set(
+      user003.clone(),
+      Map::from_array(&env, [(user001.clone(), ()), (user002.clone(), ())]),
+    );
+    trust_map.set(
+      user004.clone(),
+      Map::from_array(&env, [(user003.clone(), ())]),
+    );
+    datasets::replace(&env, Dataset::TrustMap, trust_map);
+  }
 
-    ExternalDataProvider::set_page_rank_result(env.clone(), page_rank_result.clone());
+  pub fn mock_expertise(env: Env) {
+    let mut expertise: Map<String, Vec<String>> = Map::new(&env);
+    expertise.set(
+      ExternalDataProvider::generate_username(&env, 1),
+      vec![&env, String::from_slice(&env, "Infrastructure")],
+    );
+    expertise.set(
+      ExternalDataProvider::generate_username(&env, 2),
+      vec![
+        &env,
+        String::from_slice(&env, "Financial Protocols"),
+        String::from_slice(&env, "Infrastructure"),
+      ],
+    );
+    datasets::replace(&env, Dataset::Expertise, expertise);
+  }
 
-    page_rank_result
+  pub fn mock_sample_data(env: Env) {
+    ExternalDataProvider::mock_data_assigned_reputation(env.clone());
+    ExternalDataProvider::mock_prior_voting_history(env.clone());
+    ExternalDataProvider::mock_round_bonus_map(env.clone());
+    ExternalDataProvider::mock_delegation_ranks(env.clone());
+    ExternalDataProvider::mock_trust_map(env.clone());
+    ExternalDataProvider::mock_expertise(env.clone());
   }
 }
 
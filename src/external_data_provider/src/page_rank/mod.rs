@@ -1,101 +1,282 @@
-use soroban_decimal_numbers::DecimalNumberWrapper;
-use soroban_sdk::{Env, Map, String, Vec};
+use soroban_sdk::{contracttype, Env, Map, String, Vec};
 
-use crate::types::DecimalNumber;
+use crate::types::{DecimalNumber, ExternalDataProviderError, PageRankDecimal, PageRankValue};
+
+pub const DEFAULT_ITERATIONS: u32 = 1000;
+pub const DEFAULT_DAMPING_FACTOR: DecimalNumber = (0, 850);
+// raw PageRankDecimal number, the calculation stops once the ranks change by at most 0.000001
+pub const DEFAULT_TOLERANCE: PageRankValue = 1_000;
 
 pub struct Rank {
+  // every page gets an index in the order it's seen, the graph below refers to the pages by it
+  nodes: Vec<String>,
+  indexes: Map<String, u32>,
+  // page index -> indexes of the pages it links to and the weights of the links (raw
+  // PageRankDecimal numbers)
+  edges: Map<u32, Map<u32, PageRankValue>>,
+  // indexes of the trusted pages of personalized page rank (TrustRank), empty for plain page rank
+  anchors: Map<u32, ()>,
+}
+
+// the graph the iterations run on, built once per calculation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageRankGraph {
   nodes: Vec<String>,
-  edges: Map<String, Vec<String>>,
+  // page index -> indexes of the pages linking to it and the weights of the links
+  incoming: Vec<Vec<(u32, PageRankValue)>>,
+  // page index -> the sum of the weights of its links
+  out_weights: Vec<PageRankValue>,
+  // page index -> whether the random surfer restarts from it, empty if it restarts from any page
+  anchors: Vec<bool>,
+  anchors_count: u32,
+}
+
+// the damping factor as PageRankDecimal, if the params are valid
+pub fn validate_params(
+  damping_factor: DecimalNumber,
+  tolerance: PageRankValue,
+) -> Result<PageRankDecimal, ExternalDataProviderError> {
+  let damping_factor = PageRankDecimal::from_decimal(damping_factor)?;
+  if damping_factor > PageRankDecimal::ONE || tolerance < 0 {
+    return Err(ExternalDataProviderError::InvalidPageRankParams);
+  }
+  Ok(damping_factor)
 }
 
 impl Rank {
   pub fn new(env: &Env) -> Self {
     Rank {
-      nodes: Vec::new(&env),
-      edges: Map::new(&env),
+      nodes: Vec::new(env),
+      indexes: Map::new(env),
+      edges: Map::new(env),
+      anchors: Map::new(env),
     }
   }
 
-  pub fn from_pages(env: &Env, pages: Map<String, Map<String, ()>>) -> Rank {
-    let mut result = Rank::new(&env);
+  // the pages without weights link with the weight 1
+  pub fn from_pages(
+    env: &Env,
+    pages: Map<String, Map<String, ()>>,
+    weights: Map<String, Map<String, DecimalNumber>>,
+  ) -> Result<Rank, ExternalDataProviderError> {
+    let mut result = Rank::new(env);
     for (page, links) in pages {
-      result.add_page(env, page, links);
+      match weights.get(page.clone()) {
+        Some(weighted_links) => result.add_weighted_page(env, page, weighted_links)?,
+        None => result.add_page(env, page, links),
+      }
     }
-    result
+    Ok(result)
   }
 
-  // todo optimize this
+  // every link has the weight 1
   pub fn add_page(&mut self, env: &Env, page: String, links: Map<String, ()>) {
-    let mut page_edges = self.edges.get(page.clone()).unwrap_or(Vec::new(&env));
-    if !&self.nodes.contains(page.clone()) {
-      self.nodes.push_back(page.clone());
-    }
+    let page_index = self.index_of(page);
+    let mut page_edges = self.edges.get(page_index).unwrap_or(Map::new(env));
     for (link, _) in links {
-      if !page_edges.contains(link.clone()) {
-        page_edges.push_back(link.clone());
+      page_edges.set(self.index_of(link), PageRankDecimal::ONE.as_raw());
+    }
+    self.edges.set(page_index, page_edges);
+  }
+
+  // the page passes its rank to the links proportionally to their weights
+  pub fn add_weighted_page(
+    &mut self,
+    env: &Env,
+    page: String,
+    links: Map<String, DecimalNumber>,
+  ) -> Result<(), ExternalDataProviderError> {
+    let page_index = self.index_of(page);
+    let mut page_edges = self.edges.get(page_index).unwrap_or(Map::new(env));
+    for (link, weight) in links {
+      let weight = PageRankDecimal::from_decimal(weight)?;
+      page_edges.set(self.index_of(link), weight.as_raw());
+    }
+    self.edges.set(page_index, page_edges);
+    Ok(())
+  }
+
+  // with anchors the rank flows only from the anchors through the trust links (personalized page
+  // rank), so users who are not trusted by anyone trusted by the anchors get no rank, however
+  // many of them trust each other
+  pub fn add_anchor(&mut self, page: String) {
+    let page_index = self.index_of(page);
+    self.anchors.set(page_index, ());
+  }
+
+  fn index_of(&mut self, page: String) -> u32 {
+    if let Some(index) = self.indexes.get(page.clone()) {
+      return index;
+    }
+    let index = self.nodes.len();
+    self.nodes.push_back(page.clone());
+    self.indexes.set(page, index);
+    index
+  }
+
+  pub fn graph(&self, env: &Env) -> Result<PageRankGraph, ExternalDataProviderError> {
+    let mut incoming: Vec<Vec<(u32, PageRankValue)>> = Vec::new(env);
+    let mut out_weights: Vec<PageRankValue> = Vec::new(env);
+    let mut anchors: Vec<bool> = Vec::new(env);
+    for index in 0..self.nodes.len() {
+      incoming.push_back(Vec::new(env));
+      out_weights.push_back(0);
+      if !self.anchors.is_empty() {
+        anchors.push_back(self.anchors.contains_key(index));
       }
-      if !&self.nodes.contains(link.clone()) {
-        self.nodes.push_back(link.clone());
+    }
+    for (page_index, links) in self.edges.iter() {
+      let mut out_weight = PageRankDecimal::ZERO;
+      for (link_index, weight) in links {
+        out_weight = PageRankDecimal::add(out_weight, PageRankDecimal::from_raw(weight))?;
+        let mut link_incoming = incoming.get_unchecked(link_index);
+        link_incoming.push_back((page_index, weight));
+        incoming.set(link_index, link_incoming);
       }
+      out_weights.set(page_index, out_weight.as_raw());
     }
-    self.edges.set(page.clone(), page_edges);
+    Ok(PageRankGraph {
+      nodes: self.nodes.clone(),
+      incoming,
+      out_weights,
+      anchors,
+      anchors_count: self.anchors.len(),
+    })
   }
 
-  pub fn calculate(&self, env: &Env) -> Map<String, DecimalNumber> {
-    self.calculate_custom_params(&env, 1000, (0, 850))
+  pub fn calculate(
+    &self,
+    env: &Env,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    self.calculate_custom_params(
+      env,
+      DEFAULT_ITERATIONS,
+      DEFAULT_DAMPING_FACTOR,
+      DEFAULT_TOLERANCE,
+    )
   }
 
+  // the ranks are raw PageRankDecimal numbers, the calculation is done with 9 decimal places so
+  // the ranks of big graphs don't get rounded to 0
+  // iterations is the maximum, the calculation stops earlier once the ranks change by at most
+  // tolerance (a raw PageRankDecimal number) in total
   pub fn calculate_custom_params(
     &self,
     env: &Env,
     iterations: u32,
     damping_factor: DecimalNumber,
-  ) -> Map<String, DecimalNumber> {
-    let mut page_ranks: Map<String, DecimalNumber> = Map::new(env);
-    for node in self.nodes.clone() {
-      page_ranks.set(
-        node,
-        DecimalNumberWrapper::div(
-          DecimalNumberWrapper::from("1.0"),
-          DecimalNumberWrapper::from((self.nodes.len(), 0)),
-        )
-        .as_tuple(),
-      );
+    tolerance: PageRankValue,
+  ) -> Result<Map<String, PageRankValue>, ExternalDataProviderError> {
+    let damping_factor = validate_params(damping_factor, tolerance)?;
+    let graph = self.graph(env)?;
+    let mut ranks = graph.initial_ranks(env)?;
+    for _ in 0..iterations {
+      let (new_ranks, delta) = graph.iterate(env, &ranks, damping_factor)?;
+      ranks = new_ranks;
+      if delta <= tolerance {
+        break;
+      }
     }
+    Ok(graph.ranks_map(env, &ranks))
+  }
+}
 
-    for _ in 0..iterations {
-      let mut new_ranks: Map<String, DecimalNumber> = Map::new(env);
-      let nodes = self.nodes.clone();
-      let edges = self.edges.clone();
-      for node in nodes {
-        let sub_result = DecimalNumberWrapper::sub(
-          DecimalNumberWrapper::from("1.0"),
-          DecimalNumberWrapper::from(damping_factor),
-        );
-        let div_result = DecimalNumberWrapper::div(
-          sub_result,
-          DecimalNumberWrapper::from((self.nodes.len(), 0)),
-        );
-        let mut rank = div_result;
-
-        for (other_node, edges_item) in edges.clone() {
-          if edges_item.contains(node.clone()) {
-            let edge_count: DecimalNumber = (edges_item.len(), 0);
-            let mul_result = DecimalNumberWrapper::mul(
-              DecimalNumberWrapper::from(damping_factor),
-              DecimalNumberWrapper::from(page_ranks.get(other_node).unwrap_or((0, 0))),
-            );
-            let div_result =
-              DecimalNumberWrapper::div(mul_result, DecimalNumberWrapper::from(edge_count));
-            rank = DecimalNumberWrapper::add(rank, div_result);
-          }
-        }
-
-        new_ranks.set(node.clone(), rank.as_tuple());
+// every iteration is O(pages + links), the incoming links are indexed before the first one
+impl PageRankGraph {
+  pub fn users_count(&self) -> u32 {
+    self.nodes.len()
+  }
+
+  fn is_anchor(&self, index: u32) -> bool {
+    self.anchors.is_empty() || self.anchors.get_unchecked(index)
+  }
+
+  // the pages the random surfer restarts from
+  fn anchors_count(&self) -> Result<PageRankDecimal, ExternalDataProviderError> {
+    if self.anchors.is_empty() {
+      return Ok(PageRankDecimal::from_int(self.nodes.len() as i128)?);
+    }
+    Ok(PageRankDecimal::from_int(self.anchors_count as i128)?)
+  }
+
+  // the rank is split between the anchors
+  pub fn initial_ranks(&self, env: &Env) -> Result<Vec<PageRankValue>, ExternalDataProviderError> {
+    let mut ranks: Vec<PageRankValue> = Vec::new(env);
+    if self.nodes.is_empty() {
+      return Ok(ranks);
+    }
+    let initial_rank = PageRankDecimal::div(PageRankDecimal::ONE, self.anchors_count()?)?;
+    for index in 0..self.nodes.len() {
+      if self.is_anchor(index) {
+        ranks.push_back(initial_rank.as_raw());
+      } else {
+        ranks.push_back(0);
+      }
+    }
+    Ok(ranks)
+  }
+
+  // the new ranks and how much they changed in total (a raw PageRankDecimal number)
+  pub fn iterate(
+    &self,
+    env: &Env,
+    ranks: &Vec<PageRankValue>,
+    damping_factor: PageRankDecimal,
+  ) -> Result<(Vec<PageRankValue>, PageRankValue), ExternalDataProviderError> {
+    if self.nodes.is_empty() {
+      return Ok((Vec::new(env), 0));
+    }
+    let anchors_count = self.anchors_count()?;
+    let sub_result = PageRankDecimal::sub(PageRankDecimal::ONE, damping_factor)?;
+    let teleport_rank = PageRankDecimal::div(sub_result, anchors_count)?;
+
+    // the rank every page passes to its links per unit of weight, users who trust noone (dangling
+    // nodes) share their rank with the anchors (everyone in plain page rank), otherwise it would
+    // leak and the ranks wouldn't sum to 1
+    let mut shares: Vec<PageRankValue> = Vec::new(env);
+    let mut dangling_rank = PageRankDecimal::ZERO;
+    for (rank, out_weight) in ranks.iter().zip(self.out_weights.iter()) {
+      let rank = PageRankDecimal::from_raw(rank);
+      if out_weight == 0 {
+        dangling_rank = PageRankDecimal::add(dangling_rank, rank)?;
+        shares.push_back(0);
+        continue;
       }
-      page_ranks = new_ranks;
+      let mul_result = PageRankDecimal::mul(damping_factor, rank)?;
+      let out_weight = PageRankDecimal::from_raw(out_weight);
+      shares.push_back(PageRankDecimal::div(mul_result, out_weight)?.as_raw());
     }
+    let mul_result = PageRankDecimal::mul(damping_factor, dangling_rank)?;
+    let dangling_share = PageRankDecimal::div(mul_result, anchors_count)?;
+    let anchor_rank = PageRankDecimal::add(teleport_rank, dangling_share)?;
 
+    let mut new_ranks: Vec<PageRankValue> = Vec::new(env);
+    let mut delta = PageRankDecimal::ZERO;
+    for (index, (old_rank, incoming)) in ranks.iter().zip(self.incoming.iter()).enumerate() {
+      let mut rank = if self.is_anchor(index as u32) {
+        anchor_rank
+      } else {
+        PageRankDecimal::ZERO
+      };
+      for (other_index, weight) in incoming {
+        let share = PageRankDecimal::from_raw(shares.get_unchecked(other_index));
+        let weighted_share = PageRankDecimal::mul(share, PageRankDecimal::from_raw(weight))?;
+        rank = PageRankDecimal::add(rank, weighted_share)?;
+      }
+
+      let change = PageRankDecimal::sub(rank, PageRankDecimal::from_raw(old_rank))?;
+      delta = PageRankDecimal::add(delta, PageRankDecimal::from_raw(change.as_raw().abs()))?;
+      new_ranks.push_back(rank.as_raw());
+    }
+    Ok((new_ranks, delta.as_raw()))
+  }
+
+  pub fn ranks_map(&self, env: &Env, ranks: &Vec<PageRankValue>) -> Map<String, PageRankValue> {
+    let mut page_ranks: Map<String, PageRankValue> = Map::new(env);
+    for (node, rank) in self.nodes.iter().zip(ranks.iter()) {
+      page_ranks.set(node, rank);
+    }
     page_ranks
   }
 }
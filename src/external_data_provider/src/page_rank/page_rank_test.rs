@@ -1,6 +1,17 @@
-use soroban_sdk::{Env, Map, String};
+use soroban_sdk::{testutils::Address as _, Address, Env, Map, String};
 
-use super::Rank;
+use super::{Rank, DEFAULT_DAMPING_FACTOR};
+use crate::{
+  types::{DecimalNumber, ExternalDataProviderError, PageRankDecimal, PageRankValue},
+  ExternalDataProvider, ExternalDataProviderClient,
+};
+
+// the ranks rounded to 3 decimal places, as the neurons see them
+fn rank_of(ranks: &Map<String, PageRankValue>, page: &String) -> DecimalNumber {
+  PageRankDecimal::from_raw(ranks.get(page.clone()).unwrap())
+    .as_decimal()
+    .unwrap()
+}
 
 #[test]
 fn test_simple() {
@@ -53,25 +64,386 @@ fn test_simple() {
     Map::from_array(&env, [(page_a.clone(), ())]),
   );
 
-  let ranks = page_rank.calculate(&env);
+  let ranks = page_rank.calculate(&env).unwrap();
 
-  assert!(ranks.get(page_a.clone()).unwrap() == (0, 415));
-  assert!(ranks.get(page_b.clone()).unwrap() == (0, 303));
-  assert!(ranks.get(page_c.clone()).unwrap() == (0, 213));
-  assert!(ranks.get(page_d.clone()).unwrap() == (0, 37));
+  assert!(rank_of(&ranks, &page_a) == (0, 429));
+  assert!(rank_of(&ranks, &page_b) == (0, 313));
+  assert!(rank_of(&ranks, &page_c) == (0, 219));
+  assert!(rank_of(&ranks, &page_d) == (0, 37));
 
   page_rank.add_page(&env, page_e.clone(), Map::from_array(&env, []));
 
-  let ranks = page_rank.calculate(&env);
+  let ranks = page_rank.calculate(&env).unwrap();
 
-  assert!(ranks.get(page_a.clone()).unwrap() == (0, 337));
-  assert!(ranks.get(page_b.clone()).unwrap() == (0, 246));
-  assert!(ranks.get(page_c.clone()).unwrap() == (0, 173));
-  assert!(ranks.get(page_d.clone()).unwrap() == (0, 30));
-  assert!(ranks.get(page_e.clone()).unwrap() == (0, 30));
+  // E trusts noone, its rank is shared with everyone instead of being lost
+  assert!(rank_of(&ranks, &page_a) == (0, 413));
+  assert!(rank_of(&ranks, &page_b) == (0, 302));
+  assert!(rank_of(&ranks, &page_c) == (0, 211));
+  assert!(rank_of(&ranks, &page_d) == (0, 36));
+  assert!(rank_of(&ranks, &page_e) == (0, 36));
+  let ranks_sum: PageRankValue = ranks.values().iter().sum();
+  assert!((PageRankDecimal::ONE.as_raw() - ranks_sum).abs() < 1_000);
 
   // values above might change if you modify the algorithm but the stuff below should always remain the same
   assert!(ranks.get(page_a.clone()).unwrap() > ranks.get(page_b.clone()).unwrap());
   assert!(ranks.get(page_b.clone()).unwrap() > ranks.get(page_c.clone()).unwrap());
   assert!(ranks.get(page_c.clone()).unwrap() > ranks.get(page_d.clone()).unwrap());
 }
+
+#[test]
+fn test_big_graph_precision() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  // every user trusts the next one and the one with half of their number, so the ranks differ
+  let users_count: u32 = 300;
+  let user = |index: u32| {
+    let id = [
+      b'u',
+      b's',
+      b'e',
+      b'r',
+      b'0' + (index / 100) as u8,
+      b'0' + (index / 10 % 10) as u8,
+      b'0' + (index % 10) as u8,
+    ];
+    String::from_slice(&env, core::str::from_utf8(&id).unwrap())
+  };
+  let mut trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+  for index in 0..users_count {
+    let mut links: Map<String, ()> = Map::new(&env);
+    links.set(user((index + 1) % users_count), ());
+    if index > 0 {
+      links.set(user(index / 2), ());
+    }
+    trust_map.set(user(index), links);
+  }
+
+  env.mock_all_auths();
+  let external_data_provider_id = env.register_contract(None, ExternalDataProvider);
+  let external_data_provider_client =
+    ExternalDataProviderClient::new(&env, &external_data_provider_id);
+  external_data_provider_client.initialize(&Address::generate(&env));
+  let owner = external_data_provider_client.get_owner();
+  external_data_provider_client.set_trust_map(&owner, &trust_map);
+
+  let ranks =
+    external_data_provider_client.calculate_page_rank_with_params(&owner, &20, &(0, 850), &0);
+  assert!(ranks.len() == users_count);
+  // the ranks are stored and served with all 9 decimal places, many of them are below 0.001
+  assert!(external_data_provider_client.get_page_rank_results() == ranks);
+
+  let mut ranks_sum: PageRankValue = 0;
+  for (user_id, rank) in ranks.iter() {
+    assert!(rank > 0);
+    assert!(external_data_provider_client.get_page_rank_result_for_user(&user_id) == rank);
+    ranks_sum += rank;
+  }
+  // the rank is not lost on the way, only a few billionths through the rounding
+  assert!((PageRankDecimal::ONE.as_raw() - ranks_sum).abs() < 10_000);
+}
+
+#[test]
+fn test_reference_ranking() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+  /*
+    the example graph of the PageRank article on Wikipedia, A trusts noone and G-K are trusted
+    by noone, the reference ranks are the exact solution rounded to 9 decimal places
+  */
+  let page = |name: &str| String::from_slice(&env, name);
+  let graph: [(&str, &[&str]); 11] = [
+    ("A", &[]),
+    ("B", &["C"]),
+    ("C", &["B"]),
+    ("D", &["A", "B"]),
+    ("E", &["B", "D", "F"]),
+    ("F", &["B", "E"]),
+    ("G", &["B", "E"]),
+    ("H", &["B", "E"]),
+    ("I", &["B", "E"]),
+    ("J", &["E"]),
+    ("K", &["E"]),
+  ];
+  let reference: [(&str, PageRankValue); 11] = [
+    ("A", 32_781_493),
+    ("B", 384_400_949),
+    ("C", 342_910_286),
+    ("D", 39_087_092),
+    ("E", 80_885_693),
+    ("F", 39_087_092),
+    ("G", 16_169_479),
+    ("H", 16_169_479),
+    ("I", 16_169_479),
+    ("J", 16_169_479),
+    ("K", 16_169_479),
+  ];
+
+  let mut page_rank: Rank = Rank::new(&env);
+  for (name, links) in graph {
+    let mut links_map: Map<String, ()> = Map::new(&env);
+    for link in links {
+      links_map.set(page(link), ());
+    }
+    page_rank.add_page(&env, page(name), links_map);
+  }
+
+  let ranks = page_rank.calculate(&env).unwrap();
+  for (name, reference_rank) in reference {
+    let rank = ranks.get(page(name)).unwrap();
+    assert!((rank - reference_rank).abs() < 10_000);
+  }
+  assert!(rank_of(&ranks, &page("B")) == (0, 384));
+  assert!(rank_of(&ranks, &page("E")) == (0, 80));
+}
+
+#[test]
+fn test_convergence() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let page_a = String::from_slice(&env, "A");
+  let page_b = String::from_slice(&env, "B");
+  let page_c = String::from_slice(&env, "C");
+  let mut page_rank: Rank = Rank::new(&env);
+  page_rank.add_page(
+    &env,
+    page_a.clone(),
+    Map::from_array(&env, [(page_b.clone(), ())]),
+  );
+  page_rank.add_page(
+    &env,
+    page_b.clone(),
+    Map::from_array(&env, [(page_a.clone(), ()), (page_c.clone(), ())]),
+  );
+
+  // the ranks can't change by more than 2 in total, so it stops right after the first iteration
+  let first_iteration = page_rank
+    .calculate_custom_params(&env, 1, DEFAULT_DAMPING_FACTOR, 0)
+    .unwrap();
+  let tolerant = page_rank
+    .calculate_custom_params(&env, 1000, DEFAULT_DAMPING_FACTOR, 2_000_000_000)
+    .unwrap();
+  assert!(tolerant == first_iteration);
+
+  // the early stop doesn't change the ranks more than the tolerance
+  let converged = page_rank
+    .calculate_custom_params(&env, 1000, DEFAULT_DAMPING_FACTOR, 1_000)
+    .unwrap();
+  let exact = page_rank
+    .calculate_custom_params(&env, 1000, DEFAULT_DAMPING_FACTOR, 0)
+    .unwrap();
+  for (page, rank) in exact.iter() {
+    assert!((rank - converged.get(page).unwrap()).abs() <= 1_000);
+  }
+
+  // without damping everyone gets the same rank
+  let undamped = page_rank
+    .calculate_custom_params(&env, 1000, (0, 0), 0)
+    .unwrap();
+  assert!(undamped.get(page_a.clone()).unwrap() == 333_333_333);
+  assert!(undamped.get(page_c.clone()).unwrap() == 333_333_333);
+
+  assert!(
+    page_rank.calculate_custom_params(&env, 1000, (1, 1), 0)
+      == Err(ExternalDataProviderError::InvalidPageRankParams)
+  );
+  assert!(
+    page_rank.calculate_custom_params(&env, 1000, DEFAULT_DAMPING_FACTOR, -1)
+      == Err(ExternalDataProviderError::InvalidPageRankParams)
+  );
+  assert!(Rank::new(&env).calculate(&env).unwrap().is_empty());
+}
+
+// every user trusts the next 2 ones and the one with half of their number
+fn trust_graph(env: &Env, users_count: u32) -> Rank {
+  let user = |index: u32| {
+    let id = [
+      b'u',
+      b'0' + (index / 100 % 10) as u8,
+      b'0' + (index / 10 % 10) as u8,
+      b'0' + (index % 10) as u8,
+    ];
+    String::from_slice(env, core::str::from_utf8(&id).unwrap())
+  };
+  let mut page_rank: Rank = Rank::new(env);
+  for index in 0..users_count {
+    let mut links: Map<String, ()> = Map::new(env);
+    links.set(user((index + 1) % users_count), ());
+    links.set(user((index + 2) % users_count), ());
+    links.set(user(index / 2), ());
+    page_rank.add_page(env, user(index), links);
+  }
+  page_rank
+}
+
+fn calculation_cost(env: &Env, page_rank: &Rank, iterations: u32) -> u64 {
+  env.budget().reset_default();
+  let before = env.budget().cpu_instruction_cost();
+  page_rank
+    .calculate_custom_params(env, iterations, DEFAULT_DAMPING_FACTOR, 0)
+    .unwrap();
+  env.budget().cpu_instruction_cost() - before
+}
+
+#[test]
+fn test_budget() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+  let small_graph = trust_graph(&env, 50);
+  let big_graph = trust_graph(&env, 100);
+
+  // fits into the default budget of a transaction, which panics when it's exceeded
+  let small_cost = calculation_cost(&env, &small_graph, 20);
+  let big_cost = calculation_cost(&env, &big_graph, 20);
+
+  // the cost grows with the number of links, twice the users with the same number of links
+  // each is not 4 times as expensive
+  assert!(big_cost < small_cost * 3);
+  // and every iteration costs the same
+  let longer_cost = calculation_cost(&env, &small_graph, 40);
+  assert!(longer_cost < small_cost * 3);
+  assert!(longer_cost > small_cost);
+}
+
+#[test]
+fn test_personalized() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+  /*
+    A->B
+    B->A
+    B->C
+    C->A
+
+    S1, S2 and S3 are a ring of sybils trusting each other, S1 also trusts A
+    S1->S2
+    S1->A
+    S2->S3
+    S3->S1
+  */
+  let page = |name: &str| String::from_slice(&env, name);
+  let graph: [(&str, &[&str]); 6] = [
+    ("A", &["B"]),
+    ("B", &["A", "C"]),
+    ("C", &["A"]),
+    ("S1", &["S2", "A"]),
+    ("S2", &["S3"]),
+    ("S3", &["S1"]),
+  ];
+  let mut page_rank: Rank = Rank::new(&env);
+  for (name, links) in graph {
+    let mut links_map: Map<String, ()> = Map::new(&env);
+    for link in links {
+      links_map.set(page(link), ());
+    }
+    page_rank.add_page(&env, page(name), links_map);
+  }
+
+  // the sybils get a share of the rank just by trusting each other
+  let ranks = page_rank.calculate(&env).unwrap();
+  assert!(rank_of(&ranks, &page("A")) == (0, 317));
+  assert!(rank_of(&ranks, &page("S1")) == (0, 92));
+  assert!(rank_of(&ranks, &page("S3")) == (0, 79));
+
+  // nobody trusted by the anchor trusts them
+  page_rank.add_anchor(page("A"));
+  let ranks = page_rank.calculate(&env).unwrap();
+  assert!(rank_of(&ranks, &page("A")) == (0, 452));
+  assert!(rank_of(&ranks, &page("B")) == (0, 384));
+  assert!(rank_of(&ranks, &page("C")) == (0, 163));
+  assert!(ranks.get(page("S1")).unwrap() == 0);
+  assert!(ranks.get(page("S2")).unwrap() == 0);
+  assert!(ranks.get(page("S3")).unwrap() == 0);
+  let ranks_sum: PageRankValue = ranks.values().iter().sum();
+  assert!((PageRankDecimal::ONE.as_raw() - ranks_sum).abs() < 1_000);
+
+  // an anchor who is not in the trust graph keeps the rank the others don't pass to it
+  page_rank.add_anchor(page("D"));
+  let ranks = page_rank.calculate(&env).unwrap();
+  assert!(ranks.get(page("D")).unwrap() > 0);
+  assert!(ranks.get(page("S1")).unwrap() == 0);
+  let ranks_sum: PageRankValue = ranks.values().iter().sum();
+  assert!((PageRankDecimal::ONE.as_raw() - ranks_sum).abs() < 1_000);
+}
+
+#[test]
+fn test_weighted() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+  /*
+    A->B weight 3
+    A->C weight 1
+    B->A
+    C->A
+  */
+  let page_a = String::from_slice(&env, "A");
+  let page_b = String::from_slice(&env, "B");
+  let page_c = String::from_slice(&env, "C");
+  let add_others = |page_rank: &mut Rank| {
+    page_rank.add_page(
+      &env,
+      page_b.clone(),
+      Map::from_array(&env, [(page_a.clone(), ())]),
+    );
+    page_rank.add_page(
+      &env,
+      page_c.clone(),
+      Map::from_array(&env, [(page_a.clone(), ())]),
+    );
+  };
+
+  let mut weighted: Rank = Rank::new(&env);
+  weighted
+    .add_weighted_page(
+      &env,
+      page_a.clone(),
+      Map::from_array(&env, [(page_b.clone(), (3, 0)), (page_c.clone(), (1, 0))]),
+    )
+    .unwrap();
+  add_others(&mut weighted);
+  let ranks = weighted.calculate(&env).unwrap();
+  assert!(rank_of(&ranks, &page_a) == (0, 486));
+  assert!(rank_of(&ranks, &page_b) == (0, 360));
+  assert!(rank_of(&ranks, &page_c) == (0, 153));
+
+  // only the proportions matter
+  let mut scaled: Rank = Rank::new(&env);
+  scaled
+    .add_weighted_page(
+      &env,
+      page_a.clone(),
+      Map::from_array(
+        &env,
+        [(page_b.clone(), (0, 600)), (page_c.clone(), (0, 200))],
+      ),
+    )
+    .unwrap();
+  add_others(&mut scaled);
+  let scaled_ranks = scaled.calculate(&env).unwrap();
+  assert!(rank_of(&scaled_ranks, &page_a) == (0, 486));
+  assert!(rank_of(&scaled_ranks, &page_b) == (0, 360));
+  assert!(rank_of(&scaled_ranks, &page_c) == (0, 153));
+
+  // the weight 1 is the same as no weight
+  let mut unweighted: Rank = Rank::new(&env);
+  unweighted.add_page(
+    &env,
+    page_a.clone(),
+    Map::from_array(&env, [(page_b.clone(), ()), (page_c.clone(), ())]),
+  );
+  add_others(&mut unweighted);
+  let mut weight_one: Rank = Rank::new(&env);
+  weight_one
+    .add_weighted_page(
+      &env,
+      page_a.clone(),
+      Map::from_array(&env, [(page_b.clone(), (1, 0)), (page_c.clone(), (1, 0))]),
+    )
+    .unwrap();
+  add_others(&mut weight_one);
+  let ranks = unweighted.calculate(&env).unwrap();
+  assert!(weight_one.calculate(&env).unwrap() == ranks);
+  assert!(rank_of(&ranks, &page_b) == (0, 256));
+  assert!(rank_of(&ranks, &page_c) == (0, 256));
+}
@@ -1,13 +1,96 @@
-use soroban_sdk::{contracterror, contracttype, Env, String};
+use soroban_sdk::{contracterror, contracttype, Address, Env, String};
+use voting_fixed_point::{FixedPoint, FixedPointError};
 
 pub type DecimalNumber = (u32, u32);
 
+// raw PageRankDecimal number, 1_000_000_000 is 1
+pub type PageRankValue = i128;
+
+// the ranks of big trust graphs are small, 3 decimal places would round most of them to 0
+pub const PAGE_RANK_DECIMALS: u32 = 9;
+pub type PageRankDecimal = FixedPoint<PAGE_RANK_DECIMALS>;
+
+// in ledgers (~5 seconds each), user data is extended to ~120 days whenever it gets below ~30 days
+pub const DATA_TTL_THRESHOLD: u32 = 30 * 17280;
+pub const DATA_TTL_EXTEND_TO: u32 = 120 * 17280;
+
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 pub enum ExternalDataProviderError {
   UnknownError = 0,
   TooManyDelegatees = 1,
   NotEnoughDelegatees = 2,
+  AlreadyInitialized = 3,
+  OwnerNotSet = 4,
+  Unauthorized = 5,
+  UnknownReputationCategory = 6,
+  ArithmeticOverflow = 7,
+  InvalidPageRankParams = 8,
+  PageRankNotStarted = 9,
+  PageRankNotFinished = 10,
+  NoTrustAnchors = 11,
+  InvalidTrustWeight = 12,
+}
+
+impl From<FixedPointError> for ExternalDataProviderError {
+  fn from(_: FixedPointError) -> Self {
+    // the divisors of the calculations are never 0
+    ExternalDataProviderError::ArithmeticOverflow
+  }
+}
+
+// how the page rank of the trust graph is calculated
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PageRankMode {
+  // every user is trusted the same at the start
+  Standard,
+  // the rank flows from the trust anchors only (TrustRank), so rings of sybils who trust each other
+  // get no rank unless someone trusted by the anchors trusts them
+  Personalized,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageRankParams {
+  // the maximum number of iterations
+  pub iterations: u32,
+  pub damping_factor: DecimalNumber,
+  // raw PageRankDecimal number, the calculation stops once the ranks change by at most tolerance
+  // in total
+  pub tolerance: PageRankValue,
+}
+
+// the progress of the page rank calculation started with `start_page_rank`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PageRankStatus {
+  pub params: PageRankParams,
+  pub users_count: u32,
+  // iterations done so far
+  pub iteration: u32,
+  // raw PageRankDecimal number, how much the ranks changed in total in the last iteration
+  pub last_delta: Option<PageRankValue>,
+  // the ranks have converged or all the iterations are done, the result can be stored with
+  // `finalize_page_rank`
+  pub finished: bool,
+  // the writer who started the calculation, only they can finalize it
+  pub started_by: Address,
+}
+
+// datasets that can be written by separate roles, see `add_writer`
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Dataset {
+  Reputation,
+  PriorVotingHistory,
+  RoundBonusMap,
+  DelegationRanks,
+  TrustMap,
+  PageRankResult,
+  Expertise,
+  TrustAnchors,
+  TrustWeights,
 }
 
 #[contracttype]
@@ -21,21 +104,27 @@ pub enum ReputationCategory {
   Uncategorized = 0,
 }
 
-pub fn reputation_category_from_str(env: &Env, str: String) -> ReputationCategory {
+pub fn reputation_category_from_str(
+  env: &Env,
+  str: String,
+) -> Result<ReputationCategory, ExternalDataProviderError> {
+  if str == String::from_slice(&env, "Uncategorized") {
+    return Ok(ReputationCategory::Uncategorized);
+  }
   if str == String::from_slice(&env, "Poor") {
-    return ReputationCategory::Poor;
+    return Ok(ReputationCategory::Poor);
   }
   if str == String::from_slice(&env, "Average") {
-    return ReputationCategory::Average;
+    return Ok(ReputationCategory::Average);
   }
   if str == String::from_slice(&env, "Good") {
-    return ReputationCategory::Good;
+    return Ok(ReputationCategory::Good);
   }
   if str == String::from_slice(&env, "VeryGood") {
-    return ReputationCategory::VeryGood;
+    return Ok(ReputationCategory::VeryGood);
   }
   if str == String::from_slice(&env, "Excellent") {
-    return ReputationCategory::Excellent;
+    return Ok(ReputationCategory::Excellent);
   }
-  return ReputationCategory::Uncategorized;
+  Err(ExternalDataProviderError::UnknownReputationCategory)
 }
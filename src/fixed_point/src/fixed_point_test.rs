@@ -0,0 +1,74 @@
+use crate::{FixedPoint, FixedPointError};
+
+type Decimal3 = FixedPoint<3>;
+type Decimal9 = FixedPoint<9>;
+
+#[test]
+fn test_arithmetic() {
+  let a = Decimal9::from_raw(1_500_000_000);
+  let b = Decimal9::from_raw(-250_000_000);
+
+  assert!(Decimal9::add(a, b) == Ok(Decimal9::from_raw(1_250_000_000)));
+  assert!(Decimal9::sub(b, a) == Ok(Decimal9::from_raw(-1_750_000_000)));
+  assert!(Decimal9::mul(a, b) == Ok(Decimal9::from_raw(-375_000_000)));
+  assert!(Decimal9::div(b, a) == Ok(Decimal9::from_raw(-166_666_666)));
+  assert!(Decimal9::from_int(2) == Ok(Decimal9::from_raw(2_000_000_000)));
+
+  // the precision is kept where 3 decimal places lose it
+  let third = Decimal9::div(Decimal9::ONE, Decimal9::from_int(3).unwrap()).unwrap();
+  assert!(third.as_raw() == 333_333_333);
+  assert!(
+    Decimal9::mul(
+      Decimal9::from_raw(1_000_000),
+      Decimal9::from_raw(500_000_000)
+    )
+    .unwrap()
+    .as_raw()
+      == 500_000
+  );
+  assert!(
+    Decimal3::mul(Decimal3::from_raw(1), Decimal3::from_raw(500))
+      .unwrap()
+      .as_raw()
+      == 0
+  );
+}
+
+#[test]
+fn test_errors() {
+  let max = Decimal9::from_raw(i128::MAX);
+  assert!(Decimal9::add(max, Decimal9::from_raw(1)) == Err(FixedPointError::Overflow));
+  assert!(
+    Decimal9::sub(Decimal9::from_raw(i128::MIN), Decimal9::from_raw(1))
+      == Err(FixedPointError::Overflow)
+  );
+  assert!(Decimal9::mul(max, Decimal9::ONE) == Err(FixedPointError::Overflow));
+  assert!(Decimal9::div(Decimal9::ONE, Decimal9::ZERO) == Err(FixedPointError::DivisionByZero));
+  assert!(Decimal9::from_int(i128::MAX) == Err(FixedPointError::Overflow));
+}
+
+#[test]
+fn test_decimal_number_conversion() {
+  assert!(Decimal9::from_decimal((1, 500)) == Ok(Decimal9::from_raw(1_500_000_000)));
+  assert!(Decimal3::from_decimal((1, 500)) == Ok(Decimal3::from_raw(1_500)));
+  assert!(FixedPoint::<1>::from_decimal((1, 560)) == Ok(FixedPoint::<1>::from_raw(15)));
+
+  // rounded towards zero
+  assert!(Decimal9::from_raw(1_500_999_999).as_decimal() == Ok((1, 500)));
+  assert!(Decimal9::from_raw(999_999).as_decimal() == Ok((0, 0)));
+  assert!(Decimal9::from_raw(-1).as_decimal() == Ok((0, 0)));
+  assert!(Decimal9::from_raw(-1_000_000).as_decimal() == Err(FixedPointError::Overflow));
+  assert!(
+    Decimal3::from_raw((u32::MAX as i128 + 1) * 1000).as_decimal()
+      == Err(FixedPointError::Overflow)
+  );
+  assert!(
+    Decimal3::from_decimal((u32::MAX, 999))
+      .unwrap()
+      .as_decimal()
+      == Ok((u32::MAX, 999))
+  );
+
+  assert!(Decimal3::from_raw(-1_500).rescale::<9>() == Ok(Decimal9::from_raw(-1_500_000_000)));
+  assert!(Decimal9::from_raw(-1_500_999_999).rescale::<3>() == Ok(Decimal3::from_raw(-1_500)));
+}
@@ -0,0 +1,126 @@
+#![no_std]
+
+// Signed fixed-point numbers shared by the contracts. The value is an i128 with DECIMALS decimal
+// places, so with 9 decimal places the raw value 1_500_000_000 is 1.5.
+// The contracts pass the raw values around, the (u32, u32) DecimalNumber of the older APIs has
+// 3 decimal places and is converted with from_decimal and as_decimal.
+
+// decimal places of DecimalNumber, (1, 500) is 1.5
+pub const DECIMAL_NUMBER_DECIMALS: u32 = 3;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FixedPointError {
+  // the result doesn't fit, including negative numbers converted to DecimalNumber
+  Overflow,
+  DivisionByZero,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct FixedPoint<const DECIMALS: u32> {
+  raw: i128,
+}
+
+// the checked operations mirror DecimalNumberWrapper and return a Result, unlike the ops traits
+#[allow(clippy::should_implement_trait)]
+impl<const DECIMALS: u32> FixedPoint<DECIMALS> {
+  pub const SCALE: i128 = 10_i128.pow(DECIMALS);
+  pub const ZERO: FixedPoint<DECIMALS> = FixedPoint { raw: 0 };
+  pub const ONE: FixedPoint<DECIMALS> = FixedPoint {
+    raw: FixedPoint::<DECIMALS>::SCALE,
+  };
+
+  pub fn from_raw(raw: i128) -> FixedPoint<DECIMALS> {
+    FixedPoint { raw }
+  }
+
+  pub fn as_raw(self) -> i128 {
+    self.raw
+  }
+
+  pub fn from_int(value: i128) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    value
+      .checked_mul(FixedPoint::<DECIMALS>::SCALE)
+      .map(FixedPoint::from_raw)
+      .ok_or(FixedPointError::Overflow)
+  }
+
+  // fractions below the precision are dropped if there are less than 3 decimal places
+  pub fn from_decimal(value: (u32, u32)) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    let raw = value.0 as i128 * 1000 + value.1 as i128;
+    FixedPoint::<DECIMAL_NUMBER_DECIMALS>::from_raw(raw).rescale()
+  }
+
+  // rounded towards zero to 3 decimal places
+  pub fn as_decimal(self) -> Result<(u32, u32), FixedPointError> {
+    let raw = self.rescale::<DECIMAL_NUMBER_DECIMALS>()?.as_raw();
+    if raw < 0 {
+      return Err(FixedPointError::Overflow);
+    }
+    let whole = u32::try_from(raw / 1000).map_err(|_| FixedPointError::Overflow)?;
+    Ok((whole, (raw % 1000) as u32))
+  }
+
+  // rounded towards zero if there are less decimal places
+  pub fn rescale<const OTHER_DECIMALS: u32>(
+    self,
+  ) -> Result<FixedPoint<OTHER_DECIMALS>, FixedPointError> {
+    if OTHER_DECIMALS >= DECIMALS {
+      let factor = 10_i128.pow(OTHER_DECIMALS - DECIMALS);
+      return self
+        .raw
+        .checked_mul(factor)
+        .map(FixedPoint::from_raw)
+        .ok_or(FixedPointError::Overflow);
+    }
+    let factor = 10_i128.pow(DECIMALS - OTHER_DECIMALS);
+    Ok(FixedPoint::from_raw(self.raw / factor))
+  }
+
+  pub fn add(
+    a: FixedPoint<DECIMALS>,
+    b: FixedPoint<DECIMALS>,
+  ) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    a.raw
+      .checked_add(b.raw)
+      .map(FixedPoint::from_raw)
+      .ok_or(FixedPointError::Overflow)
+  }
+
+  pub fn sub(
+    a: FixedPoint<DECIMALS>,
+    b: FixedPoint<DECIMALS>,
+  ) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    a.raw
+      .checked_sub(b.raw)
+      .map(FixedPoint::from_raw)
+      .ok_or(FixedPointError::Overflow)
+  }
+
+  // rounded towards zero
+  pub fn mul(
+    a: FixedPoint<DECIMALS>,
+    b: FixedPoint<DECIMALS>,
+  ) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    a.raw
+      .checked_mul(b.raw)
+      .map(|raw| FixedPoint::from_raw(raw / FixedPoint::<DECIMALS>::SCALE))
+      .ok_or(FixedPointError::Overflow)
+  }
+
+  // rounded towards zero
+  pub fn div(
+    a: FixedPoint<DECIMALS>,
+    b: FixedPoint<DECIMALS>,
+  ) -> Result<FixedPoint<DECIMALS>, FixedPointError> {
+    if b.raw == 0 {
+      return Err(FixedPointError::DivisionByZero);
+    }
+    a.raw
+      .checked_mul(FixedPoint::<DECIMALS>::SCALE)
+      .map(|raw| FixedPoint::from_raw(raw / b.raw))
+      .ok_or(FixedPointError::Overflow)
+  }
+}
+
+#[cfg(test)]
+mod fixed_point_test;
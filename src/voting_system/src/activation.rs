@@ -0,0 +1,166 @@
+use crate::types::{
+  PreciseDecimal, PreciseDecimalNumber, SignedDecimal, SignedDecimalNumber, VotingSystemError,
+};
+use soroban_sdk::{contracttype, Vec};
+
+// the input and the output have 9 decimal places, 1_500_000_000 is 1.5
+const RAW_ONE: u128 = PreciseDecimal::SCALE as u128;
+// the configured values have 3 decimal places and are scaled up to the ones of the input
+const VALUE_SCALE: i128 = PreciseDecimal::SCALE / SignedDecimal::SCALE;
+// log2 is calculated with 18 decimal places and rounded down to 9 afterwards
+const LOG_PRECISION: u128 = 1_000_000_000_000_000_000;
+const LOG_ITERATIONS: u32 = 30;
+// the configured values are at most a billion (either sign), far beyond any voting power, so the
+// interpolation between the breakpoints can't overflow
+const MAX_VALUE: SignedDecimalNumber = 1_000_000_000 * SignedDecimal::SCALE;
+
+// shapes the output of a neuron before it is multiplied by the neuron's weight,
+// the values are raw decimal numbers with 3 decimal places, the input can be negative
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Activation {
+  // f(x) = x
+  Linear,
+  // (min, max)
+  Clamp(SignedDecimalNumber, SignedDecimalNumber),
+  // (threshold, value below the threshold, value from the threshold up)
+  Step(
+    SignedDecimalNumber,
+    SignedDecimalNumber,
+    SignedDecimalNumber,
+  ),
+  // f(x) = log2(1 + x), 0 for negative x
+  Logarithmic,
+  // f(x) = sqrt(x), e.g. for quadratic voting, 0 for negative x
+  SquareRoot,
+  // (x, y) breakpoints sorted by x, linear between them and flat outside of them
+  Piecewise(Vec<(SignedDecimalNumber, SignedDecimalNumber)>),
+}
+
+impl Activation {
+  pub fn validate(&self) -> Result<(), VotingSystemError> {
+    match self {
+      Activation::Clamp(min, max) => {
+        if min > max {
+          return Err(VotingSystemError::InvalidActivation);
+        }
+        Activation::validate_values(&[*min, *max])?;
+      }
+      Activation::Step(threshold, below, above) => {
+        Activation::validate_values(&[*threshold, *below, *above])?;
+      }
+      Activation::Piecewise(breakpoints) => {
+        if breakpoints.is_empty() {
+          return Err(VotingSystemError::InvalidActivation);
+        }
+        let mut previous_x: Option<SignedDecimalNumber> = None;
+        for (x, y) in breakpoints.iter() {
+          if previous_x.is_some_and(|previous_x| previous_x >= x) {
+            return Err(VotingSystemError::InvalidActivation);
+          }
+          Activation::validate_values(&[x, y])?;
+          previous_x = Some(x);
+        }
+      }
+      _ => (),
+    }
+    Ok(())
+  }
+
+  fn validate_values(values: &[SignedDecimalNumber]) -> Result<(), VotingSystemError> {
+    if values.iter().any(|value| value.abs() > MAX_VALUE) {
+      return Err(VotingSystemError::InvalidActivation);
+    }
+    Ok(())
+  }
+
+  // none of the activations can overflow, the results are at most as big as the input or the
+  // configured values
+  pub fn apply(&self, x: PreciseDecimalNumber) -> PreciseDecimalNumber {
+    match self {
+      Activation::Linear => x,
+      Activation::Clamp(min, max) => x.clamp(Activation::scale(*min), Activation::scale(*max)),
+      Activation::Step(threshold, below, above) => {
+        if x >= Activation::scale(*threshold) {
+          Activation::scale(*above)
+        } else {
+          Activation::scale(*below)
+        }
+      }
+      Activation::Logarithmic => Activation::log2_one_plus(Activation::as_u64(x)) as i128,
+      Activation::SquareRoot => Activation::sqrt(Activation::as_u64(x)) as i128,
+      Activation::Piecewise(breakpoints) => Activation::interpolate(breakpoints, x),
+    }
+  }
+
+  // the validated values can't overflow, the others are saturated
+  fn scale(value: SignedDecimalNumber) -> PreciseDecimalNumber {
+    value.saturating_mul(VALUE_SCALE)
+  }
+
+  // negative inputs are 0, the ones above u64::MAX are capped, far beyond any voting power
+  fn as_u64(x: PreciseDecimalNumber) -> u64 {
+    x.clamp(0, u64::MAX as i128) as u64
+  }
+
+  // binary logarithm by repeated squaring, every iteration adds one bit of the fractional part
+  fn log2_one_plus(x: u64) -> u64 {
+    let mut y = (x as u128 + RAW_ONE) * (LOG_PRECISION / RAW_ONE);
+    let mut result: u128 = 0;
+    // y is below 2 afterwards, so squaring it can't overflow
+    while y >= 2 * LOG_PRECISION {
+      y /= 2;
+      result += LOG_PRECISION;
+    }
+    let mut bit = LOG_PRECISION / 2;
+    for _ in 0..LOG_ITERATIONS {
+      y = y * y / LOG_PRECISION;
+      if y >= 2 * LOG_PRECISION {
+        y /= 2;
+        result += bit;
+      }
+      bit /= 2;
+    }
+    (result / (LOG_PRECISION / RAW_ONE)) as u64
+  }
+
+  // sqrt(x / ONE) * ONE == sqrt(x * ONE), rounded down
+  fn sqrt(x: u64) -> u64 {
+    let value = x as u128 * RAW_ONE;
+    let mut low: u128 = 0;
+    let mut high: u128 = value.min(u64::MAX as u128) + 1;
+    while low + 1 < high {
+      let middle = (low + high) / 2;
+      if middle * middle <= value {
+        low = middle;
+      } else {
+        high = middle;
+      }
+    }
+    low as u64
+  }
+
+  fn interpolate(
+    breakpoints: &Vec<(SignedDecimalNumber, SignedDecimalNumber)>,
+    x: PreciseDecimalNumber,
+  ) -> PreciseDecimalNumber {
+    let (first_x, first_y) = breakpoints.first().unwrap();
+    let (first_x, first_y) = (Activation::scale(first_x), Activation::scale(first_y));
+    if x <= first_x {
+      return first_y;
+    }
+    let mut previous = (first_x, first_y);
+    for (next_x, next_y) in breakpoints.iter().skip(1) {
+      let (next_x, next_y) = (Activation::scale(next_x), Activation::scale(next_y));
+      if x <= next_x {
+        let (previous_x, previous_y) = previous;
+        let progress = x - previous_x;
+        let length = next_x - previous_x;
+        let rise = next_y - previous_y;
+        return previous_y + rise * progress / length;
+      }
+      previous = (next_x, next_y);
+    }
+    previous.1
+  }
+}
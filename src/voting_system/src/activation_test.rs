@@ -0,0 +1,133 @@
+use soroban_sdk::{vec, Env, Vec};
+
+use crate::{
+  activation::Activation,
+  types::{PreciseDecimalNumber, SignedDecimalNumber, VotingSystemError},
+};
+
+// the activations work on voting powers with 9 decimal places and are configured with 3 of them
+fn precise(raw: SignedDecimalNumber) -> PreciseDecimalNumber {
+  raw * 1_000_000
+}
+
+#[test]
+pub fn test_linear_clamp_and_step() {
+  assert!(Activation::Linear.apply(precise(1500)) == precise(1500));
+
+  let clamp = Activation::Clamp(500, 1000);
+  assert!(clamp.apply(precise(1500)) == precise(1000));
+  assert!(clamp.apply(precise(200)) == precise(500));
+  assert!(clamp.apply(precise(700)) == precise(700));
+
+  let step = Activation::Step(1000, 0, 2000);
+  assert!(step.apply(precise(999)) == precise(0));
+  assert!(step.apply(precise(1000)) == precise(2000));
+  assert!(step.apply(precise(5000)) == precise(2000));
+
+  // negative inputs
+  assert!(Activation::Linear.apply(precise(-1500)) == precise(-1500));
+  assert!(clamp.apply(precise(-1500)) == precise(500));
+  assert!(step.apply(precise(-1)) == precise(0));
+}
+
+#[test]
+pub fn test_logarithmic_and_square_root() {
+  assert!(Activation::Logarithmic.apply(precise(0)) == precise(0));
+  assert!(Activation::Logarithmic.apply(precise(1000)) == precise(1000));
+  assert!(Activation::Logarithmic.apply(precise(3000)) == precise(2000));
+  // log2(2.5) = 1.321928094...
+  assert!(Activation::Logarithmic.apply(precise(1500)) == 1_321_928_094);
+  // log2(1001) = 9.967226258...
+  assert!(Activation::Logarithmic.apply(precise(1_000_000)) == 9_967_226_258);
+
+  assert!(Activation::SquareRoot.apply(precise(0)) == precise(0));
+  assert!(Activation::SquareRoot.apply(precise(4000)) == precise(2000));
+  assert!(Activation::SquareRoot.apply(precise(250)) == precise(500));
+  // sqrt(2) = 1.414213562...
+  assert!(Activation::SquareRoot.apply(precise(2000)) == 1_414_213_562);
+
+  // both are 0 for negative inputs
+  assert!(Activation::Logarithmic.apply(precise(-1500)) == precise(0));
+  assert!(Activation::SquareRoot.apply(precise(-4000)) == precise(0));
+}
+
+#[test]
+pub fn test_piecewise() {
+  let env = Env::default();
+  let piecewise = Activation::Piecewise(vec![&env, (1000, 0), (2000, 2000), (4000, 3000)]);
+  assert!(piecewise.validate() == Ok(()));
+
+  // flat before the first and after the last breakpoint
+  assert!(piecewise.apply(precise(500)) == precise(0));
+  assert!(piecewise.apply(precise(-500)) == precise(0));
+  assert!(piecewise.apply(precise(9000)) == precise(3000));
+  // linear between the breakpoints
+  assert!(piecewise.apply(precise(1500)) == precise(1000));
+  assert!(piecewise.apply(precise(3000)) == precise(2500));
+  assert!(piecewise.apply(precise(2000)) == precise(2000));
+
+  // decreasing curves are allowed as well
+  let decreasing = Activation::Piecewise(vec![&env, (0, 2000), (2000, 0)]);
+  assert!(decreasing.apply(precise(500)) == precise(1500));
+}
+
+#[test]
+pub fn test_validate() {
+  let env = Env::default();
+  assert!(Activation::Linear.validate() == Ok(()));
+  assert!(Activation::Clamp(1000, 1000).validate() == Ok(()));
+  assert!(Activation::Clamp(1001, 1000).validate() == Err(VotingSystemError::InvalidActivation));
+
+  let empty: Vec<(i128, i128)> = Vec::new(&env);
+  assert!(Activation::Piecewise(empty).validate() == Err(VotingSystemError::InvalidActivation));
+  assert!(
+    Activation::Piecewise(vec![&env, (1000, 0), (1000, 500)]).validate()
+      == Err(VotingSystemError::InvalidActivation)
+  );
+  assert!(
+    Activation::Piecewise(vec![&env, (2000, 0), (1000, 500)]).validate()
+      == Err(VotingSystemError::InvalidActivation)
+  );
+
+  // the values are limited to a billion either way
+  assert!(Activation::Clamp(-1_000_000_000_000, 1_000_000_000_000).validate() == Ok(()));
+  assert!(
+    Activation::Clamp(-1_000_000_000_001, 0).validate()
+      == Err(VotingSystemError::InvalidActivation)
+  );
+  assert!(
+    Activation::Step(0, 0, 1_000_000_000_001).validate()
+      == Err(VotingSystemError::InvalidActivation)
+  );
+  assert!(
+    Activation::Piecewise(vec![&env, (0, 0), (1_000_000_000_001, 1000)]).validate()
+      == Err(VotingSystemError::InvalidActivation)
+  );
+}
+
+#[test]
+pub fn test_negative_values() {
+  let env = Env::default();
+
+  // a penalty below -1 is kept, anything above 1 is capped
+  let clamp = Activation::Clamp(-1000, 1000);
+  assert!(clamp.validate() == Ok(()));
+  assert!(clamp.apply(precise(-500)) == precise(-500));
+  assert!(clamp.apply(precise(-1500)) == precise(-1000));
+  assert!(clamp.apply(precise(1500)) == precise(1000));
+  assert!(Activation::Clamp(-2000, -1000).validate() == Ok(()));
+  assert!(Activation::Clamp(-1000, -2000).validate() == Err(VotingSystemError::InvalidActivation));
+
+  // negative results get a penalty, the others nothing
+  let step = Activation::Step(0, -1000, 0);
+  assert!(step.apply(precise(-1)) == precise(-1000));
+  assert!(step.apply(precise(0)) == precise(0));
+  assert!(step.apply(precise(700)) == precise(0));
+
+  let piecewise = Activation::Piecewise(vec![&env, (-2000, -1000), (0, 0), (2000, 500)]);
+  assert!(piecewise.validate() == Ok(()));
+  assert!(piecewise.apply(precise(-5000)) == precise(-1000));
+  assert!(piecewise.apply(precise(-1000)) == precise(-500));
+  assert!(piecewise.apply(precise(1000)) == precise(250));
+  assert!(piecewise.apply(precise(5000)) == precise(500));
+}
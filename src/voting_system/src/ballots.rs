@@ -0,0 +1,158 @@
+use crate::{
+  submissions,
+  types::{Vote, BALLOT_TTL_EXTEND_TO, BALLOT_TTL_THRESHOLD},
+  DataKey,
+};
+use soroban_sdk::{Env, Map, String, Vec};
+
+// every ballot is kept in its own persistent entry, together with the indexes below
+// so a single vote does not have to read and rewrite the ballots of all the other voters
+
+fn extend_ttl(env: &Env, key: &DataKey) {
+  env
+    .storage()
+    .persistent()
+    .extend_ttl(key, BALLOT_TTL_THRESHOLD, BALLOT_TTL_EXTEND_TO);
+}
+
+fn get_index(env: &Env, key: &DataKey) -> Map<String, ()> {
+  env.storage().persistent().get(key).unwrap_or(Map::new(env))
+}
+
+fn set_index(env: &Env, key: &DataKey, index: &Map<String, ()>) {
+  if index.is_empty() {
+    env.storage().persistent().remove(key);
+    return;
+  }
+  env.storage().persistent().set(key, index);
+  extend_ttl(env, key);
+}
+
+fn add_to_index(env: &Env, key: &DataKey, id: String) {
+  let mut index = get_index(env, key);
+  if index.contains_key(id.clone()) {
+    extend_ttl(env, key);
+    return;
+  }
+  index.set(id, ());
+  set_index(env, key, &index);
+}
+
+fn remove_from_index(env: &Env, key: &DataKey, id: String) {
+  let mut index = get_index(env, key);
+  if index.remove(id).is_some() {
+    set_index(env, key, &index);
+  }
+}
+
+pub fn get_vote(env: &Env, round_id: u32, submission_id: String, voter_id: String) -> Option<Vote> {
+  env
+    .storage()
+    .persistent()
+    .get(&DataKey::Ballot(round_id, submission_id, voter_id))
+}
+
+pub fn set_vote(env: &Env, round_id: u32, submission_id: String, voter_id: String, vote: Vote) {
+  let key = DataKey::Ballot(round_id, submission_id.clone(), voter_id.clone());
+  env.storage().persistent().set(&key, &vote);
+  extend_ttl(env, &key);
+
+  add_to_index(
+    env,
+    &DataKey::SubmissionVoters(round_id, submission_id.clone()),
+    voter_id.clone(),
+  );
+  add_to_index(
+    env,
+    &DataKey::VoterSubmissions(round_id, voter_id.clone()),
+    submission_id,
+  );
+  add_to_index(env, &DataKey::Voters(round_id), voter_id);
+}
+
+pub fn remove_vote(env: &Env, round_id: u32, submission_id: String, voter_id: String) {
+  let key = DataKey::Ballot(round_id, submission_id.clone(), voter_id.clone());
+  if !env.storage().persistent().has(&key) {
+    return;
+  }
+  env.storage().persistent().remove(&key);
+
+  remove_from_index(
+    env,
+    &DataKey::SubmissionVoters(round_id, submission_id.clone()),
+    voter_id.clone(),
+  );
+  let voter_submissions_key = DataKey::VoterSubmissions(round_id, voter_id.clone());
+  remove_from_index(env, &voter_submissions_key, submission_id);
+  // the voter is no longer listed once they have no votes left in the round
+  if get_index(env, &voter_submissions_key).is_empty() {
+    remove_from_index(env, &DataKey::Voters(round_id), voter_id);
+  }
+}
+
+pub fn has_votes(env: &Env, round_id: u32, submission_id: String) -> bool {
+  env
+    .storage()
+    .persistent()
+    .has(&DataKey::SubmissionVoters(round_id, submission_id))
+}
+
+pub fn get_voters(env: &Env, round_id: u32) -> Vec<String> {
+  get_index(env, &DataKey::Voters(round_id)).keys()
+}
+
+// Map<voter_id, vote>
+pub fn get_votes_for_submission(
+  env: &Env,
+  round_id: u32,
+  submission_id: String,
+) -> Map<String, Vote> {
+  let mut votes: Map<String, Vote> = Map::new(env);
+  for voter_id in get_index(
+    env,
+    &DataKey::SubmissionVoters(round_id, submission_id.clone()),
+  )
+  .keys()
+  {
+    if let Some(vote) = get_vote(env, round_id, submission_id.clone(), voter_id.clone()) {
+      votes.set(voter_id, vote);
+    }
+  }
+  votes
+}
+
+// Map<submission_id, vote>
+pub fn get_votes_for_voter(env: &Env, round_id: u32, voter_id: String) -> Map<String, Vote> {
+  let mut votes: Map<String, Vote> = Map::new(env);
+  for submission_id in get_index(env, &DataKey::VoterSubmissions(round_id, voter_id.clone())).keys()
+  {
+    if let Some(vote) = get_vote(env, round_id, submission_id.clone(), voter_id.clone()) {
+      votes.set(submission_id, vote);
+    }
+  }
+  votes
+}
+
+// extends the lifetime of all the ballots of the round and their indexes
+pub fn extend_round_ttl(env: &Env, round_id: u32) {
+  for submission_id in submissions::get_ids(env, round_id) {
+    let submission_voters_key = DataKey::SubmissionVoters(round_id, submission_id.clone());
+    if !env.storage().persistent().has(&submission_voters_key) {
+      continue;
+    }
+    extend_ttl(env, &submission_voters_key);
+    for voter_id in get_index(env, &submission_voters_key).keys() {
+      extend_ttl(
+        env,
+        &DataKey::Ballot(round_id, submission_id.clone(), voter_id),
+      );
+    }
+  }
+  let voters_key = DataKey::Voters(round_id);
+  if env.storage().persistent().has(&voters_key) {
+    extend_ttl(env, &voters_key);
+    for voter_id in get_voters(env, round_id) {
+      extend_ttl(env, &DataKey::VoterSubmissions(round_id, voter_id));
+    }
+  }
+}
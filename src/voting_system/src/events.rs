@@ -0,0 +1,156 @@
+use crate::activation::Activation;
+use crate::neural_governance::VotingPowerBounds;
+use crate::tally::SubmissionResult;
+use crate::types::{
+  DecimalNumber, LayerAggregator, LayerInputMode, NeuronType, SubmissionStatus, Vote,
+};
+use soroban_sdk::{symbol_short, Address, Env, Map, String, Vec};
+
+// Every event has two Symbol topics: (category, action). The data is a tuple described below.
+//
+// ("vote", "cast")        (round_id, submission_id, voter_id, vote) - first vote of the voter for the submission
+// ("vote", "changed")     (round_id, submission_id, voter_id, vote) - the voter replaced their vote
+// ("vote", "removed")     (round_id, submission_id, voter_id)
+// ("delegate", "set")     (round_id, voter_id, delegatees)
+// ("sub", "added")        (round_id, submission_id)
+// ("sub", "status")       (round_id, submission_id, status)
+// ("layer", "added")      layer_id
+// ("layer", "removed")    layer_id
+// ("layer", "aggr")       (layer_id, aggregator)
+// ("layer", "input")      (layer_id, input_mode)
+// ("neuron", "added")     (layer_id, neuron)
+// ("neuron", "removed")   (layer_id, neuron)
+// ("neuron", "weight")    (layer_id, neuron, weight)
+// ("neuron", "activ")     (layer_id, neuron, activation)
+// ("gov", "migrated")     number of migrated layers
+// ("gov", "bounds")       VotingPowerBounds
+// ("provider", "set")     external_data_provider_address
+// ("tally", "results")    (round_id, Map<submission_id, SubmissionResult>)
+
+pub fn vote_cast(env: &Env, round_id: u32, submission_id: String, voter_id: String, vote: Vote) {
+  env.events().publish(
+    (symbol_short!("vote"), symbol_short!("cast")),
+    (round_id, submission_id, voter_id, vote),
+  );
+}
+
+pub fn vote_changed(env: &Env, round_id: u32, submission_id: String, voter_id: String, vote: Vote) {
+  env.events().publish(
+    (symbol_short!("vote"), symbol_short!("changed")),
+    (round_id, submission_id, voter_id, vote),
+  );
+}
+
+pub fn vote_removed(env: &Env, round_id: u32, submission_id: String, voter_id: String) {
+  env.events().publish(
+    (symbol_short!("vote"), symbol_short!("removed")),
+    (round_id, submission_id, voter_id),
+  );
+}
+
+pub fn delegatees_set(env: &Env, round_id: u32, voter_id: String, delegatees: Vec<String>) {
+  env.events().publish(
+    (symbol_short!("delegate"), symbol_short!("set")),
+    (round_id, voter_id, delegatees),
+  );
+}
+
+pub fn submission_added(env: &Env, round_id: u32, submission_id: String) {
+  env.events().publish(
+    (symbol_short!("sub"), symbol_short!("added")),
+    (round_id, submission_id),
+  );
+}
+
+pub fn submission_status_set(
+  env: &Env,
+  round_id: u32,
+  submission_id: String,
+  status: SubmissionStatus,
+) {
+  env.events().publish(
+    (symbol_short!("sub"), symbol_short!("status")),
+    (round_id, submission_id, status),
+  );
+}
+
+pub fn layer_added(env: &Env, layer_id: u32) {
+  env
+    .events()
+    .publish((symbol_short!("layer"), symbol_short!("added")), layer_id);
+}
+
+pub fn layer_removed(env: &Env, layer_id: u32) {
+  env
+    .events()
+    .publish((symbol_short!("layer"), symbol_short!("removed")), layer_id);
+}
+
+pub fn layer_aggregator_set(env: &Env, layer_id: u32, aggregator: LayerAggregator) {
+  env.events().publish(
+    (symbol_short!("layer"), symbol_short!("aggr")),
+    (layer_id, aggregator),
+  );
+}
+
+pub fn layer_input_mode_set(env: &Env, layer_id: u32, input_mode: LayerInputMode) {
+  env.events().publish(
+    (symbol_short!("layer"), symbol_short!("input")),
+    (layer_id, input_mode),
+  );
+}
+
+pub fn neuron_added(env: &Env, layer_id: u32, neuron: NeuronType) {
+  env.events().publish(
+    (symbol_short!("neuron"), symbol_short!("added")),
+    (layer_id, neuron),
+  );
+}
+
+pub fn neuron_removed(env: &Env, layer_id: u32, neuron: NeuronType) {
+  env.events().publish(
+    (symbol_short!("neuron"), symbol_short!("removed")),
+    (layer_id, neuron),
+  );
+}
+
+pub fn neuron_weight_set(env: &Env, layer_id: u32, neuron: NeuronType, weight: DecimalNumber) {
+  env.events().publish(
+    (symbol_short!("neuron"), symbol_short!("weight")),
+    (layer_id, neuron, weight),
+  );
+}
+
+pub fn neuron_activation_set(env: &Env, layer_id: u32, neuron: NeuronType, activation: Activation) {
+  env.events().publish(
+    (symbol_short!("neuron"), symbol_short!("activ")),
+    (layer_id, neuron, activation),
+  );
+}
+
+pub fn voting_power_bounds_set(env: &Env, voting_power_bounds: VotingPowerBounds) {
+  env.events().publish(
+    (symbol_short!("gov"), symbol_short!("bounds")),
+    voting_power_bounds,
+  );
+}
+
+pub fn neural_governance_migrated(env: &Env, layers: u32) {
+  env
+    .events()
+    .publish((symbol_short!("gov"), symbol_short!("migrated")), layers);
+}
+
+pub fn external_data_provider_set(env: &Env, external_data_provider_address: Address) {
+  env.events().publish(
+    (symbol_short!("provider"), symbol_short!("set")),
+    external_data_provider_address,
+  );
+}
+
+pub fn tally_results(env: &Env, round_id: u32, results: Map<String, SubmissionResult>) {
+  env.events().publish(
+    (symbol_short!("tally"), symbol_short!("results")),
+    (round_id, results),
+  );
+}
@@ -1,109 +1,295 @@
 use crate::{
+  activation::Activation,
   external_data_provider_contract,
-  types::{DecimalNumber, LayerAggregator, NeuronType, VotingSystemError},
+  types::{
+    LayerAggregator, LayerInputMode, NeuronType, PreciseDecimal, PreciseDecimalNumber,
+    SignedDecimal, VotingSystemError, INITIAL_VOTING_POWER,
+  },
 };
-use soroban_decimal_numbers::DecimalNumberWrapper;
 use soroban_sdk::{contracttype, Env, Map, String, Vec};
 
 use crate::neurons::{
-  assigned_reputation_neuron, dummy_neuron, prior_voting_history_neuron, trust_graph_neuron,
+  assigned_reputation_neuron, category_expertise_neuron, dummy_neuron, external_neuron,
+  prior_voting_history_neuron, trust_graph_neuron,
 };
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeuronConfig {
+  // raw decimal number
+  pub weight: u32,
+  pub activation: Activation,
+}
+
+// how a neuron contributed to the result of its layer for a single voter
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeuronExplanation {
+  pub neuron: NeuronType,
+  // the result of the neuron's oracle_function
+  pub raw_output: PreciseDecimalNumber,
+  // combined with the previous layer result and passed through the activation function
+  pub activated_output: PreciseDecimalNumber,
+  // multiplied by the neuron's weight, this is what the layer aggregator gets
+  pub weighted_output: PreciseDecimalNumber,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Layer {
   pub id: u32,
-  // neuron type, neuron raw weight
-  pub neurons: Map<NeuronType, u32>,
+  pub neurons: Map<NeuronType, NeuronConfig>,
   pub aggregator: LayerAggregator,
+  pub input_mode: LayerInputMode,
 }
 
 impl Layer {
+  #[allow(clippy::too_many_arguments)]
   pub fn execute_layer(
     &self,
     env: Env,
     voter_id: String,
-    _submission_id: String,
-    previous_layer_vote: (u32, u32),
+    submission_id: String,
+    // category of the submission in the registry, if it is registered
+    submission_category: Option<String>,
+    // the first layer has no previous layer
+    previous_layer_vote: Option<PreciseDecimalNumber>,
     external_data_provider_client: &external_data_provider_contract::Client,
-  ) -> Result<Vec<(u32, u32)>, VotingSystemError> {
+    // filled in only when the voting power is explained, so the tally doesn't pay for it
+    mut explanation: Option<&mut Vec<NeuronExplanation>>,
+  ) -> Result<Vec<PreciseDecimalNumber>, VotingSystemError> {
     if self.aggregator == LayerAggregator::Unknown {
       return Err(VotingSystemError::LayerAggregatorNotSet);
     }
 
-    let mut neuron_votes: Vec<(u32, u32)> = Vec::new(&env);
+    let mut neuron_votes: Vec<PreciseDecimalNumber> = Vec::new(&env);
     if self.neurons.is_empty() {
       return Err(VotingSystemError::NoNeuronsExist);
     }
 
-    for (neuron, raw_weight) in self.neurons.iter() {
-      let raw_neuron_vote: DecimalNumber = match neuron {
-        NeuronType::Dummy => dummy_neuron::oracle_function(env.clone(), voter_id.clone())?,
-        NeuronType::AssignedReputation => assigned_reputation_neuron::oracle_function(
+    let neuron_input = match self.input_mode {
+      LayerInputMode::Ignored => INITIAL_VOTING_POWER,
+      _ => previous_layer_vote.unwrap_or(INITIAL_VOTING_POWER),
+    };
+    for (neuron, neuron_config) in self.neurons.iter() {
+      // only the external neurons can return negative votes, e.g. as a penalty
+      let raw_neuron_vote: PreciseDecimalNumber = match neuron.clone() {
+        NeuronType::Dummy => PreciseDecimal::from_decimal(dummy_neuron::oracle_function(
           env.clone(),
           voter_id.clone(),
-          &external_data_provider_client,
-        )?,
-        NeuronType::PriorVotingHistory => prior_voting_history_neuron::oracle_function(
+          submission_id.clone(),
+        )?)?
+        .as_raw(),
+        NeuronType::AssignedReputation => {
+          PreciseDecimal::from_decimal(assigned_reputation_neuron::oracle_function(
+            env.clone(),
+            voter_id.clone(),
+            submission_id.clone(),
+            &external_data_provider_client,
+          )?)?
+          .as_raw()
+        }
+        NeuronType::PriorVotingHistory => {
+          PreciseDecimal::from_decimal(prior_voting_history_neuron::oracle_function(
+            env.clone(),
+            voter_id.clone(),
+            submission_id.clone(),
+            &external_data_provider_client,
+          )?)?
+          .as_raw()
+        }
+        NeuronType::TrustGraph => trust_graph_neuron::oracle_function(
           env.clone(),
           voter_id.clone(),
+          submission_id.clone(),
           &external_data_provider_client,
         )?,
-        NeuronType::TrustGraph => trust_graph_neuron::oracle_function(
+        NeuronType::CategoryExpertise => {
+          PreciseDecimal::from_decimal(category_expertise_neuron::oracle_function(
+            env.clone(),
+            voter_id.clone(),
+            submission_id.clone(),
+            submission_category.clone(),
+            &external_data_provider_client,
+          )?)?
+          .as_raw()
+        }
+        NeuronType::External(neuron_address) => external_neuron::oracle_function(
           env.clone(),
+          neuron_address,
           voter_id.clone(),
-          &external_data_provider_client,
+          submission_id.clone(),
+          neuron_input,
         )?,
       };
-      let neuron_vote = self.run_neuron_weight_function(
-        DecimalNumberWrapper::add(
-          DecimalNumberWrapper::from(raw_neuron_vote),
-          DecimalNumberWrapper::from(previous_layer_vote),
-        )
-        .as_tuple(),
-        DecimalNumberWrapper::from(raw_weight).as_tuple(),
-      );
+      let activated_neuron_vote = neuron_config
+        .activation
+        .apply(self.combine_with_previous_layer(raw_neuron_vote, previous_layer_vote)?);
+      let neuron_vote =
+        self.run_neuron_weight_function(activated_neuron_vote, neuron_config.weight)?;
+      if let Some(explanation) = explanation.as_mut() {
+        explanation.push_back(NeuronExplanation {
+          neuron,
+          raw_output: raw_neuron_vote,
+          activated_output: activated_neuron_vote,
+          weighted_output: neuron_vote,
+        });
+      }
       neuron_votes.push_back(neuron_vote);
     }
     Ok(neuron_votes)
   }
 
+  fn combine_with_previous_layer(
+    &self,
+    raw_neuron_vote: PreciseDecimalNumber,
+    previous_layer_vote: Option<PreciseDecimalNumber>,
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
+    let Some(previous_layer_vote) = previous_layer_vote else {
+      return Ok(raw_neuron_vote);
+    };
+    let raw_neuron_vote = PreciseDecimal::from_raw(raw_neuron_vote);
+    let previous_layer_vote = PreciseDecimal::from_raw(previous_layer_vote);
+    let combined = match self.input_mode {
+      LayerInputMode::Additive => PreciseDecimal::add(raw_neuron_vote, previous_layer_vote)?,
+      LayerInputMode::Multiplicative => PreciseDecimal::mul(raw_neuron_vote, previous_layer_vote)?,
+      LayerInputMode::Ignored | LayerInputMode::Explicit => raw_neuron_vote,
+    };
+    Ok(combined.as_raw())
+  }
+
   fn run_neuron_weight_function(
     &self,
-    raw_neuron_vote: (u32, u32),
-    weight: DecimalNumber,
-  ) -> (u32, u32) {
-    DecimalNumberWrapper::mul(
-      DecimalNumberWrapper::from(raw_neuron_vote),
-      DecimalNumberWrapper::from(weight),
+    raw_neuron_vote: PreciseDecimalNumber,
+    // raw decimal number
+    weight: u32,
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
+    Ok(
+      PreciseDecimal::mul(
+        PreciseDecimal::from_raw(raw_neuron_vote),
+        Layer::precise_weight(weight)?,
+      )?
+      .as_raw(),
     )
-    .as_tuple()
+  }
+
+  // the weights are configured with the 3 decimal places of DecimalNumber
+  fn precise_weight(weight: u32) -> Result<PreciseDecimal, VotingSystemError> {
+    Ok(SignedDecimal::from_raw(weight as i128).rescale()?)
   }
 
   pub fn run_layer_aggregator(
     &self,
-    neuron_votes: Vec<(u32, u32)>,
-  ) -> Result<(u32, u32), VotingSystemError> {
+    neuron_votes: Vec<PreciseDecimalNumber>,
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
     match self.aggregator {
       LayerAggregator::Unknown => {
         return Err(VotingSystemError::CannotRunUnknownLayerAggregator);
       }
-      LayerAggregator::Sum => neuron_votes
+      LayerAggregator::Sum => {
+        if neuron_votes.is_empty() {
+          return Err(VotingSystemError::ReducingvotesForSumAggregatorFailed);
+        }
+        Ok(Layer::sum(&neuron_votes)?.as_raw())
+      }
+      LayerAggregator::Product => {
+        let mut neuron_votes_iter = neuron_votes.iter();
+        let mut product = PreciseDecimal::from_raw(
+          neuron_votes_iter
+            .next()
+            .ok_or(VotingSystemError::ReducingvotesForProductAggregatorFailed)?,
+        );
+        for neuron_vote in neuron_votes_iter {
+          product = PreciseDecimal::mul(product, PreciseDecimal::from_raw(neuron_vote))?;
+        }
+        Ok(product.as_raw())
+      }
+      LayerAggregator::Min => neuron_votes
         .iter()
-        .reduce(|acc, item| {
-          let acc = DecimalNumberWrapper::from(acc);
-          let item = DecimalNumberWrapper::from(item);
-          DecimalNumberWrapper::add(acc, item).as_tuple()
-        })
-        .ok_or(VotingSystemError::ReducingvotesForSumAggregatorFailed),
-      LayerAggregator::Product => neuron_votes
+        .min()
+        .ok_or(VotingSystemError::ReducingvotesForMinAggregatorFailed),
+      LayerAggregator::Max => neuron_votes
         .iter()
-        .reduce(|acc, item| {
-          let acc = DecimalNumberWrapper::from(acc);
-          let item = DecimalNumberWrapper::from(item);
-          DecimalNumberWrapper::mul(acc, item).as_tuple()
-        })
-        .ok_or(VotingSystemError::ReducingvotesForProductAggregatorFailed),
+        .max()
+        .ok_or(VotingSystemError::ReducingvotesForMaxAggregatorFailed),
+      LayerAggregator::Average => {
+        if neuron_votes.is_empty() {
+          return Err(VotingSystemError::ReducingvotesForAverageAggregatorFailed);
+        }
+        Ok(
+          PreciseDecimal::div(
+            Layer::sum(&neuron_votes)?,
+            PreciseDecimal::from_int(neuron_votes.len() as i128)?,
+          )?
+          .as_raw(),
+        )
+      }
+      LayerAggregator::Median => Layer::median(&neuron_votes)?
+        .ok_or(VotingSystemError::ReducingvotesForMedianAggregatorFailed),
+      LayerAggregator::WeightedAverage => {
+        if neuron_votes.is_empty() {
+          return Err(VotingSystemError::ReducingvotesForWeightedAverageAggregatorFailed);
+        }
+        // the neuron votes are already multiplied by the weights of the neurons
+        let mut weights_sum: PreciseDecimal = Default::default();
+        for neuron_config in self.neurons.values() {
+          weights_sum =
+            PreciseDecimal::add(weights_sum, Layer::precise_weight(neuron_config.weight)?)?;
+        }
+        if weights_sum.as_raw() == 0 {
+          return Err(VotingSystemError::NeuronWeightsSumToZero);
+        }
+        Ok(PreciseDecimal::div(Layer::sum(&neuron_votes)?, weights_sum)?.as_raw())
+      }
+    }
+  }
+
+  fn median(
+    votes: &Vec<PreciseDecimalNumber>,
+  ) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+    Layer::median_of_sorted(&Layer::sort(votes))
+  }
+
+  // also used for the voting power cap relative to the median voter, with the voters sorted by tally
+  pub fn median_of_sorted(
+    sorted_votes: &Vec<PreciseDecimalNumber>,
+  ) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+    if sorted_votes.is_empty() {
+      return Ok(None);
+    }
+    let middle = sorted_votes.len() / 2;
+    if sorted_votes.len() % 2 == 1 {
+      return Ok(sorted_votes.get(middle));
+    }
+    // the average of the two middle votes
+    let median = PreciseDecimal::div(
+      PreciseDecimal::add(
+        PreciseDecimal::from_raw(sorted_votes.get(middle - 1).unwrap()),
+        PreciseDecimal::from_raw(sorted_votes.get(middle).unwrap()),
+      )?,
+      PreciseDecimal::from_int(2)?,
+    )?;
+    Ok(Some(median.as_raw()))
+  }
+
+  fn sum(neuron_votes: &Vec<PreciseDecimalNumber>) -> Result<PreciseDecimal, VotingSystemError> {
+    let mut sum: PreciseDecimal = Default::default();
+    for neuron_vote in neuron_votes.iter() {
+      sum = PreciseDecimal::add(sum, PreciseDecimal::from_raw(neuron_vote))?;
+    }
+    Ok(sum)
+  }
+
+  // the votes in ascending order, layers have just a few neurons so insertion sort is enough
+  fn sort(neuron_votes: &Vec<PreciseDecimalNumber>) -> Vec<PreciseDecimalNumber> {
+    let mut sorted: Vec<PreciseDecimalNumber> = Vec::new(neuron_votes.env());
+    for neuron_vote in neuron_votes.iter() {
+      let mut index = 0;
+      while index < sorted.len() && sorted.get(index).unwrap() <= neuron_vote {
+        index += 1;
+      }
+      sorted.insert(index, neuron_vote);
     }
+    sorted
   }
 }
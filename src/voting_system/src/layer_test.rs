@@ -0,0 +1,161 @@
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Map, Vec};
+
+use crate::{
+  activation::Activation,
+  layer::{Layer, NeuronConfig},
+  types::{LayerAggregator, LayerInputMode, NeuronType, PreciseDecimalNumber, VotingSystemError},
+};
+
+// a layer with the given aggregator and one neuron per weight
+fn layer_with_weights(env: &Env, aggregator: LayerAggregator, raw_weights: &[u32]) -> Layer {
+  let mut neurons: Map<NeuronType, NeuronConfig> = Map::new(env);
+  for raw_weight in raw_weights {
+    neurons.set(
+      NeuronType::External(Address::generate(env)),
+      NeuronConfig {
+        weight: *raw_weight,
+        activation: Activation::Linear,
+      },
+    );
+  }
+  Layer {
+    id: 0,
+    neurons,
+    aggregator,
+    input_mode: LayerInputMode::Additive,
+  }
+}
+
+fn neuron_votes(env: &Env) -> Vec<PreciseDecimalNumber> {
+  vec![
+    env,
+    1_500_000_000,
+    250_000_000,
+    3_000_000_000,
+    2_000_000_000,
+  ]
+}
+
+#[test]
+pub fn test_min_max_aggregators() {
+  let env = Env::default();
+
+  let layer = layer_with_weights(&env, LayerAggregator::Min, &[1000, 1000, 1000, 1000]);
+  assert!(layer.run_layer_aggregator(neuron_votes(&env)) == Ok(250_000_000));
+
+  let layer = layer_with_weights(&env, LayerAggregator::Max, &[1000, 1000, 1000, 1000]);
+  assert!(layer.run_layer_aggregator(neuron_votes(&env)) == Ok(3_000_000_000));
+
+  // a single vote is both the minimum and the maximum
+  assert!(layer.run_layer_aggregator(vec![&env, 100]) == Ok(100));
+  assert!(
+    layer.run_layer_aggregator(Vec::new(&env))
+      == Err(VotingSystemError::ReducingvotesForMaxAggregatorFailed)
+  );
+}
+
+#[test]
+pub fn test_average_aggregator() {
+  let env = Env::default();
+  let layer = layer_with_weights(&env, LayerAggregator::Average, &[1000, 1000, 1000, 1000]);
+
+  // (1.5 + 0.25 + 3 + 2) / 4
+  assert!(layer.run_layer_aggregator(neuron_votes(&env)) == Ok(1_687_500_000));
+
+  assert!(
+    layer.run_layer_aggregator(Vec::new(&env))
+      == Err(VotingSystemError::ReducingvotesForAverageAggregatorFailed)
+  );
+}
+
+#[test]
+pub fn test_median_aggregator() {
+  let env = Env::default();
+  let layer = layer_with_weights(&env, LayerAggregator::Median, &[1000, 1000, 1000, 1000]);
+
+  // even number of votes - the average of 1.5 and 2
+  assert!(layer.run_layer_aggregator(neuron_votes(&env)) == Ok(1_750_000_000));
+
+  // odd number of votes - the middle one
+  assert!(
+    layer.run_layer_aggregator(vec![&env, 5_000_000_000, 100_000_000, 1_000_000_000])
+      == Ok(1_000_000_000)
+  );
+
+  assert!(
+    layer.run_layer_aggregator(Vec::new(&env))
+      == Err(VotingSystemError::ReducingvotesForMedianAggregatorFailed)
+  );
+}
+
+#[test]
+pub fn test_weighted_average_aggregator() {
+  let env = Env::default();
+  // weights 1, 0.5, 2 and 0.5, the neuron votes below are already multiplied by them
+  let layer = layer_with_weights(
+    &env,
+    LayerAggregator::WeightedAverage,
+    &[1000, 500, 2000, 500],
+  );
+
+  // 6.75 / 4
+  assert!(layer.run_layer_aggregator(neuron_votes(&env)) == Ok(1_687_500_000));
+
+  // the sum of the weights differs from the number of neurons, (2 + 4) / 4
+  let layer = layer_with_weights(&env, LayerAggregator::WeightedAverage, &[2000, 2000]);
+  assert!(
+    layer.run_layer_aggregator(vec![&env, 2_000_000_000, 4_000_000_000]) == Ok(1_500_000_000)
+  );
+
+  let layer = layer_with_weights(&env, LayerAggregator::WeightedAverage, &[0, 0]);
+  assert!(
+    layer.run_layer_aggregator(vec![&env, 0, 0]) == Err(VotingSystemError::NeuronWeightsSumToZero)
+  );
+}
+
+#[test]
+pub fn test_negative_votes() {
+  let env = Env::default();
+  let votes = vec![&env, 1_500_000_000, -2_500_000_000, 500_000_000];
+
+  let layer = layer_with_weights(&env, LayerAggregator::Sum, &[1000, 1000, 1000]);
+  assert!(layer.run_layer_aggregator(votes.clone()) == Ok(-500_000_000));
+
+  let layer = layer_with_weights(&env, LayerAggregator::Product, &[1000, 1000, 1000]);
+  // 1.5 * -2.5 * 0.5
+  assert!(layer.run_layer_aggregator(votes.clone()) == Ok(-1_875_000_000));
+
+  let layer = layer_with_weights(&env, LayerAggregator::Min, &[1000, 1000, 1000]);
+  assert!(layer.run_layer_aggregator(votes.clone()) == Ok(-2_500_000_000));
+
+  let layer = layer_with_weights(&env, LayerAggregator::Median, &[1000, 1000, 1000]);
+  assert!(layer.run_layer_aggregator(votes) == Ok(500_000_000));
+}
+
+#[test]
+pub fn test_product_precision() {
+  let env = Env::default();
+  let layer = layer_with_weights(&env, LayerAggregator::Product, &[1000, 1000, 1000]);
+
+  // 0.001 * 0.5 would be rounded to 0 with 3 decimal places before it is multiplied by 2
+  assert!(
+    layer.run_layer_aggregator(vec![&env, 1_000_000, 500_000_000, 2_000_000_000]) == Ok(1_000_000)
+  );
+}
+
+#[test]
+pub fn test_overflow() {
+  let env = Env::default();
+
+  let layer = layer_with_weights(&env, LayerAggregator::Sum, &[1000, 1000]);
+  assert!(
+    layer.run_layer_aggregator(vec![&env, i128::MAX, 1])
+      == Err(VotingSystemError::ArithmeticOverflow)
+  );
+
+  let layer = layer_with_weights(&env, LayerAggregator::Product, &[1000, 1000]);
+  assert!(
+    layer.run_layer_aggregator(vec![&env, i128::MAX / 1_000_000_000_000, 2_000_000_000_000])
+      == Err(VotingSystemError::ArithmeticOverflow)
+  );
+}
@@ -1,19 +1,34 @@
 #![no_std]
 #![allow(non_upper_case_globals)]
 
+mod activation;
+mod ballots;
+mod events;
 mod layer;
+mod migration;
 mod neural_governance;
 mod neurons;
+mod round;
+mod submissions;
+mod tally;
 mod types;
 
-use crate::types::{Vote, VotingSystemError, QUORUM_SIZE};
-use neural_governance::NeuralGovernance;
+use crate::types::{
+  Vote, VotingSystemError, BALLOT_TTL_EXTEND_TO, BALLOT_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO,
+  INSTANCE_TTL_THRESHOLD, QUORUM_SIZE,
+};
+use activation::Activation;
+use neural_governance::{NeuralGovernance, VotingPowerBounds, VotingPowerExplanation};
+use round::Round;
 use soroban_decimal_numbers::DecimalNumberWrapper;
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Map, String, Vec};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Map, String, Vec};
+use submissions::Submission;
+use tally::{SubmissionResult, SubmissionTally};
 use types::{
-  layer_aggregator_from_str, neuron_type_from_str, normalized_vote_from_str, vote_from_str,
-  LayerAggregator, NormalizedVote, ABSTAIN_VOTING_POWER, DEFAULT_WEIGHT, MAX_DELEGATEES,
-  MIN_DELEGATEES, QUORUM_PARTICIPATION_TRESHOLD,
+  layer_aggregator_from_str, layer_input_mode_from_str, neuron_type_from_str,
+  normalized_vote_from_str, normalized_vote_to_str, vote_from_str, LayerAggregator, NeuronType,
+  NormalizedVote, PreciseDecimal, PreciseDecimalNumber, RoundState, SignedDecimal,
+  SubmissionStatus, DEFAULT_WEIGHT, MAX_DELEGATEES, MIN_DELEGATEES, QUORUM_PARTICIPATION_TRESHOLD,
 };
 
 mod external_data_provider_contract {
@@ -25,19 +40,64 @@ mod external_data_provider_contract {
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
-  // storage type: instance
-  // Map<submission_id, Map<user_id, vote>>
-  Votes,
+  // storage type: persistent
+  // Vote - (round_id, submission_id, user_id) => vote of the user for the submission in the given round
+  Ballot(u32, String, String),
+  // storage type: persistent
+  // Map<submission_id, ()> - submissions registered in the given round
+  Submissions(u32),
+  // storage type: persistent
+  // Submission - (round_id, submission_id) => metadata and status of the submission
+  Submission(u32, String),
+  // storage type: persistent
+  // Map<user_id, ()> - users who have at least one vote in the given round
+  Voters(u32),
+  // storage type: persistent
+  // Map<user_id, ()> - (round_id, submission_id) => users who voted for the submission
+  SubmissionVoters(u32, String),
+  // storage type: persistent
+  // Map<submission_id, ()> - (round_id, user_id) => submissions the user voted for
+  VoterSubmissions(u32, String),
   // storage type: instance
   NeuralGovernance,
   // storage type: instance
-  // Map<user_id, Vec<user_id>> - users to the vector of users they delegated their votes to
-  Delegatees,
+  // u32 - version of the stored NeuralGovernance shape, missing for the legacy one
+  NeuralGovernanceVersion,
+  // storage type: persistent
+  // Map<user_id, Vec<user_id>> - users to the vector of users they delegated their votes to in the given round
+  Delegatees(u32),
   // storage type: instance
   ExternalDataProvider,
+  // storage type: persistent
+  // Map<user_id, (u32, u32)> - voting powers calculated in the given round
+  VotingPowers(u32),
+  // storage type: persistent
+  // Address - user_id => the address which has to authorize the votes of the voter
+  VoterAddress(String),
+  // storage type: instance
+  // Address - the only account allowed to change the configuration of the system
+  Admin,
+  // storage type: instance
+  // Address - admin proposed by the current admin, becomes the admin once they accept it
+  PendingAdmin,
   // storage type: instance
-  // Map<user_id, (u32, u32)>
-  VotingPowers,
+  // u32 - id of the latest round, rounds are numbered from 1
+  CurrentRound,
+  // storage type: persistent
+  // Round
+  Round(u32),
+  // storage type: persistent
+  // Map<submission_id, SubmissionResult> - results of the last tally of the given round
+  Results(u32),
+  // storage type: persistent
+  // (u32, PreciseDecimalNumber) - (round_id, submission_id) => MedianVotingPowerVersion it was
+  // calculated with and the median unbounded voting power of the submission's voters, only stored
+  // if the cap relative to the median voter is set
+  MedianVotingPower(u32, String),
+  // storage type: instance
+  // u32 - increased whenever the neural governance, the votes, the delegatees or the external data
+  // provider change, the medians stored with an older version are not used
+  MedianVotingPowerVersion,
 }
 
 #[contract]
@@ -45,30 +105,220 @@ pub struct VotingSystem;
 
 #[contractimpl]
 impl VotingSystem {
-  pub fn initialize(env: Env) {
+  pub fn initialize(env: Env, admin: Address) -> Result<(), VotingSystemError> {
+    if env.storage().instance().has(&DataKey::Admin) {
+      return Err(VotingSystemError::AlreadyInitialized);
+    }
+    env.storage().instance().set(&DataKey::Admin, &admin);
+    VotingSystem::extend_instance_ttl(&env);
+
     let ng = NeuralGovernance {
       layers: Vec::new(&env),
       current_layer_id: 0,
+      voting_power_bounds: Default::default(),
     };
+    VotingSystem::set_neural_governance(env, ng);
+    Ok(())
+  }
+
+  pub fn get_admin(env: Env) -> Result<Address, VotingSystemError> {
     env
       .storage()
       .instance()
-      .set(&DataKey::NeuralGovernance, &ng);
+      .get(&DataKey::Admin)
+      .ok_or(VotingSystemError::AdminNotSet)
   }
 
-  pub fn get_neural_governance(env: Env) -> Result<NeuralGovernance, VotingSystemError> {
+  // every call changing the state goes through require_admin or require_voter_auth, or extends
+  // the instance itself
+  fn extend_instance_ttl(env: &Env) {
+    env
+      .storage()
+      .instance()
+      .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+  }
+
+  fn require_admin(env: &Env) -> Result<(), VotingSystemError> {
+    let admin = VotingSystem::get_admin(env.clone())?;
+    admin.require_auth();
+    VotingSystem::extend_instance_ttl(env);
+    Ok(())
+  }
+
+  // the first step of transferring the admin role, the new admin has to call accept_admin
+  pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::PendingAdmin, &new_admin);
+    Ok(())
+  }
+
+  pub fn get_pending_admin(env: Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::PendingAdmin)
+  }
+
+  pub fn accept_admin(env: Env) -> Result<(), VotingSystemError> {
+    let pending_admin =
+      VotingSystem::get_pending_admin(env.clone()).ok_or(VotingSystemError::NoPendingAdmin)?;
+    pending_admin.require_auth();
+    VotingSystem::extend_instance_ttl(&env);
+
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::Admin, &pending_admin);
+    env.storage().instance().remove(&DataKey::PendingAdmin);
+    Ok(())
+  }
+
+  pub fn get_current_round(env: Env) -> Result<Round, VotingSystemError> {
+    let round_id: u32 = env
+      .storage()
+      .instance()
+      .get(&DataKey::CurrentRound)
+      .ok_or(VotingSystemError::RoundNotFound)?;
+    VotingSystem::get_round(env, round_id)
+  }
+
+  // the state reflects the voting window, e.g. a round is closed once its end ledger is reached
+  pub fn get_round(env: Env, round_id: u32) -> Result<Round, VotingSystemError> {
+    let mut round: Round = env
+      .storage()
+      .persistent()
+      .get(&DataKey::Round(round_id))
+      .ok_or(VotingSystemError::RoundNotFound)?;
+    round.sync_with_ledger(env.ledger().sequence());
+    Ok(round)
+  }
+
+  // there is no round with id 0, so before the first round is created reads return empty collections
+  fn current_round_id(env: &Env) -> u32 {
+    env
+      .storage()
+      .instance()
+      .get(&DataKey::CurrentRound)
+      .unwrap_or(0)
+  }
+
+  fn set_round(env: &Env, round: &Round) {
+    let key = DataKey::Round(round.id);
+    env.storage().persistent().set(&key, round);
+    VotingSystem::extend_ttl(env, &key);
+  }
+
+  // creates a new round in the Draft state, the previous round has to be finalized first
+  pub fn create_round(env: Env) -> Result<u32, VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    if let Ok(current_round) = VotingSystem::get_current_round(env.clone()) {
+      if current_round.state != RoundState::Finalized {
+        return Err(VotingSystemError::RoundNotFinalized);
+      }
+    }
+
+    let round = Round::new(VotingSystem::current_round_id(&env) + 1);
+    VotingSystem::set_round(&env, &round);
     env
       .storage()
       .instance()
-      .get(&DataKey::NeuralGovernance)
+      .set(&DataKey::CurrentRound, &round.id);
+    Ok(round.id)
+  }
+
+  pub fn open_round(env: Env) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    VotingSystem::move_current_round_to(&env, RoundState::Open)
+  }
+
+  pub fn close_round(env: Env) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    VotingSystem::move_current_round_to(&env, RoundState::Closed)
+  }
+
+  // finalized round cannot be tallied again, its results stay available in get_round_results
+  pub fn finalize_round(env: Env) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    VotingSystem::move_current_round_to(&env, RoundState::Finalized)
+  }
+
+  // votes are accepted only between the given ledger sequences, the round is opened and closed automatically
+  pub fn set_voting_window(
+    env: Env,
+    start_ledger: u32,
+    end_ledger: u32,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut round = VotingSystem::get_current_round(env.clone())?;
+    round.set_voting_window(start_ledger, end_ledger)?;
+    round.sync_with_ledger(env.ledger().sequence());
+    VotingSystem::set_round(&env, &round);
+    Ok(())
+  }
+
+  fn move_current_round_to(env: &Env, state: RoundState) -> Result<(), VotingSystemError> {
+    let mut round = VotingSystem::get_current_round(env.clone())?;
+    round.move_to(state)?;
+    VotingSystem::set_round(env, &round);
+    Ok(())
+  }
+
+  fn get_open_round(env: &Env) -> Result<Round, VotingSystemError> {
+    let round = VotingSystem::get_current_round(env.clone())?;
+    round.require_open(env.ledger().sequence())?;
+    Ok(round)
+  }
+
+  // Map<submission_id, SubmissionResult>
+  pub fn get_round_results(
+    env: Env,
+    round_id: u32,
+  ) -> Result<Map<String, SubmissionResult>, VotingSystemError> {
+    env
+      .storage()
+      .persistent()
+      .get(&DataKey::Results(round_id))
+      .ok_or(VotingSystemError::RoundNotTallied)
+  }
+
+  // layers stored before the activation functions and input modes were added are read as linear additive ones
+  pub fn get_neural_governance(env: Env) -> Result<NeuralGovernance, VotingSystemError> {
+    migration::load_neural_governance(&env)
+      .map(|(neural_governance, _)| neural_governance)
       .ok_or(VotingSystemError::NeuralGovernanceNotSet)
   }
 
+  // rewrites layers stored before the activation functions and input modes were added in the current shape
+  pub fn migrate_neural_governance(env: Env) -> Result<bool, VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let (neural_governance, is_legacy) =
+      migration::load_neural_governance(&env).ok_or(VotingSystemError::NeuralGovernanceNotSet)?;
+    if is_legacy {
+      let layers = neural_governance.layers.len();
+      VotingSystem::set_neural_governance(env.clone(), neural_governance);
+      events::neural_governance_migrated(&env, layers);
+    }
+    Ok(is_legacy)
+  }
+
+  // replaces the code of the contract, the storage is kept so migrate_neural_governance should be called afterwards
+  // deployments created before this entry point existed can't be upgraded, they have to be redeployed
+  pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    env.deployer().update_current_contract_wasm(new_wasm_hash);
+    Ok(())
+  }
+
   fn set_neural_governance(env: Env, neural_governance: NeuralGovernance) {
+    VotingSystem::invalidate_median_voting_powers(&env);
     env
       .storage()
       .instance()
       .set(&DataKey::NeuralGovernance, &neural_governance);
+    env.storage().instance().set(
+      &DataKey::NeuralGovernanceVersion,
+      &migration::NEURAL_GOVERNANCE_VERSION,
+    );
   }
 
   pub fn calculate_quorum_consensus(
@@ -87,10 +337,11 @@ impl VotingSystem {
     let delegation_ranks: Map<String, u32> =
       external_data_provider_client.get_delegation_ranks_for_users(&delegatees.clone());
 
-    let all_votes = VotingSystem::get_votes(env.clone());
-    let submission_votes = all_votes
-      .get(submission_id.clone())
-      .unwrap_or(Map::new(&env));
+    let submission_votes = ballots::get_votes_for_submission(
+      &env,
+      VotingSystem::current_round_id(&env),
+      submission_id.clone(),
+    );
 
     let mut sorted_delegatees: Map<String, u32> = Map::new(&env);
     for delegatee_id in delegatees {
@@ -154,88 +405,149 @@ impl VotingSystem {
   pub fn multiple_vote_operations(
     env: Env,
     voter_id: String,
-    votes: Map<String, String>,
+    votes: Map<String, Vote>,
   ) -> Result<Map<String, Vote>, VotingSystemError> {
-    let mut all_votes = VotingSystem::get_votes(env.clone());
-    for (submission_id, vote) in votes {
-      let vote: Vote = vote_from_str(&env, vote);
-
-      let mut submission_votes = all_votes
-        .get(submission_id.clone())
-        .unwrap_or(Map::new(&env));
-      if vote == Vote::Remove {
-        submission_votes.remove(voter_id.clone());
-      } else {
-        submission_votes.set(voter_id.clone(), vote);
-      }
+    let round = VotingSystem::get_open_round(&env)?;
+    VotingSystem::require_voter_auth(&env, &voter_id)?;
 
-      if submission_votes.is_empty() {
-        all_votes.remove(submission_id.clone());
-      } else {
-        all_votes.set(submission_id, submission_votes);
-      }
+    for (submission_id, vote) in votes.clone() {
+      VotingSystem::require_votable_submission(&env, round.id, submission_id, vote)?;
+    }
+    for (submission_id, vote) in votes {
+      VotingSystem::store_vote(&env, round.id, voter_id.clone(), submission_id, vote);
     }
 
-    env.storage().instance().set(&DataKey::Votes, &all_votes);
     Ok(VotingSystem::get_votes_for_user(env, voter_id))
   }
 
+  // string variant for the CLI, unknown votes are rejected before anything is stored
+  pub fn multiple_vote_operations_str(
+    env: Env,
+    voter_id: String,
+    votes: Map<String, String>,
+  ) -> Result<Map<String, Vote>, VotingSystemError> {
+    let mut typed_votes: Map<String, Vote> = Map::new(&env);
+    for (submission_id, vote) in votes {
+      typed_votes.set(submission_id, vote_from_str(&env, vote)?);
+    }
+    VotingSystem::multiple_vote_operations(env, voter_id, typed_votes)
+  }
+
   // votes: Map<submission_id, vote>
   pub fn multiple_vote_operations_vec(
     env: Env,
     voter_id: String,
     // TODO this should be a map but soroban's maps are buggy so we use vector of tuples
     // map would not work when used as an argument for specific keys and would just throw errors
-    votes_vec: Vec<(String, String)>,
+    votes_vec: Vec<(String, Vote)>,
   ) -> Result<Map<String, Vote>, VotingSystemError> {
-    let mut votes: Map<String, String> = Map::new(&env);
+    let mut votes: Map<String, Vote> = Map::new(&env);
     for (submission_id, vote) in votes_vec {
       votes.set(submission_id, vote);
     }
     VotingSystem::multiple_vote_operations(env, voter_id, votes)
   }
 
+  pub fn multiple_vote_operations_vec_str(
+    env: Env,
+    voter_id: String,
+    votes_vec: Vec<(String, String)>,
+  ) -> Result<Map<String, Vote>, VotingSystemError> {
+    let mut votes: Map<String, Vote> = Map::new(&env);
+    for (submission_id, vote) in votes_vec {
+      votes.set(submission_id, vote_from_str(&env, vote)?);
+    }
+    VotingSystem::multiple_vote_operations(env, voter_id, votes)
+  }
+
   pub fn vote(
+    env: Env,
+    voter_id: String,
+    submission_id: String,
+    vote: Vote,
+  ) -> Result<Map<String, Vote>, VotingSystemError> {
+    let round = VotingSystem::get_open_round(&env)?;
+    VotingSystem::require_voter_auth(&env, &voter_id)?;
+    VotingSystem::cast_vote(env, round.id, voter_id, submission_id, vote)
+  }
+
+  // string variant for the CLI, e.g. "Yes", "No", "Abstain", "Delegate" or "Remove"
+  pub fn vote_str(
     env: Env,
     voter_id: String,
     submission_id: String,
     vote: String,
   ) -> Result<Map<String, Vote>, VotingSystemError> {
-    let vote: Vote = vote_from_str(&env, vote);
+    let vote = vote_from_str(&env, vote)?;
+    VotingSystem::vote(env, voter_id, submission_id, vote)
+  }
+
+  fn cast_vote(
+    env: Env,
+    round_id: u32,
+    voter_id: String,
+    submission_id: String,
+    vote: Vote,
+  ) -> Result<Map<String, Vote>, VotingSystemError> {
+    VotingSystem::require_votable_submission(&env, round_id, submission_id.clone(), vote)?;
     if vote == Vote::Delegate
-      && VotingSystem::get_delegatees(env.clone())
+      && VotingSystem::get_delegatees_for_round(env.clone(), round_id)
         .get(voter_id.clone())
         .is_none()
     {
       return Err(VotingSystemError::DelegateesNotFound);
     }
 
-    let mut votes = VotingSystem::get_votes(env.clone());
-    let mut submission_votes: Map<String, Vote> =
-      votes.get(submission_id.clone()).unwrap_or(Map::new(&env));
+    VotingSystem::store_vote(&env, round_id, voter_id.clone(), submission_id, vote);
+
+    Ok(VotingSystem::get_votes_for_user(env, voter_id))
+  }
+
+  // votes can only be cast for registered submissions which were not withdrawn,
+  // removing a vote is still possible after the submission was withdrawn
+  fn require_votable_submission(
+    env: &Env,
+    round_id: u32,
+    submission_id: String,
+    vote: Vote,
+  ) -> Result<(), VotingSystemError> {
+    let submission = submissions::get(env, round_id, submission_id)?;
+    if vote != Vote::Remove {
+      submission.require_active()?;
+    }
+    Ok(())
+  }
 
+  fn store_vote(env: &Env, round_id: u32, voter_id: String, submission_id: String, vote: Vote) {
+    let previous_vote = ballots::get_vote(env, round_id, submission_id.clone(), voter_id.clone());
     if vote == Vote::Remove {
-      submission_votes.remove(voter_id.clone());
-    } else {
-      submission_votes.set(voter_id.clone(), vote);
+      if previous_vote.is_some() {
+        ballots::remove_vote(env, round_id, submission_id.clone(), voter_id.clone());
+        VotingSystem::invalidate_median_voting_powers(env);
+        events::vote_removed(env, round_id, submission_id, voter_id);
+      }
+      return;
     }
 
-    if submission_votes.is_empty() {
-      votes.remove(submission_id.clone());
+    ballots::set_vote(env, round_id, submission_id.clone(), voter_id.clone(), vote);
+    VotingSystem::invalidate_median_voting_powers(env);
+    if previous_vote.is_none() {
+      events::vote_cast(env, round_id, submission_id, voter_id, vote);
     } else {
-      votes.set(submission_id, submission_votes);
+      events::vote_changed(env, round_id, submission_id, voter_id, vote);
     }
-
-    env.storage().instance().set(&DataKey::Votes, &votes);
-
-    Ok(VotingSystem::get_votes_for_user(env, voter_id))
   }
 
   pub fn get_delegatees(env: Env) -> Map<String, Vec<String>> {
+    let round_id = VotingSystem::current_round_id(&env);
+    VotingSystem::get_delegatees_for_round(env, round_id)
+  }
+
+  pub fn get_delegatees_for_round(env: Env, round_id: u32) -> Map<String, Vec<String>> {
     env
       .storage()
-      .instance()
-      .get(&DataKey::Delegatees)
+      .persistent()
+      .get(&DataKey::Delegatees(round_id))
       .unwrap_or(Map::new(&env))
   }
 
@@ -243,6 +555,17 @@ impl VotingSystem {
     env: Env,
     voter_id: String,
     delegatees_for_user: Vec<String>,
+  ) -> Result<Vec<String>, VotingSystemError> {
+    let round = VotingSystem::get_open_round(&env)?;
+    VotingSystem::require_voter_auth(&env, &voter_id)?;
+    VotingSystem::store_delegatees(env, round.id, voter_id, delegatees_for_user)
+  }
+
+  fn store_delegatees(
+    env: Env,
+    round_id: u32,
+    voter_id: String,
+    delegatees_for_user: Vec<String>,
   ) -> Result<Vec<String>, VotingSystemError> {
     if delegatees_for_user.len() > MAX_DELEGATEES {
       return Err(VotingSystemError::TooManyDelegatees);
@@ -250,15 +573,16 @@ impl VotingSystem {
     if delegatees_for_user.len() < MIN_DELEGATEES {
       return Err(VotingSystemError::NotEnoughDelegatees);
     }
-    let mut all_delegatees = VotingSystem::get_delegatees(env.clone());
-    all_delegatees.set(voter_id.clone(), delegatees_for_user);
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::Delegatees, &all_delegatees);
+    let mut all_delegatees = VotingSystem::get_delegatees_for_round(env.clone(), round_id);
+    all_delegatees.set(voter_id.clone(), delegatees_for_user.clone());
+    let key = DataKey::Delegatees(round_id);
+    env.storage().persistent().set(&key, &all_delegatees);
+    VotingSystem::extend_ttl(&env, &key);
+    VotingSystem::invalidate_median_voting_powers(&env);
+    events::delegatees_set(&env, round_id, voter_id.clone(), delegatees_for_user);
 
     Ok(
-      VotingSystem::get_delegatees(env.clone())
+      VotingSystem::get_delegatees_for_round(env.clone(), round_id)
         .get(voter_id.clone())
         .unwrap_or(Vec::new(&env)),
     )
@@ -270,46 +594,74 @@ impl VotingSystem {
     submission_id: String,
     delegatees_for_user: Vec<String>,
   ) -> Result<Map<String, Vote>, VotingSystemError> {
-    VotingSystem::set_delegatees(env.clone(), voter_id.clone(), delegatees_for_user)?;
-    VotingSystem::vote(
-      env.clone(),
-      voter_id,
-      submission_id,
-      String::from_slice(&env, "Delegate"),
-    )
+    let round = VotingSystem::get_open_round(&env)?;
+    // both operations below belong to the same invocation, so the voter authorizes them once
+    VotingSystem::require_voter_auth(&env, &voter_id)?;
+    VotingSystem::store_delegatees(env.clone(), round.id, voter_id.clone(), delegatees_for_user)?;
+    VotingSystem::cast_vote(env, round.id, voter_id, submission_id, Vote::Delegate)
   }
 
+  // votes of the current round
   pub fn get_votes(env: Env) -> Map<String, Map<String, Vote>> {
-    env
-      .storage()
-      .instance()
-      .get(&DataKey::Votes)
-      .unwrap_or(Map::new(&env))
+    let round_id = VotingSystem::current_round_id(&env);
+    VotingSystem::get_votes_for_round(env, round_id)
   }
 
+  // reads every ballot of the round, use get_votes_for_user or get_votes_for_submission for single entries
+  pub fn get_votes_for_round(env: Env, round_id: u32) -> Map<String, Map<String, Vote>> {
+    let mut votes: Map<String, Map<String, Vote>> = Map::new(&env);
+    for submission_id in submissions::get_ids(&env, round_id) {
+      if !ballots::has_votes(&env, round_id, submission_id.clone()) {
+        continue;
+      }
+      votes.set(
+        submission_id.clone(),
+        ballots::get_votes_for_submission(&env, round_id, submission_id),
+      );
+    }
+    votes
+  }
+
+  // returns Map<voter_id, vote>
+  pub fn get_votes_for_submission(env: Env, submission_id: String) -> Map<String, Vote> {
+    ballots::get_votes_for_submission(&env, VotingSystem::current_round_id(&env), submission_id)
+  }
+
+  // number of submissions with at least one vote in the current round
   pub fn get_votes_length(env: Env) -> u32 {
-    let votes: Map<String, Map<String, Vote>> = env
-      .storage()
-      .instance()
-      .get(&DataKey::Votes)
-      .unwrap_or(Map::new(&env));
-    votes.len()
+    let round_id = VotingSystem::current_round_id(&env);
+    let mut length = 0;
+    for submission_id in submissions::get_ids(&env, round_id) {
+      if ballots::has_votes(&env, round_id, submission_id) {
+        length += 1;
+      }
+    }
+    length
   }
 
+  // submission id => vote
   pub fn get_votes_for_user(env: Env, voter_id: String) -> Map<String, Vote> {
-    let all_votes: Map<String, Map<String, Vote>> = env
-      .storage()
-      .instance()
-      .get(&DataKey::Votes)
-      .unwrap_or(Map::new(&env));
-    // submission id => vote
-    let mut result: Map<String, Vote> = Map::new(&env);
-    for (submission_id, submission_votes) in all_votes {
-      if let Some(vote) = submission_votes.get(voter_id.clone()) {
-        result.set(submission_id, vote);
+    ballots::get_votes_for_voter(&env, VotingSystem::current_round_id(&env), voter_id)
+  }
+
+  // the round, its ballots, delegations, voting powers and results are kept in persistent storage,
+  // anyone can extend their lifetime (e.g. until the round is tallied)
+  pub fn extend_round_ttl(env: Env, round_id: u32) -> Result<(), VotingSystemError> {
+    VotingSystem::get_round(env.clone(), round_id)?;
+    VotingSystem::extend_instance_ttl(&env);
+    for key in [
+      DataKey::Round(round_id),
+      DataKey::Results(round_id),
+      DataKey::Delegatees(round_id),
+      DataKey::VotingPowers(round_id),
+    ] {
+      if env.storage().persistent().has(&key) {
+        VotingSystem::extend_ttl(&env, &key);
       }
     }
-    result
+    submissions::extend_round_ttl(&env, round_id);
+    ballots::extend_round_ttl(&env, round_id);
+    Ok(())
   }
 
   pub fn remove_vote(
@@ -317,86 +669,162 @@ impl VotingSystem {
     voter_id: String,
     submission_id: String,
   ) -> Result<Map<String, Vote>, VotingSystemError> {
-    VotingSystem::vote(
-      env.clone(),
-      voter_id,
-      submission_id,
-      String::from_slice(&env, "Remove"),
-    )
+    VotingSystem::vote(env, voter_id, submission_id, Vote::Remove)
+  }
+
+  pub fn get_voter_address(env: Env, voter_id: String) -> Result<Address, VotingSystemError> {
+    let key = DataKey::VoterAddress(voter_id);
+    let voter_address: Address = env
+      .storage()
+      .persistent()
+      .get(&key)
+      .ok_or(VotingSystemError::VoterNotRegistered)?;
+    VotingSystem::extend_ttl(&env, &key);
+    Ok(voter_address)
   }
 
-  pub fn add_submission(env: Env, submission_id: String) -> Result<(), VotingSystemError> {
-    let mut votes = VotingSystem::get_votes(env.clone());
-    if votes.get(submission_id.clone()).is_some() {
+  // the registrations and the rounds live as long as they are used
+  fn extend_ttl(env: &Env, key: &DataKey) {
+    env
+      .storage()
+      .persistent()
+      .extend_ttl(key, BALLOT_TTL_THRESHOLD, BALLOT_TTL_EXTEND_TO);
+  }
+
+  // binds the voter id to an address, from now on only this address can vote as this voter
+  pub fn register_voter(
+    env: Env,
+    voter_id: String,
+    voter_address: Address,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+
+    let key = DataKey::VoterAddress(voter_id);
+    let registered_address: Option<Address> = env.storage().persistent().get(&key);
+    if let Some(registered_address) = registered_address {
+      if registered_address != voter_address {
+        return Err(VotingSystemError::VoterAlreadyRegistered);
+      }
+    }
+    env.storage().persistent().set(&key, &voter_address);
+    VotingSystem::extend_ttl(&env, &key);
+
+    Ok(())
+  }
+
+  fn require_voter_auth(env: &Env, voter_id: &String) -> Result<(), VotingSystemError> {
+    let voter_address = VotingSystem::get_voter_address(env.clone(), voter_id.clone())?;
+    voter_address.require_auth();
+    VotingSystem::extend_instance_ttl(env);
+    Ok(())
+  }
+
+  // registers the submission in the current round, only registered submissions can be voted for
+  pub fn add_submission(
+    env: Env,
+    submission_id: String,
+    title: String,
+    category: String,
+    requested_budget: u64,
+    proposer: Address,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let round = VotingSystem::get_current_round(env.clone())?;
+    round.require_accepting_submissions()?;
+
+    if submissions::has(&env, round.id, submission_id.clone()) {
       return Err(VotingSystemError::SubmissionAlreadyAdded);
     }
-    votes.set(submission_id, Map::new(&env));
-    env.storage().instance().set(&DataKey::Votes, &votes);
+    let submission = Submission {
+      id: submission_id.clone(),
+      title,
+      category,
+      requested_budget,
+      proposer,
+      status: SubmissionStatus::Active,
+    };
+    submissions::set(&env, round.id, &submission);
+    events::submission_added(&env, round.id, submission_id);
 
     Ok(())
   }
 
+  // withdrawn submissions can't be voted for and are skipped by the tally
+  pub fn set_submission_status(
+    env: Env,
+    submission_id: String,
+    status: SubmissionStatus,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let round = VotingSystem::get_current_round(env.clone())?;
+    round.require_accepting_submissions()?;
+
+    let mut submission = submissions::get(&env, round.id, submission_id.clone())?;
+    submission.status = status;
+    submissions::set(&env, round.id, &submission);
+    events::submission_status_set(&env, round.id, submission_id, status);
+
+    Ok(())
+  }
+
+  pub fn get_submission(env: Env, submission_id: String) -> Result<Submission, VotingSystemError> {
+    submissions::get(&env, VotingSystem::current_round_id(&env), submission_id)
+  }
+
   pub fn get_submissions(env: Env) -> Vec<String> {
-    VotingSystem::get_votes(env.clone()).keys()
+    submissions::get_ids(&env, VotingSystem::current_round_id(&env))
   }
 
   pub fn get_voters(env: Env) -> Vec<String> {
-    let votes = VotingSystem::get_votes(env.clone());
-    let mut voters: Map<String, ()> = Map::new(&env);
-    for (_, submission_votes) in votes {
-      for voter_id in submission_votes.keys() {
-        voters.set(voter_id, ());
+    ballots::get_voters(&env, VotingSystem::current_round_id(&env))
+  }
+
+  // result: map<submission_id, SubmissionResult>
+  // the current round has to be closed, the result is stored and can be read with get_round_results
+  pub fn tally(env: Env) -> Result<Map<String, SubmissionResult>, VotingSystemError> {
+    let mut round = VotingSystem::get_current_round(env.clone())?;
+    round.require_closed()?;
+    round.move_to(RoundState::Tallied)?;
+    VotingSystem::extend_instance_ttl(&env);
+
+    let neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let mut result: Map<String, SubmissionResult> = Map::new(&env);
+    for submission_id in submissions::get_active_ids(&env, round.id) {
+      let (submission_tally, median_voting_power) =
+        tally::tally_submission(&env, &neural_governance, round.id, submission_id.clone())?;
+      // voting_power_for_voter uses the same median afterwards
+      if let Some(median_voting_power) = median_voting_power {
+        VotingSystem::store_median_voting_power(
+          &env,
+          round.id,
+          submission_id.clone(),
+          median_voting_power,
+        );
       }
+      result.set(submission_id, submission_tally.result()?);
     }
-    voters.keys()
-  }
-
-  // result: map<submission_id, submission_voting_power>
-  pub fn tally(env: Env) -> Result<Map<String, (u32, u32)>, VotingSystemError> {
-    let all_votes = VotingSystem::get_votes(env.clone());
-    let mut result: Map<String, (u32, u32)> = Map::new(&env);
-    // String, Map<String, (Vote, (u32, u32))>
-    for (submission_id, submission_votes) in all_votes {
-      let mut submission_voting_power_plus: DecimalNumberWrapper = Default::default();
-      let mut submission_voting_power_minus: DecimalNumberWrapper = Default::default();
-      // String, (Vote, (u32, u32))
-      for (voter_id, mut vote) in submission_votes.clone() {
-        if vote == Vote::Delegate {
-          vote = VotingSystem::calculate_quorum_consensus(
-            env.clone(),
-            voter_id.clone(),
-            submission_id.clone(),
-          )?;
-        }
-        let voting_power = match vote {
-          Vote::Abstain => ABSTAIN_VOTING_POWER,
-          _ => VotingSystem::get_neural_governance(env.clone())?.execute_neural_governance(
-            env.clone(),
-            voter_id.clone(),
-            submission_id.clone(),
-          )?,
-        };
-        match vote {
-          Vote::Yes => {
-            submission_voting_power_plus = DecimalNumberWrapper::add(
-              submission_voting_power_plus,
-              DecimalNumberWrapper::from(voting_power),
-            )
-          }
-          Vote::No => {
-            submission_voting_power_minus = DecimalNumberWrapper::add(
-              submission_voting_power_minus,
-              DecimalNumberWrapper::from(voting_power),
-            )
-          }
-          _ => (),
-        };
-      }
+
+    let results_key = DataKey::Results(round.id);
+    env.storage().persistent().set(&results_key, &result);
+    VotingSystem::extend_ttl(&env, &results_key);
+    VotingSystem::set_round(&env, &round);
+    events::tally_results(&env, round.id, result.clone());
+    Ok(result)
+  }
+
+  // the same calculation as tally with the yes and no voting power, the abstain and delegated
+  // votes and the contribution of every voter for every submission, nothing is stored
+  pub fn tally_detailed(env: Env) -> Result<Map<String, SubmissionTally>, VotingSystemError> {
+    let round = VotingSystem::get_current_round(env.clone())?;
+    round.require_closed()?;
+
+    let neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let mut result: Map<String, SubmissionTally> = Map::new(&env);
+    for submission_id in submissions::get_active_ids(&env, round.id) {
       result.set(
-        submission_id,
-        DecimalNumberWrapper::sub(submission_voting_power_plus, submission_voting_power_minus)
-          .as_tuple(),
-      )
+        submission_id.clone(),
+        tally::tally_submission(&env, &neural_governance, round.id, submission_id)?.0,
+      );
     }
     Ok(result)
   }
@@ -404,6 +832,7 @@ impl VotingSystem {
   /**
    * This is a breakdown of the tally function, instead of tally, you can call:
    * 1. normalize_votes
+   * 1a. calculate_median_voting_power - for every submission, only if the cap relative to the median voter is set
    * 2. voting_power_for_voter - for every user/submission (this depends on the fact whether any neurons consider submission id in calculations) - you should iterate over the result of normalize_votes
    * 3. submissions_voting_powers - with the results of normalize_votes and voting_power_for_voter
    *
@@ -412,32 +841,15 @@ impl VotingSystem {
 
   // this will convert all the votes to either Yes or No
   // returns Map<submission_id, Map<voter_id, normalized_vote>>
-  pub fn normalize_votes(env: Env) -> Result<Map<String, Map<String, String>>, VotingSystemError> {
-    // Map<submission_id, Map<voter_id, Vote>>
-    let all_votes = VotingSystem::get_votes(env.clone());
-    let mut normalized_votes: Map<String, Map<String, String>> = Map::new(&env);
-    for (submission_id, submission_votes) in all_votes {
-      // Map<voter_id, normalized_vote>
-      let mut normalized_votes_for_submission: Map<String, String> = Map::new(&env);
-      for (voter_id, mut vote) in submission_votes.clone() {
-        if vote == Vote::Delegate {
-          vote = VotingSystem::calculate_quorum_consensus(
-            env.clone(),
-            voter_id.clone(),
-            submission_id.clone(),
-          )?;
-        }
-        if vote == Vote::Abstain {
-          continue;
-        }
-        if vote == Vote::Yes {
-          normalized_votes_for_submission.set(voter_id, String::from_slice(&env, "Yes"));
-        } else if vote == Vote::No {
-          normalized_votes_for_submission.set(voter_id, String::from_slice(&env, "No"));
-        } else {
-          return Err(VotingSystemError::UnexpectedValue);
-        }
-      }
+  pub fn normalize_votes(
+    env: Env,
+  ) -> Result<Map<String, Map<String, NormalizedVote>>, VotingSystemError> {
+    VotingSystem::get_current_round(env.clone())?.require_closed()?;
+    let round_id = VotingSystem::current_round_id(&env);
+    let mut normalized_votes: Map<String, Map<String, NormalizedVote>> = Map::new(&env);
+    for submission_id in submissions::get_active_ids(&env, round_id) {
+      let normalized_votes_for_submission =
+        VotingSystem::normalize_submission_votes(&env, round_id, submission_id.clone())?;
       if !normalized_votes_for_submission.is_empty() {
         normalized_votes.set(submission_id, normalized_votes_for_submission);
       }
@@ -445,6 +857,21 @@ impl VotingSystem {
     Ok(normalized_votes)
   }
 
+  // string variant for the CLI, normalized votes are either "Yes" or "No"
+  pub fn normalize_votes_str(
+    env: Env,
+  ) -> Result<Map<String, Map<String, String>>, VotingSystemError> {
+    let mut result: Map<String, Map<String, String>> = Map::new(&env);
+    for (submission_id, normalized_votes) in VotingSystem::normalize_votes(env.clone())? {
+      let mut normalized_votes_str: Map<String, String> = Map::new(&env);
+      for (voter_id, normalized_vote) in normalized_votes {
+        normalized_votes_str.set(voter_id, normalized_vote_to_str(&env, normalized_vote));
+      }
+      result.set(submission_id, normalized_votes_str);
+    }
+    Ok(result)
+  }
+
   // normalize votes but just override the collection in place (in the storage) and do not return anything
   // they will be referred to in submissions_voting_powers (maybe normalized can be saved in a separate collection in storage)
 
@@ -452,14 +879,25 @@ impl VotingSystem {
   pub fn normalize_votes_for_submission(
     env: Env,
     submission_id: String,
-  ) -> Result<Map<String, String>, VotingSystemError> {
-    let submission_votes = VotingSystem::get_votes(env.clone())
-      .get(submission_id.clone())
-      .unwrap_or(Map::new(&env));
+  ) -> Result<Map<String, NormalizedVote>, VotingSystemError> {
+    VotingSystem::get_current_round(env.clone())?.require_closed()?;
+    VotingSystem::normalize_submission_votes(
+      &env,
+      VotingSystem::current_round_id(&env),
+      submission_id,
+    )
+  }
 
-    let mut result: Map<String, String> = Map::new(&env);
+  fn normalize_submission_votes(
+    env: &Env,
+    round_id: u32,
+    submission_id: String,
+  ) -> Result<Map<String, NormalizedVote>, VotingSystemError> {
+    let mut result: Map<String, NormalizedVote> = Map::new(env);
 
-    for (voter_id, mut vote) in submission_votes {
+    for (voter_id, mut vote) in
+      ballots::get_votes_for_submission(env, round_id, submission_id.clone())
+    {
       if vote == Vote::Delegate {
         vote = VotingSystem::calculate_quorum_consensus(
           env.clone(),
@@ -467,44 +905,230 @@ impl VotingSystem {
           submission_id.clone(),
         )?;
       }
-      if vote == Vote::Abstain {
-        continue;
-      }
-      if vote == Vote::Yes {
-        result.set(voter_id, String::from_slice(&env, "Yes"));
-      } else if vote == Vote::No {
-        result.set(voter_id, String::from_slice(&env, "No"));
-      } else {
-        return Err(VotingSystemError::UnexpectedValue);
+      match vote {
+        Vote::Abstain => continue,
+        Vote::Yes => result.set(voter_id, NormalizedVote::Yes),
+        Vote::No => result.set(voter_id, NormalizedVote::No),
+        _ => return Err(VotingSystemError::UnexpectedValue),
       }
     }
 
     Ok(result)
   }
 
+  // runs the neural governance for every voter of the submission and stores the median of their
+  // voting powers, so voting_power_for_voter doesn't have to do it for every voter
+  // tally stores it as well, a change of the neural governance, the votes, the delegatees or the
+  // external data provider discards it until it's calculated again
+  pub fn calculate_median_voting_power(
+    env: Env,
+    submission_id: String,
+  ) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+    let round = VotingSystem::get_current_round(env.clone())?;
+    round.require_closed()?;
+    let neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let median_voting_power = VotingSystem::submission_median_voting_power(
+      &env,
+      &neural_governance,
+      submission_id.clone(),
+    )?;
+    if let Some(median_voting_power) = median_voting_power {
+      VotingSystem::store_median_voting_power(&env, round.id, submission_id, median_voting_power);
+    }
+    Ok(median_voting_power)
+  }
+
   // this calls a neural governance for every voter and submission
+  // if the cap relative to the median voter is set, the median stored by
+  // calculate_median_voting_power is used, without it the neural governance of all the submission's
+  // voters is run on every call and nothing is stored, in any state of the round
   pub fn voting_power_for_voter(
     env: Env,
     voter_id: String,
     submission_id: String,
   ) -> Result<(u32, u32), VotingSystemError> {
-    let voting_power = VotingSystem::get_neural_governance(env.clone())?
-      .execute_neural_governance(env.clone(), voter_id.clone(), submission_id.clone())?;
+    let neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let unbounded_voting_power = neural_governance.execute_neural_governance(
+      env.clone(),
+      voter_id.clone(),
+      submission_id.clone(),
+    )?;
+    let voting_power = VotingSystem::bound_voting_power(
+      &env,
+      &neural_governance,
+      submission_id,
+      unbounded_voting_power,
+    )?;
 
-    VotingSystem::set_voting_power_for_user(env, voter_id, voting_power);
+    VotingSystem::store_voting_power(env, voter_id, voting_power);
 
     Ok(voting_power)
   }
 
-  pub fn get_voting_powers(env: Env) -> Map<String, (u32, u32)> {
+  // what voting_power_for_voter returns with the result of every layer and neuron, nothing is stored
+  pub fn explain_voting_power(
+    env: Env,
+    voter_id: String,
+    submission_id: String,
+  ) -> Result<VotingPowerExplanation, VotingSystemError> {
+    let neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let (layers, unbounded_voting_power) =
+      neural_governance.explain_neural_governance(env.clone(), voter_id, submission_id.clone())?;
+    let voting_power = VotingSystem::bound_voting_power(
+      &env,
+      &neural_governance,
+      submission_id,
+      unbounded_voting_power,
+    )?;
+    Ok(VotingPowerExplanation {
+      layers,
+      unbounded_voting_power,
+      voting_power,
+    })
+  }
+
+  fn bound_voting_power(
+    env: &Env,
+    neural_governance: &NeuralGovernance,
+    submission_id: String,
+    voting_power: PreciseDecimalNumber,
+  ) -> Result<(u32, u32), VotingSystemError> {
+    let mut median_voting_power =
+      VotingSystem::stored_median_voting_power(env, submission_id.clone());
+    if median_voting_power.is_none() {
+      median_voting_power =
+        VotingSystem::submission_median_voting_power(env, neural_governance, submission_id)?;
+    }
+    let voting_power = neural_governance
+      .voting_power_bounds
+      .apply(voting_power, median_voting_power)?;
+    // only the returned voting power is rounded to the 3 decimal places of DecimalNumber
+    Ok(PreciseDecimal::from_raw(voting_power).as_decimal()?)
+  }
+
+  fn median_voting_power_version(env: &Env) -> u32 {
     env
       .storage()
       .instance()
-      .get(&DataKey::VotingPowers)
+      .get(&DataKey::MedianVotingPowerVersion)
+      .unwrap_or(0)
+  }
+
+  // called by everything that can change the voting powers, external neurons and the data in the
+  // external data provider are not tracked
+  fn invalidate_median_voting_powers(env: &Env) {
+    env.storage().instance().set(
+      &DataKey::MedianVotingPowerVersion,
+      &VotingSystem::median_voting_power_version(env).wrapping_add(1),
+    );
+  }
+
+  fn store_median_voting_power(
+    env: &Env,
+    round_id: u32,
+    submission_id: String,
+    median_voting_power: PreciseDecimalNumber,
+  ) {
+    let key = DataKey::MedianVotingPower(round_id, submission_id);
+    env.storage().persistent().set(
+      &key,
+      &(
+        VotingSystem::median_voting_power_version(env),
+        median_voting_power,
+      ),
+    );
+    VotingSystem::extend_ttl(env, &key);
+  }
+
+  // the median of the current round, unless something changed since it was stored
+  fn stored_median_voting_power(env: &Env, submission_id: String) -> Option<PreciseDecimalNumber> {
+    let (version, median_voting_power): (u32, PreciseDecimalNumber) =
+      env.storage().persistent().get(&DataKey::MedianVotingPower(
+        VotingSystem::current_round_id(env),
+        submission_id,
+      ))?;
+    if version != VotingSystem::median_voting_power_version(env) {
+      return None;
+    }
+    Some(median_voting_power)
+  }
+
+  // runs the neural governance for every voter of the submission
+  fn submission_median_voting_power(
+    env: &Env,
+    neural_governance: &NeuralGovernance,
+    submission_id: String,
+  ) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+    if neural_governance
+      .voting_power_bounds
+      .median_multiple
+      .is_none()
+    {
+      return Ok(None);
+    }
+    let normalized_votes = VotingSystem::normalize_submission_votes(
+      env,
+      VotingSystem::current_round_id(env),
+      submission_id.clone(),
+    )?;
+    let unbounded_voting_powers = VotingSystem::unbounded_voting_powers(
+      env,
+      neural_governance,
+      submission_id,
+      &normalized_votes,
+    )?;
+    VotingSystem::median_voting_power(neural_governance, &unbounded_voting_powers)
+  }
+
+  // Map<voter_id, voting_power> - results of the last layer for the voters who voted Yes or No
+  fn unbounded_voting_powers(
+    env: &Env,
+    neural_governance: &NeuralGovernance,
+    submission_id: String,
+    normalized_votes: &Map<String, NormalizedVote>,
+  ) -> Result<Map<String, PreciseDecimalNumber>, VotingSystemError> {
+    let mut voting_powers: Map<String, PreciseDecimalNumber> = Map::new(env);
+    for voter_id in normalized_votes.keys() {
+      let voting_power = neural_governance.execute_neural_governance(
+        env.clone(),
+        voter_id.clone(),
+        submission_id.clone(),
+      )?;
+      voting_powers.set(voter_id, voting_power);
+    }
+    Ok(voting_powers)
+  }
+
+  // only needed if the cap relative to the median voter is set
+  fn median_voting_power(
+    neural_governance: &NeuralGovernance,
+    unbounded_voting_powers: &Map<String, PreciseDecimalNumber>,
+  ) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+    if neural_governance
+      .voting_power_bounds
+      .median_multiple
+      .is_none()
+    {
+      return Ok(None);
+    }
+    tally::median_voting_power(unbounded_voting_powers.values())
+  }
+
+  // voting powers of the current round
+  pub fn get_voting_powers(env: Env) -> Map<String, (u32, u32)> {
+    env
+      .storage()
+      .persistent()
+      .get(&DataKey::VotingPowers(VotingSystem::current_round_id(&env)))
       .unwrap_or(Map::new(&env))
   }
 
-  pub fn set_voting_powers(env: Env, new_voting_powers: Vec<(String, u32)>) {
+  pub fn set_voting_powers(
+    env: Env,
+    new_voting_powers: Vec<(String, u32)>,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+
     let mut voting_powers = VotingSystem::get_voting_powers(env.clone());
 
     for (voter_id, voting_power) in new_voting_powers {
@@ -514,25 +1138,32 @@ impl VotingSystem {
       );
     }
 
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::VotingPowers, &voting_powers);
+    VotingSystem::set_voting_powers_of_current_round(&env, &voting_powers);
+    Ok(())
   }
 
-  pub fn set_voting_power_for_user(env: Env, voter_id: String, voting_power: (u32, u32)) {
-    let mut voting_powers: Map<String, (u32, u32)> = env
-      .storage()
-      .instance()
-      .get(&DataKey::VotingPowers)
-      .unwrap_or(Map::new(&env));
+  fn set_voting_powers_of_current_round(env: &Env, voting_powers: &Map<String, (u32, u32)>) {
+    let key = DataKey::VotingPowers(VotingSystem::current_round_id(env));
+    env.storage().persistent().set(&key, voting_powers);
+    VotingSystem::extend_ttl(env, &key);
+  }
+
+  pub fn set_voting_power_for_user(
+    env: Env,
+    voter_id: String,
+    voting_power: (u32, u32),
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    VotingSystem::store_voting_power(env, voter_id, voting_power);
+    Ok(())
+  }
+
+  fn store_voting_power(env: Env, voter_id: String, voting_power: (u32, u32)) {
+    let mut voting_powers = VotingSystem::get_voting_powers(env.clone());
 
     voting_powers.set(voter_id.clone(), voting_power);
 
-    env
-      .storage()
-      .instance()
-      .set(&DataKey::VotingPowers, &voting_powers);
+    VotingSystem::set_voting_powers_of_current_round(&env, &voting_powers);
   }
 
   pub fn submissions_voting_powers(
@@ -540,41 +1171,41 @@ impl VotingSystem {
     // Map<user_id, voting_power>
     voters_voting_powers: Map<String, u32>,
     // Map<submission_id, Map<user_id, normalized_vote>>
-    normalized_votes: Map<String, Map<String, String>>,
-  ) -> Result<Map<String, (u32, u32)>, VotingSystemError> {
-    let mut result: Map<String, (u32, u32)> = Map::new(&env);
+    normalized_votes: Map<String, Map<String, NormalizedVote>>,
+  ) -> Result<Map<String, SubmissionResult>, VotingSystemError> {
+    let mut result: Map<String, SubmissionResult> = Map::new(&env);
 
     for (submission_id, votes) in normalized_votes {
-      let mut submission_voting_power_plus: DecimalNumberWrapper = Default::default();
-      let mut submission_voting_power_minus: DecimalNumberWrapper = Default::default();
-      for (voter_id, normalized_vote_str) in votes {
-        let normalized_vote = normalized_vote_from_str(&env, normalized_vote_str)?;
+      let mut submission_voting_power_plus: SignedDecimal = Default::default();
+      let mut submission_voting_power_minus: SignedDecimal = Default::default();
+      let mut yes_count = 0;
+      let votes_count = votes.len();
+      for (voter_id, normalized_vote) in votes {
         let voter_voting_power: Option<u32> = voters_voting_powers.get(voter_id);
         if voter_voting_power.is_none() {
           return Err(VotingSystemError::UnknownVoter);
         }
-        let voter_voting_power: u32 = voter_voting_power.unwrap();
-        let voter_voting_power: (u32, u32) =
-          DecimalNumberWrapper::from(voter_voting_power).as_tuple();
+        let voter_voting_power = SignedDecimal::from_raw(voter_voting_power.unwrap() as i128);
         match normalized_vote {
           NormalizedVote::Yes => {
-            submission_voting_power_plus = DecimalNumberWrapper::add(
-              submission_voting_power_plus,
-              DecimalNumberWrapper::from(voter_voting_power),
-            )
+            yes_count += 1;
+            submission_voting_power_plus =
+              SignedDecimal::add(submission_voting_power_plus, voter_voting_power)?
           }
           NormalizedVote::No => {
-            submission_voting_power_minus = DecimalNumberWrapper::add(
-              submission_voting_power_minus,
-              DecimalNumberWrapper::from(voter_voting_power),
-            )
+            submission_voting_power_minus =
+              SignedDecimal::add(submission_voting_power_minus, voter_voting_power)?
           }
         };
       }
+      // the normalized votes don't include the abstaining and delegating voters
       result.set(
         submission_id,
-        DecimalNumberWrapper::sub(submission_voting_power_plus, submission_voting_power_minus)
-          .as_tuple(),
+        SubmissionResult::new(
+          submission_voting_power_plus,
+          submission_voting_power_minus,
+          (yes_count, votes_count - yes_count, 0, 0),
+        )?,
       )
     }
 
@@ -588,35 +1219,39 @@ impl VotingSystem {
     voters_voting_powers_vec: Vec<(String, u32)>,
     // Map<submission_id, Map<user_id, normalized_vote>>
     normalized_votes_vec: Vec<(String, String, String)>,
-  ) -> Result<Map<String, (u32, u32)>, VotingSystemError> {
+  ) -> Result<Map<String, SubmissionResult>, VotingSystemError> {
     let mut voters_voting_powers: Map<String, u32> = Map::new(&env);
     for (user_id, voting_power) in voters_voting_powers_vec {
       voters_voting_powers.set(user_id, voting_power);
     }
 
-    let mut normalized_votes: Map<String, Map<String, String>> = Map::new(&env);
+    let mut normalized_votes: Map<String, Map<String, NormalizedVote>> = Map::new(&env);
     for (submission_id, voter_id, vote) in normalized_votes_vec {
       if normalized_votes.get(submission_id.clone()).is_none() {
         normalized_votes.set(submission_id.clone(), Map::new(&env));
       }
       let mut current = normalized_votes.get(submission_id.clone()).unwrap();
-      current.set(voter_id.clone(), vote.clone());
+      current.set(voter_id.clone(), normalized_vote_from_str(&env, vote)?);
       normalized_votes.set(submission_id.clone(), current);
     }
     VotingSystem::submissions_voting_powers(env, voters_voting_powers, normalized_votes)
   }
 
   pub fn add_layer(env: Env) -> Result<u32, VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
     let new_layer_id = neural_governance.add_layer(env.clone());
-    VotingSystem::set_neural_governance(env, neural_governance);
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::layer_added(&env, new_layer_id);
     Ok(new_layer_id)
   }
 
   pub fn remove_layer(env: Env, layer_id: u32) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
     neural_governance.remove_layer(layer_id)?;
-    VotingSystem::set_neural_governance(env, neural_governance);
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::layer_removed(&env, layer_id);
     Ok(())
   }
 
@@ -625,26 +1260,80 @@ impl VotingSystem {
     layer_id: u32,
     aggregator: String,
   ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
-    let layer_aggregator: LayerAggregator = layer_aggregator_from_str(&env, aggregator);
-    neural_governance.set_layer_aggregator(layer_id, layer_aggregator)?;
-    VotingSystem::set_neural_governance(env, neural_governance);
+    let layer_aggregator: LayerAggregator = layer_aggregator_from_str(&env, aggregator)?;
+    neural_governance.set_layer_aggregator(layer_id, layer_aggregator.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::layer_aggregator_set(&env, layer_id, layer_aggregator);
     Ok(())
   }
 
-  pub fn add_neuron(env: Env, layer_id: u32, neuron: String) -> Result<(), VotingSystemError> {
+  pub fn set_layer_input_mode(
+    env: Env,
+    layer_id: u32,
+    input_mode: String,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let input_mode = layer_input_mode_from_str(&env, input_mode)?;
+    neural_governance.set_layer_input_mode(layer_id, input_mode.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::layer_input_mode_set(&env, layer_id, input_mode);
+    Ok(())
+  }
+
+  pub fn add_neuron(env: Env, layer_id: u32, neuron: String) -> Result<(), VotingSystemError> {
     let neuron = neuron_type_from_str(&env, neuron)?;
-    neural_governance.add_neuron(layer_id, neuron)?;
-    VotingSystem::set_neural_governance(env, neural_governance);
+    VotingSystem::add_neuron_of_type(env, layer_id, neuron)
+  }
+
+  // adds a neuron deployed as a separate contract, it has to implement
+  // oracle_function(voter_id, submission_id, previous_layer_vote) -> DecimalNumber
+  pub fn add_external_neuron(
+    env: Env,
+    layer_id: u32,
+    neuron_address: Address,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::add_neuron_of_type(env, layer_id, NeuronType::External(neuron_address))
+  }
+
+  fn add_neuron_of_type(
+    env: Env,
+    layer_id: u32,
+    neuron: NeuronType,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    neural_governance.add_neuron(layer_id, neuron.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::neuron_added(&env, layer_id, neuron);
     Ok(())
   }
 
   pub fn remove_neuron(env: Env, layer_id: u32, neuron: String) -> Result<(), VotingSystemError> {
-    let mut neural_governance = VotingSystem::get_neural_governance(env.clone()).unwrap();
     let neuron = neuron_type_from_str(&env, neuron)?;
-    neural_governance.remove_neuron(layer_id, neuron)?;
-    VotingSystem::set_neural_governance(env, neural_governance);
+    VotingSystem::remove_neuron_of_type(env, layer_id, neuron)
+  }
+
+  pub fn remove_external_neuron(
+    env: Env,
+    layer_id: u32,
+    neuron_address: Address,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::remove_neuron_of_type(env, layer_id, NeuronType::External(neuron_address))
+  }
+
+  fn remove_neuron_of_type(
+    env: Env,
+    layer_id: u32,
+    neuron: NeuronType,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    neural_governance.remove_neuron(layer_id, neuron.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::neuron_removed(&env, layer_id, neuron);
     Ok(())
   }
 
@@ -654,39 +1343,143 @@ impl VotingSystem {
     neuron: String,
     weight: u32,
   ) -> Result<(), VotingSystemError> {
-    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
     let neuron = neuron_type_from_str(&env, neuron)?;
+    VotingSystem::set_neuron_of_type_weight(env, layer_id, neuron, weight)
+  }
+
+  pub fn set_external_neuron_weight(
+    env: Env,
+    layer_id: u32,
+    neuron_address: Address,
+    weight: u32,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::set_neuron_of_type_weight(
+      env,
+      layer_id,
+      NeuronType::External(neuron_address),
+      weight,
+    )
+  }
+
+  fn set_neuron_of_type_weight(
+    env: Env,
+    layer_id: u32,
+    neuron: NeuronType,
+    weight: u32,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
     let weight = DecimalNumberWrapper::from(weight).as_tuple();
-    neural_governance.set_neuron_weight(layer_id, neuron, weight)?;
-    VotingSystem::set_neural_governance(env, neural_governance);
+    neural_governance.set_neuron_weight(layer_id, neuron.clone(), weight)?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::neuron_weight_set(&env, layer_id, neuron, weight);
+    Ok(())
+  }
+
+  pub fn set_neuron_activation(
+    env: Env,
+    layer_id: u32,
+    neuron: String,
+    activation: Activation,
+  ) -> Result<(), VotingSystemError> {
+    let neuron = neuron_type_from_str(&env, neuron)?;
+    VotingSystem::set_neuron_of_type_activation(env, layer_id, neuron, activation)
+  }
+
+  pub fn set_external_neuron_activation(
+    env: Env,
+    layer_id: u32,
+    neuron_address: Address,
+    activation: Activation,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::set_neuron_of_type_activation(
+      env,
+      layer_id,
+      NeuronType::External(neuron_address),
+      activation,
+    )
+  }
+
+  fn set_neuron_of_type_activation(
+    env: Env,
+    layer_id: u32,
+    neuron: NeuronType,
+    activation: Activation,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    neural_governance.set_neuron_activation(layer_id, neuron.clone(), activation.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::neuron_activation_set(&env, layer_id, neuron, activation);
+    Ok(())
+  }
+
+  // all the values are raw decimal numbers, (1000, None, Some(3000)) limits the voting power
+  // to at least 1 and at most three times the median voting power of the submission's voters
+  pub fn set_voting_power_bounds(
+    env: Env,
+    min: u32,
+    max: Option<u32>,
+    median_multiple: Option<u32>,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let voting_power_bounds = VotingPowerBounds {
+      min,
+      max,
+      median_multiple,
+    };
+    neural_governance.set_voting_power_bounds(voting_power_bounds.clone())?;
+    VotingSystem::set_neural_governance(env.clone(), neural_governance);
+    events::voting_power_bounds_set(&env, voting_power_bounds);
     Ok(())
   }
 
-  // todo test this method
   pub fn setup_layer(
     env: Env,
     layer_aggregator: String,
+    layer_input_mode: String,
     neurons: Vec<(String, u32)>,
   ) -> Result<(), VotingSystemError> {
-    let layer_id = VotingSystem::add_layer(env.clone())?;
-    VotingSystem::set_layer_aggregator(env.clone(), layer_id, layer_aggregator)?;
+    VotingSystem::require_admin(&env)?;
+    let mut neural_governance = VotingSystem::get_neural_governance(env.clone())?;
+    let layer_id = neural_governance.add_layer(env.clone());
+    events::layer_added(&env, layer_id);
+    let layer_aggregator = layer_aggregator_from_str(&env, layer_aggregator)?;
+    neural_governance.set_layer_aggregator(layer_id, layer_aggregator.clone())?;
+    events::layer_aggregator_set(&env, layer_id, layer_aggregator);
+    let layer_input_mode = layer_input_mode_from_str(&env, layer_input_mode)?;
+    neural_governance.set_layer_input_mode(layer_id, layer_input_mode.clone())?;
+    events::layer_input_mode_set(&env, layer_id, layer_input_mode);
 
     for (neuron, neuron_weight) in neurons {
-      VotingSystem::add_neuron(env.clone(), layer_id, neuron.clone())?;
+      let neuron = neuron_type_from_str(&env, neuron)?;
+      neural_governance.add_neuron(layer_id, neuron.clone())?;
+      events::neuron_added(&env, layer_id, neuron.clone());
       if DecimalNumberWrapper::from(neuron_weight).as_tuple() != DEFAULT_WEIGHT
         && neuron_weight != 0
       {
-        VotingSystem::set_neuron_weight(env.clone(), layer_id, neuron.clone(), neuron_weight)?;
+        let weight = DecimalNumberWrapper::from(neuron_weight).as_tuple();
+        neural_governance.set_neuron_weight(layer_id, neuron.clone(), weight)?;
+        events::neuron_weight_set(&env, layer_id, neuron, weight);
       }
     }
+    VotingSystem::set_neural_governance(env, neural_governance);
     Ok(())
   }
 
-  pub fn set_external_data_provider(env: Env, external_data_provider_address: Address) {
+  pub fn set_external_data_provider(
+    env: Env,
+    external_data_provider_address: Address,
+  ) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     env.storage().instance().set(
       &DataKey::ExternalDataProvider,
       &external_data_provider_address,
     );
+    VotingSystem::invalidate_median_voting_powers(&env);
+    events::external_data_provider_set(&env, external_data_provider_address);
+    Ok(())
   }
 
   pub fn get_external_data_provider(env: Env) -> Result<Address, VotingSystemError> {
@@ -697,16 +1490,24 @@ impl VotingSystem {
       .ok_or(VotingSystemError::ExternalDataProviderNotSet)?
   }
 
+  // the voting system contract has to be a writer of the PageRankResult dataset
   pub fn calculate_page_rank(env: Env) -> Result<(), VotingSystemError> {
+    VotingSystem::require_admin(&env)?;
     let external_data_provider_address = VotingSystem::get_external_data_provider(env.clone())?;
     let external_data_provider_client =
       external_data_provider_contract::Client::new(&env, &external_data_provider_address);
 
-    external_data_provider_client.calculate_page_rank();
+    external_data_provider_client.calculate_page_rank(&env.current_contract_address());
 
     Ok(())
   }
 }
 
+#[cfg(test)]
+mod activation_test;
+#[cfg(test)]
+mod layer_test;
+#[cfg(test)]
+mod tally_test;
 #[cfg(test)]
 mod voting_system_test;
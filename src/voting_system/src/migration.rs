@@ -0,0 +1,202 @@
+use crate::{
+  activation::Activation,
+  layer::{Layer, NeuronConfig},
+  neural_governance::{NeuralGovernance, VotingPowerBounds},
+  types::{LayerAggregator, LayerInputMode, NeuronType},
+  DataKey,
+};
+use soroban_sdk::{contracttype, Env, Map, Vec};
+
+// Layer as it was stored before neurons got activation functions and layers got input modes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyLayer {
+  pub id: u32,
+  // neuron type, neuron raw weight
+  pub neurons: Map<NeuronType, u32>,
+  pub aggregator: LayerAggregator,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyNeuralGovernance {
+  pub layers: Vec<LegacyLayer>,
+  pub current_layer_id: u32,
+}
+
+impl LegacyNeuralGovernance {
+  // keeps the weights, the linear activations and the additive input mode give the old behaviour
+  pub fn into_current(self, env: &Env) -> NeuralGovernance {
+    let mut layers: Vec<Layer> = Vec::new(env);
+    for legacy_layer in self.layers.iter() {
+      let mut neurons: Map<NeuronType, NeuronConfig> = Map::new(env);
+      for (neuron, weight) in legacy_layer.neurons.iter() {
+        neurons.set(
+          neuron,
+          NeuronConfig {
+            weight,
+            activation: Activation::Linear,
+          },
+        );
+      }
+      layers.push_back(Layer {
+        id: legacy_layer.id,
+        neurons,
+        aggregator: legacy_layer.aggregator,
+        input_mode: LayerInputMode::Additive,
+      });
+    }
+    NeuralGovernance {
+      layers,
+      current_layer_id: self.current_layer_id,
+      voting_power_bounds: VotingPowerBounds::default(),
+    }
+  }
+}
+
+// Activation as it was stored in versions 1 and 2, before the values could be negative
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ActivationV2 {
+  Linear,
+  Clamp(u32, u32),
+  Step(u32, u32, u32),
+  Logarithmic,
+  SquareRoot,
+  Piecewise(Vec<(u32, u32)>),
+}
+
+impl ActivationV2 {
+  // the raw values keep their 3 decimal places
+  pub fn into_current(self, env: &Env) -> Activation {
+    match self {
+      ActivationV2::Linear => Activation::Linear,
+      ActivationV2::Clamp(min, max) => Activation::Clamp(min as i128, max as i128),
+      ActivationV2::Step(threshold, below, above) => {
+        Activation::Step(threshold as i128, below as i128, above as i128)
+      }
+      ActivationV2::Logarithmic => Activation::Logarithmic,
+      ActivationV2::SquareRoot => Activation::SquareRoot,
+      ActivationV2::Piecewise(breakpoints) => {
+        let mut signed_breakpoints = Vec::new(env);
+        for (x, y) in breakpoints.iter() {
+          signed_breakpoints.push_back((x as i128, y as i128));
+        }
+        Activation::Piecewise(signed_breakpoints)
+      }
+    }
+  }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeuronConfigV2 {
+  pub weight: u32,
+  pub activation: ActivationV2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayerV2 {
+  pub id: u32,
+  pub neurons: Map<NeuronType, NeuronConfigV2>,
+  pub aggregator: LayerAggregator,
+  pub input_mode: LayerInputMode,
+}
+
+impl LayerV2 {
+  pub fn into_current(self, env: &Env) -> Layer {
+    let mut neurons: Map<NeuronType, NeuronConfig> = Map::new(env);
+    for (neuron, neuron_config) in self.neurons.iter() {
+      neurons.set(
+        neuron,
+        NeuronConfig {
+          weight: neuron_config.weight,
+          activation: neuron_config.activation.into_current(env),
+        },
+      );
+    }
+    Layer {
+      id: self.id,
+      neurons,
+      aggregator: self.aggregator,
+      input_mode: self.input_mode,
+    }
+  }
+
+  fn into_current_layers(env: &Env, layers: Vec<LayerV2>) -> Vec<Layer> {
+    let mut current_layers: Vec<Layer> = Vec::new(env);
+    for layer in layers.iter() {
+      current_layers.push_back(layer.into_current(env));
+    }
+    current_layers
+  }
+}
+
+// NeuralGovernance as it was stored in version 1, before the voting power bounds
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeuralGovernanceV1 {
+  pub layers: Vec<LayerV2>,
+  pub current_layer_id: u32,
+}
+
+impl NeuralGovernanceV1 {
+  pub fn into_current(self, env: &Env) -> NeuralGovernance {
+    NeuralGovernance {
+      layers: LayerV2::into_current_layers(env, self.layers),
+      current_layer_id: self.current_layer_id,
+      voting_power_bounds: VotingPowerBounds::default(),
+    }
+  }
+}
+
+// NeuralGovernance as it was stored in version 2, before the activations got signed values
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NeuralGovernanceV2 {
+  pub layers: Vec<LayerV2>,
+  pub current_layer_id: u32,
+  pub voting_power_bounds: VotingPowerBounds,
+}
+
+impl NeuralGovernanceV2 {
+  pub fn into_current(self, env: &Env) -> NeuralGovernance {
+    NeuralGovernance {
+      layers: LayerV2::into_current_layers(env, self.layers),
+      current_layer_id: self.current_layer_id,
+      voting_power_bounds: self.voting_power_bounds,
+    }
+  }
+}
+
+// version of the stored NeuralGovernance shape, contracts which don't store it have the legacy one
+pub const NEURAL_GOVERNANCE_VERSION: u32 = 3;
+
+// reads the stored neural governance in any of the shapes, the flag is true if it is an older one
+pub fn load_neural_governance(env: &Env) -> Option<(NeuralGovernance, bool)> {
+  let version: u32 = env
+    .storage()
+    .instance()
+    .get(&DataKey::NeuralGovernanceVersion)
+    .unwrap_or(0);
+  match version {
+    NEURAL_GOVERNANCE_VERSION => {
+      let neural_governance = env.storage().instance().get(&DataKey::NeuralGovernance)?;
+      Some((neural_governance, false))
+    }
+    2 => {
+      let v2: NeuralGovernanceV2 = env.storage().instance().get(&DataKey::NeuralGovernance)?;
+      Some((v2.into_current(env), true))
+    }
+    1 => {
+      let v1: NeuralGovernanceV1 = env.storage().instance().get(&DataKey::NeuralGovernance)?;
+      Some((v1.into_current(env), true))
+    }
+    _ => {
+      let legacy: LegacyNeuralGovernance =
+        env.storage().instance().get(&DataKey::NeuralGovernance)?;
+      Some((legacy.into_current(env), true))
+    }
+  }
+}
@@ -1,10 +1,11 @@
 #![allow(non_upper_case_globals)]
 
 use crate::{
-  external_data_provider_contract,
+  activation::Activation,
+  external_data_provider_contract, submissions,
   types::{
-    DecimalNumber, LayerAggregator, NeuronType, VotingSystemError, DEFAULT_WEIGHT,
-    INITIAL_VOTING_POWER,
+    DecimalNumber, LayerAggregator, LayerInputMode, NeuronType, PreciseDecimal,
+    PreciseDecimalNumber, SignedDecimal, VotingSystemError, DEFAULT_WEIGHT, INITIAL_VOTING_POWER,
   },
   VotingSystem,
 };
@@ -12,13 +13,81 @@ use crate::{
 use soroban_decimal_numbers::DecimalNumberWrapper;
 use soroban_sdk::{contracttype, Env, Map, String, Vec};
 
-use crate::layer::Layer;
+use crate::layer::{Layer, NeuronConfig, NeuronExplanation};
+
+// bounds applied to the result of the last layer, all the values are raw decimal numbers with the
+// 3 decimal places of DecimalNumber
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VotingPowerBounds {
+  pub min: u32,
+  pub max: Option<u32>,
+  // the voting power is capped at the median voting power of the submission's voters times this
+  pub median_multiple: Option<u32>,
+}
+
+impl VotingPowerBounds {
+  pub fn validate(&self) -> Result<(), VotingSystemError> {
+    if self.max.is_some_and(|max| max < self.min) || self.median_multiple == Some(0) {
+      return Err(VotingSystemError::InvalidVotingPowerBounds);
+    }
+    Ok(())
+  }
+
+  // median is the median voting power of the submission's voters before they are bounded,
+  // the floor is applied last so it wins over the caps, it is never below 0 so a negative
+  // voting power can cancel a vote but never turn it around
+  pub fn apply(
+    &self,
+    voting_power: PreciseDecimalNumber,
+    median: Option<PreciseDecimalNumber>,
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
+    let mut voting_power = voting_power;
+    if let (Some(median_multiple), Some(median)) = (self.median_multiple, median) {
+      let median_cap = PreciseDecimal::mul(
+        PreciseDecimal::from_raw(median),
+        VotingPowerBounds::precise(median_multiple)?,
+      )?;
+      voting_power = voting_power.min(median_cap.as_raw());
+    }
+    if let Some(max) = self.max {
+      voting_power = voting_power.min(VotingPowerBounds::precise(max)?.as_raw());
+    }
+    Ok(voting_power.max(VotingPowerBounds::precise(self.min)?.as_raw()))
+  }
+
+  fn precise(value: u32) -> Result<PreciseDecimal, VotingSystemError> {
+    Ok(SignedDecimal::from_raw(value as i128).rescale()?)
+  }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayerExplanation {
+  pub layer_id: u32,
+  pub aggregator: LayerAggregator,
+  pub neurons: Vec<NeuronExplanation>,
+  // the result of the layer aggregator
+  pub result: PreciseDecimalNumber,
+}
+
+// returned by explain_voting_power
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VotingPowerExplanation {
+  pub layers: Vec<LayerExplanation>,
+  // the result of the last layer
+  pub unbounded_voting_power: PreciseDecimalNumber,
+  // after the voting power bounds are applied, this is what the vote counts for
+  pub voting_power: DecimalNumber,
+}
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct NeuralGovernance {
   pub layers: Vec<Layer>,
   pub current_layer_id: u32,
+  pub voting_power_bounds: VotingPowerBounds,
 }
 
 impl NeuralGovernance {
@@ -27,6 +96,7 @@ impl NeuralGovernance {
       id: self.current_layer_id,
       neurons: Map::new(&env),
       aggregator: LayerAggregator::Unknown,
+      input_mode: LayerInputMode::Additive,
     });
     let result = self.current_layer_id;
     self.current_layer_id += 1;
@@ -52,12 +122,29 @@ impl NeuralGovernance {
     Ok(index.ok_or(VotingSystemError::NoSuchLayer)?)
   }
 
+  // the built-in neurons don't take the previous layer result, so they can't be in a layer which
+  // only passes it to the neurons, they would behave like in an Ignored one
+  fn validate_neuron_input(
+    input_mode: &LayerInputMode,
+    neuron: &NeuronType,
+  ) -> Result<(), VotingSystemError> {
+    if *input_mode == LayerInputMode::Explicit && !matches!(neuron, NeuronType::External(_)) {
+      return Err(VotingSystemError::UnexpectedValue);
+    }
+    Ok(())
+  }
+
   pub fn add_neuron(&mut self, layer_id: u32, neuron: NeuronType) -> Result<(), VotingSystemError> {
     let index = self.get_layer_index(layer_id)?;
     let mut new_layer = self.layers.get(index).unwrap().clone();
-    new_layer
-      .neurons
-      .set(neuron, DecimalNumberWrapper::from(DEFAULT_WEIGHT).as_raw());
+    NeuralGovernance::validate_neuron_input(&new_layer.input_mode, &neuron)?;
+    new_layer.neurons.set(
+      neuron,
+      NeuronConfig {
+        weight: DecimalNumberWrapper::from(DEFAULT_WEIGHT).as_raw(),
+        activation: Activation::Linear,
+      },
+    );
     self.layers.remove(index);
     self.layers.insert(index, new_layer.clone());
 
@@ -91,6 +178,22 @@ impl NeuralGovernance {
     Ok(())
   }
 
+  pub fn set_layer_input_mode(
+    &mut self,
+    layer_id: u32,
+    input_mode: LayerInputMode,
+  ) -> Result<(), VotingSystemError> {
+    let index = self.get_layer_index(layer_id)?;
+    let mut new_layer = self.layers.get(index).unwrap().clone();
+    for neuron in new_layer.neurons.keys() {
+      NeuralGovernance::validate_neuron_input(&input_mode, &neuron)?;
+    }
+    new_layer.input_mode = input_mode;
+    self.layers.remove(index);
+    self.layers.insert(index, new_layer.clone());
+    Ok(())
+  }
+
   pub fn set_neuron_weight(
     &mut self,
     layer_id: u32,
@@ -99,21 +202,84 @@ impl NeuralGovernance {
   ) -> Result<(), VotingSystemError> {
     let index = self.get_layer_index(layer_id)?;
     let mut new_layer = self.layers.get(index).unwrap().clone();
-    new_layer
+    NeuralGovernance::validate_neuron_input(&new_layer.input_mode, &neuron)?;
+    let activation = new_layer
+      .neurons
+      .get(neuron.clone())
+      .map(|neuron_config| neuron_config.activation)
+      .unwrap_or(Activation::Linear);
+    new_layer.neurons.set(
+      neuron,
+      NeuronConfig {
+        weight: DecimalNumberWrapper::from(weight).as_raw(),
+        activation,
+      },
+    );
+    self.layers.remove(index);
+    self.layers.insert(index, new_layer.clone());
+    Ok(())
+  }
+
+  pub fn set_neuron_activation(
+    &mut self,
+    layer_id: u32,
+    neuron: NeuronType,
+    activation: Activation,
+  ) -> Result<(), VotingSystemError> {
+    activation.validate()?;
+    let index = self.get_layer_index(layer_id)?;
+    let mut new_layer = self.layers.get(index).unwrap().clone();
+    let mut neuron_config = new_layer
       .neurons
-      .set(neuron, DecimalNumberWrapper::from(weight).as_raw());
+      .get(neuron.clone())
+      .ok_or(VotingSystemError::NoSuchNeuron)?;
+    neuron_config.activation = activation;
+    new_layer.neurons.set(neuron, neuron_config);
     self.layers.remove(index);
     self.layers.insert(index, new_layer.clone());
     Ok(())
   }
 
+  pub fn set_voting_power_bounds(
+    &mut self,
+    voting_power_bounds: VotingPowerBounds,
+  ) -> Result<(), VotingSystemError> {
+    voting_power_bounds.validate()?;
+    self.voting_power_bounds = voting_power_bounds;
+    Ok(())
+  }
+
+  // the result of the last layer, the voting power bounds are applied by the caller as the cap
+  // relative to the median voter needs the voting powers of the other voters
   pub fn execute_neural_governance(
     &self,
     env: Env,
     voter_id: String,
     submission_id: String,
-  ) -> Result<(u32, u32), VotingSystemError> {
-    let mut current_layer_result: (u32, u32) = INITIAL_VOTING_POWER;
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
+    self.run_layers(env, voter_id, submission_id, None)
+  }
+
+  // the same calculation as execute_neural_governance with the results of every layer and neuron
+  pub fn explain_neural_governance(
+    &self,
+    env: Env,
+    voter_id: String,
+    submission_id: String,
+  ) -> Result<(Vec<LayerExplanation>, PreciseDecimalNumber), VotingSystemError> {
+    let mut layers: Vec<LayerExplanation> = Vec::new(&env);
+    let result = self.run_layers(env, voter_id, submission_id, Some(&mut layers))?;
+    Ok((layers, result))
+  }
+
+  fn run_layers(
+    &self,
+    env: Env,
+    voter_id: String,
+    submission_id: String,
+    mut explanation: Option<&mut Vec<LayerExplanation>>,
+  ) -> Result<PreciseDecimalNumber, VotingSystemError> {
+    let mut current_layer_result: Option<PreciseDecimalNumber> = None;
 
     if self.layers.is_empty() {
       return Err(VotingSystemError::NoLayersExist);
@@ -121,16 +287,35 @@ impl NeuralGovernance {
     let external_data_provider_address = VotingSystem::get_external_data_provider(env.clone())?;
     let external_data_provider_client =
       external_data_provider_contract::Client::new(&env, &external_data_provider_address);
+    let submission_category = submissions::get(
+      &env,
+      VotingSystem::current_round_id(&env),
+      submission_id.clone(),
+    )
+    .ok()
+    .map(|submission| submission.category);
     for layer in self.layers.clone() {
-      let layer_result: Vec<(u32, u32)> = layer.execute_layer(
+      let mut neurons: Vec<NeuronExplanation> = Vec::new(&env);
+      let layer_result: Vec<PreciseDecimalNumber> = layer.execute_layer(
         env.clone(),
         voter_id.clone(),
         submission_id.clone(),
+        submission_category.clone(),
         current_layer_result,
         &external_data_provider_client,
+        explanation.as_ref().map(|_| &mut neurons),
       )?;
-      current_layer_result = layer.run_layer_aggregator(layer_result)?;
+      let aggregated_result = layer.run_layer_aggregator(layer_result)?;
+      if let Some(explanation) = explanation.as_mut() {
+        explanation.push_back(LayerExplanation {
+          layer_id: layer.id,
+          aggregator: layer.aggregator.clone(),
+          neurons,
+          result: aggregated_result,
+        });
+      }
+      current_layer_result = Some(aggregated_result);
     }
-    Ok(current_layer_result)
+    Ok(current_layer_result.unwrap_or(INITIAL_VOTING_POWER))
   }
 }
@@ -0,0 +1,24 @@
+use crate::{
+  external_data_provider_contract,
+  types::{VotingSystemError, CATEGORY_EXPERTISE_BONUS},
+};
+use soroban_sdk::{Env, String};
+
+// gives a bonus to the voters who are experts in the category of the submission
+pub fn oracle_function(
+  _env: Env,
+  voter_id: String,
+  _submission_id: String,
+  submission_category: Option<String>,
+  external_data_provider_client: &external_data_provider_contract::Client,
+) -> Result<(u32, u32), VotingSystemError> {
+  let Some(submission_category) = submission_category else {
+    return Ok((0, 0));
+  };
+  let voter_expertise = external_data_provider_client.get_user_expertise(&voter_id);
+  if !voter_expertise.contains(&submission_category) {
+    return Ok((0, 0));
+  }
+
+  Ok(CATEGORY_EXPERTISE_BONUS)
+}
@@ -0,0 +1,44 @@
+use crate::types::{
+  PreciseDecimal, PreciseDecimalNumber, SignedDecimal, SignedDecimalNumber, VotingSystemError,
+  PRECISE_DECIMALS,
+};
+use soroban_sdk::{contractclient, Address, Env, String};
+
+// the interface every external neuron contract has to implement, only its client is used,
+// the votes are signed raw decimal numbers so a neuron can return a penalty
+#[allow(dead_code)]
+#[contractclient(name = "NeuronClient")]
+pub trait NeuronInterface {
+  fn oracle_function(
+    env: Env,
+    voter_id: String,
+    submission_id: String,
+    previous_layer_vote: SignedDecimalNumber,
+  ) -> SignedDecimalNumber;
+}
+
+// the interface keeps the 3 decimal places it was published with, the previous layer result is
+// rounded towards zero to them and the vote gets the decimal places of the voting power
+pub fn oracle_function(
+  env: Env,
+  neuron_address: Address,
+  voter_id: String,
+  submission_id: String,
+  previous_layer_vote: PreciseDecimalNumber,
+) -> Result<PreciseDecimalNumber, VotingSystemError> {
+  let previous_layer_vote: SignedDecimal =
+    PreciseDecimal::from_raw(previous_layer_vote).rescale()?;
+  // a failing neuron contract fails the calculation with an error instead of trapping
+  match NeuronClient::new(&env, &neuron_address).try_oracle_function(
+    &voter_id,
+    &submission_id,
+    &previous_layer_vote.as_raw(),
+  ) {
+    Ok(Ok(neuron_vote)) => Ok(
+      SignedDecimal::from_raw(neuron_vote)
+        .rescale::<PRECISE_DECIMALS>()?
+        .as_raw(),
+    ),
+    _ => Err(VotingSystemError::ExternalNeuronFailed),
+  }
+}
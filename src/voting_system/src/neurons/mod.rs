@@ -1,4 +1,6 @@
 pub mod assigned_reputation_neuron;
+pub mod category_expertise_neuron;
 pub mod dummy_neuron;
+pub mod external_neuron;
 pub mod prior_voting_history_neuron;
 pub mod trust_graph_neuron;
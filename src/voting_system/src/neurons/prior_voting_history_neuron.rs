@@ -5,6 +5,7 @@ use soroban_sdk::{Env, String};
 pub fn oracle_function(
   _env: Env,
   voter_id: String,
+  _submission_id: String,
   external_data_provider_client: &external_data_provider_contract::Client,
 ) -> Result<(u32, u32), VotingSystemError> {
   let voter_active_rounds = external_data_provider_client.get_user_prior_voting_history(&voter_id);
@@ -1,12 +1,15 @@
-use crate::{external_data_provider_contract, types::VotingSystemError};
+use crate::{
+  external_data_provider_contract,
+  types::{PreciseDecimalNumber, VotingSystemError},
+};
 use soroban_sdk::{Env, String};
 
+// the ranks are stored with the 9 decimal places of the voting power, so they are used as they are
 pub fn oracle_function(
   _env: Env,
   voter_id: String,
+  _submission_id: String,
   external_data_provider_client: &external_data_provider_contract::Client,
-) -> Result<(u32, u32), VotingSystemError> {
-  let rank = external_data_provider_client.get_page_rank_result_for_user(&voter_id);
-
-  Ok(rank)
+) -> Result<PreciseDecimalNumber, VotingSystemError> {
+  Ok(external_data_provider_client.get_page_rank_result_for_user(&voter_id))
 }
@@ -0,0 +1,104 @@
+use crate::types::{RoundState, VotingSystemError};
+use soroban_sdk::contracttype;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Round {
+  pub id: u32,
+  pub state: RoundState,
+  // ledger sequences, votes are accepted from start_ledger until end_ledger (exclusive)
+  // end_ledger is 0 when the round has no voting window and is opened and closed by the admin
+  pub start_ledger: u32,
+  pub end_ledger: u32,
+}
+
+impl Round {
+  pub fn new(id: u32) -> Round {
+    Round {
+      id,
+      state: RoundState::Draft,
+      start_ledger: 0,
+      end_ledger: 0,
+    }
+  }
+
+  pub fn set_voting_window(
+    &mut self,
+    start_ledger: u32,
+    end_ledger: u32,
+  ) -> Result<(), VotingSystemError> {
+    self.require_accepting_submissions()?;
+    if start_ledger >= end_ledger {
+      return Err(VotingSystemError::InvalidVotingWindow);
+    }
+    self.start_ledger = start_ledger;
+    self.end_ledger = end_ledger;
+    Ok(())
+  }
+
+  pub fn has_voting_window(&self) -> bool {
+    self.end_ledger != 0
+  }
+
+  // opens and closes the round automatically according to its voting window
+  pub fn sync_with_ledger(&mut self, ledger: u32) {
+    if !self.has_voting_window() {
+      return;
+    }
+    if ledger >= self.end_ledger {
+      if self.state == RoundState::Draft || self.state == RoundState::Open {
+        self.state = RoundState::Closed;
+      }
+    } else if ledger >= self.start_ledger && self.state == RoundState::Draft {
+      self.state = RoundState::Open;
+    }
+  }
+
+  // Draft -> Open -> Closed -> Tallied -> Finalized
+  // a tallied round can be tallied again (e.g. after changing the neural governance) until it is finalized
+  pub fn move_to(&mut self, state: RoundState) -> Result<(), VotingSystemError> {
+    let allowed = matches!(
+      (self.state, state),
+      (RoundState::Draft, RoundState::Open)
+        | (RoundState::Open, RoundState::Closed)
+        | (RoundState::Closed, RoundState::Tallied)
+        | (RoundState::Tallied, RoundState::Tallied)
+        | (RoundState::Tallied, RoundState::Finalized)
+    );
+    if !allowed {
+      return Err(VotingSystemError::InvalidRoundStateTransition);
+    }
+    self.state = state;
+    Ok(())
+  }
+
+  pub fn require_open(&self, ledger: u32) -> Result<(), VotingSystemError> {
+    if self.has_voting_window() {
+      if ledger < self.start_ledger {
+        return Err(VotingSystemError::VotingWindowNotStarted);
+      }
+      if ledger >= self.end_ledger {
+        return Err(VotingSystemError::VotingWindowEnded);
+      }
+    }
+    if self.state != RoundState::Open {
+      return Err(VotingSystemError::RoundNotOpen);
+    }
+    Ok(())
+  }
+
+  // submissions can be added before the round is opened as well
+  pub fn require_accepting_submissions(&self) -> Result<(), VotingSystemError> {
+    if self.state != RoundState::Draft && self.state != RoundState::Open {
+      return Err(VotingSystemError::RoundNotOpen);
+    }
+    Ok(())
+  }
+
+  pub fn require_closed(&self) -> Result<(), VotingSystemError> {
+    if self.state == RoundState::Draft || self.state == RoundState::Open {
+      return Err(VotingSystemError::RoundNotClosed);
+    }
+    Ok(())
+  }
+}
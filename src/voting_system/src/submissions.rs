@@ -0,0 +1,105 @@
+use crate::{
+  types::{SubmissionStatus, VotingSystemError, BALLOT_TTL_EXTEND_TO, BALLOT_TTL_THRESHOLD},
+  DataKey,
+};
+use soroban_sdk::{contracttype, Address, Env, Map, String, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Submission {
+  pub id: String,
+  pub title: String,
+  // track of the round, e.g. "Infrastructure"
+  pub category: String,
+  pub requested_budget: u64,
+  pub proposer: Address,
+  pub status: SubmissionStatus,
+}
+
+impl Submission {
+  // votes can only be cast for submissions which are registered and were not withdrawn
+  pub fn require_active(&self) -> Result<(), VotingSystemError> {
+    if self.status == SubmissionStatus::Withdrawn {
+      return Err(VotingSystemError::SubmissionWithdrawn);
+    }
+    Ok(())
+  }
+}
+
+// submissions live as long as the ballots of their round, they are kept in the same kind of entries
+
+fn extend_ttl(env: &Env, key: &DataKey) {
+  env
+    .storage()
+    .persistent()
+    .extend_ttl(key, BALLOT_TTL_THRESHOLD, BALLOT_TTL_EXTEND_TO);
+}
+
+fn get_index(env: &Env, round_id: u32) -> Map<String, ()> {
+  env
+    .storage()
+    .persistent()
+    .get(&DataKey::Submissions(round_id))
+    .unwrap_or(Map::new(env))
+}
+
+pub fn has(env: &Env, round_id: u32, submission_id: String) -> bool {
+  env
+    .storage()
+    .persistent()
+    .has(&DataKey::Submission(round_id, submission_id))
+}
+
+pub fn get(
+  env: &Env,
+  round_id: u32,
+  submission_id: String,
+) -> Result<Submission, VotingSystemError> {
+  env
+    .storage()
+    .persistent()
+    .get(&DataKey::Submission(round_id, submission_id))
+    .ok_or(VotingSystemError::SubmissionNotFound)
+}
+
+pub fn set(env: &Env, round_id: u32, submission: &Submission) {
+  let key = DataKey::Submission(round_id, submission.id.clone());
+  env.storage().persistent().set(&key, submission);
+  extend_ttl(env, &key);
+
+  let index_key = DataKey::Submissions(round_id);
+  let mut index = get_index(env, round_id);
+  if !index.contains_key(submission.id.clone()) {
+    index.set(submission.id.clone(), ());
+    env.storage().persistent().set(&index_key, &index);
+  }
+  extend_ttl(env, &index_key);
+}
+
+// ids of all the registered submissions of the round, including the withdrawn ones
+pub fn get_ids(env: &Env, round_id: u32) -> Vec<String> {
+  get_index(env, round_id).keys()
+}
+
+pub fn get_active_ids(env: &Env, round_id: u32) -> Vec<String> {
+  let mut ids: Vec<String> = Vec::new(env);
+  for submission_id in get_ids(env, round_id) {
+    if let Ok(submission) = get(env, round_id, submission_id.clone()) {
+      if submission.status == SubmissionStatus::Active {
+        ids.push_back(submission_id);
+      }
+    }
+  }
+  ids
+}
+
+pub fn extend_round_ttl(env: &Env, round_id: u32) {
+  let index_key = DataKey::Submissions(round_id);
+  if !env.storage().persistent().has(&index_key) {
+    return;
+  }
+  extend_ttl(env, &index_key);
+  for submission_id in get_ids(env, round_id) {
+    extend_ttl(env, &DataKey::Submission(round_id, submission_id));
+  }
+}
@@ -0,0 +1,215 @@
+use crate::{
+  ballots,
+  layer::Layer,
+  neural_governance::NeuralGovernance,
+  types::{
+    DecimalNumber, NormalizedVote, PreciseDecimal, PreciseDecimalNumber, SignedDecimal,
+    SignedDecimalNumber, Vote, VotingSystemError,
+  },
+  VotingSystem,
+};
+use soroban_sdk::{contracttype, Env, Map, String, Vec};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoterContribution {
+  pub vote: NormalizedVote,
+  // the voter delegated their vote and this is the consensus of their delegatees
+  pub delegated: bool,
+  // after the voting power bounds are applied
+  pub voting_power: DecimalNumber,
+}
+
+// returned by tally for every submission
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubmissionResult {
+  pub yes_power: DecimalNumber,
+  pub no_power: DecimalNumber,
+  // yes_power - no_power
+  pub net: SignedDecimalNumber,
+  pub yes_count: u32,
+  pub no_count: u32,
+  pub abstain_count: u32,
+  pub delegated_count: u32,
+  // yes_power / (yes_power + no_power), 0 if there is no voting power at all
+  pub approval_ratio: DecimalNumber,
+}
+
+impl SubmissionResult {
+  pub fn new(
+    yes_power: SignedDecimal,
+    no_power: SignedDecimal,
+    (yes_count, no_count, abstain_count, delegated_count): (u32, u32, u32, u32),
+  ) -> Result<SubmissionResult, VotingSystemError> {
+    let total_power = SignedDecimal::add(yes_power, no_power)?;
+    let mut approval_ratio: DecimalNumber = (0, 0);
+    if total_power.as_raw() > 0 {
+      approval_ratio = SignedDecimal::div(yes_power, total_power)?.as_decimal()?;
+    }
+    Ok(SubmissionResult {
+      yes_power: yes_power.as_decimal()?,
+      no_power: no_power.as_decimal()?,
+      net: SignedDecimal::sub(yes_power, no_power)?.as_raw(),
+      yes_count,
+      no_count,
+      abstain_count,
+      delegated_count,
+      approval_ratio,
+    })
+  }
+}
+
+// returned by tally_detailed for every submission
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubmissionTally {
+  pub yes_power: DecimalNumber,
+  pub no_power: DecimalNumber,
+  // including the voters whose delegatees' consensus was to abstain
+  pub abstain_count: u32,
+  // voters who delegated their vote, whatever the consensus of their delegatees was
+  pub delegated_count: u32,
+  // Map<voter_id, VoterContribution> - voters who voted Yes or No, directly or through delegation
+  pub contributions: Map<String, VoterContribution>,
+}
+
+impl SubmissionTally {
+  pub fn result(&self) -> Result<SubmissionResult, VotingSystemError> {
+    let mut yes_count = 0;
+    for contribution in self.contributions.values() {
+      if contribution.vote == NormalizedVote::Yes {
+        yes_count += 1;
+      }
+    }
+    SubmissionResult::new(
+      SignedDecimal::from_decimal(self.yes_power)?,
+      SignedDecimal::from_decimal(self.no_power)?,
+      (
+        yes_count,
+        self.contributions.len() - yes_count,
+        self.abstain_count,
+        self.delegated_count,
+      ),
+    )
+  }
+}
+
+// the median of the voters' unbounded voting powers, a submission can have many voters so they are
+// not sorted with the insertion sort of the layers
+pub fn median_voting_power(
+  voting_powers: Vec<PreciseDecimalNumber>,
+) -> Result<Option<PreciseDecimalNumber>, VotingSystemError> {
+  Layer::median_of_sorted(&sort_voting_powers(voting_powers))
+}
+
+// heapsort in ascending order, O(n log n) whatever the order of the voting powers is
+pub fn sort_voting_powers(
+  mut voting_powers: Vec<PreciseDecimalNumber>,
+) -> Vec<PreciseDecimalNumber> {
+  let len = voting_powers.len();
+  for root in (0..len / 2).rev() {
+    sift_down(&mut voting_powers, root, len);
+  }
+  for end in (1..len).rev() {
+    swap(&mut voting_powers, 0, end);
+    sift_down(&mut voting_powers, 0, end);
+  }
+  voting_powers
+}
+
+// moves the value at root down until it is not smaller than its children, only the first end values
+// are the heap
+fn sift_down(heap: &mut Vec<PreciseDecimalNumber>, mut root: u32, end: u32) {
+  loop {
+    let mut child = 2 * root + 1;
+    if child >= end {
+      return;
+    }
+    if child + 1 < end && heap.get(child).unwrap() < heap.get(child + 1).unwrap() {
+      child += 1;
+    }
+    if heap.get(root).unwrap() >= heap.get(child).unwrap() {
+      return;
+    }
+    swap(heap, root, child);
+    root = child;
+  }
+}
+
+fn swap(values: &mut Vec<PreciseDecimalNumber>, i: u32, j: u32) {
+  let value = values.get(i).unwrap();
+  values.set(i, values.get(j).unwrap());
+  values.set(j, value);
+}
+
+// also returns the median voting power of the submission's voters if the cap relative to it is set
+pub fn tally_submission(
+  env: &Env,
+  neural_governance: &NeuralGovernance,
+  round_id: u32,
+  submission_id: String,
+) -> Result<(SubmissionTally, Option<PreciseDecimalNumber>), VotingSystemError> {
+  let mut normalized_votes: Map<String, NormalizedVote> = Map::new(env);
+  let mut delegated_voters: Map<String, ()> = Map::new(env);
+  let mut abstain_count = 0;
+  for (voter_id, mut vote) in
+    ballots::get_votes_for_submission(env, round_id, submission_id.clone())
+  {
+    if vote == Vote::Delegate {
+      delegated_voters.set(voter_id.clone(), ());
+      vote = VotingSystem::calculate_quorum_consensus(
+        env.clone(),
+        voter_id.clone(),
+        submission_id.clone(),
+      )?;
+    }
+    match vote {
+      Vote::Abstain => abstain_count += 1,
+      Vote::Yes => normalized_votes.set(voter_id, NormalizedVote::Yes),
+      Vote::No => normalized_votes.set(voter_id, NormalizedVote::No),
+      _ => return Err(VotingSystemError::UnexpectedValue),
+    }
+  }
+
+  let unbounded_voting_powers = VotingSystem::unbounded_voting_powers(
+    env,
+    neural_governance,
+    submission_id,
+    &normalized_votes,
+  )?;
+  let median_voting_power =
+    VotingSystem::median_voting_power(neural_governance, &unbounded_voting_powers)?;
+
+  let mut yes_power: PreciseDecimal = Default::default();
+  let mut no_power: PreciseDecimal = Default::default();
+  let mut contributions: Map<String, VoterContribution> = Map::new(env);
+  for (voter_id, normalized_vote) in normalized_votes {
+    let voting_power = neural_governance.voting_power_bounds.apply(
+      unbounded_voting_powers.get(voter_id.clone()).unwrap(),
+      median_voting_power,
+    )?;
+    let voting_power = PreciseDecimal::from_raw(voting_power);
+    match normalized_vote {
+      NormalizedVote::Yes => yes_power = PreciseDecimal::add(yes_power, voting_power)?,
+      NormalizedVote::No => no_power = PreciseDecimal::add(no_power, voting_power)?,
+    };
+    contributions.set(
+      voter_id.clone(),
+      VoterContribution {
+        vote: normalized_vote,
+        delegated: delegated_voters.contains_key(voter_id),
+        voting_power: voting_power.as_decimal()?,
+      },
+    );
+  }
+
+  let submission_tally = SubmissionTally {
+    yes_power: yes_power.as_decimal()?,
+    no_power: no_power.as_decimal()?,
+    abstain_count,
+    delegated_count: delegated_voters.len(),
+    contributions,
+  };
+  Ok((submission_tally, median_voting_power))
+}
@@ -0,0 +1,37 @@
+use soroban_sdk::{vec, Env, Vec};
+
+use crate::{
+  tally::{median_voting_power, sort_voting_powers},
+  types::PreciseDecimalNumber,
+};
+
+#[test]
+pub fn test_sort_voting_powers() {
+  let env = Env::default();
+
+  assert!(sort_voting_powers(Vec::new(&env)) == Vec::new(&env));
+  assert!(sort_voting_powers(vec![&env, 300]) == vec![&env, 300]);
+  assert!(
+    sort_voting_powers(vec![&env, 300, -100, 2000, 0, 300, 150])
+      == vec![&env, -100, 0, 150, 300, 300, 2000]
+  );
+
+  // descending input, the worst case of the insertion sort
+  let mut voting_powers: Vec<PreciseDecimalNumber> = Vec::new(&env);
+  let mut expected: Vec<PreciseDecimalNumber> = Vec::new(&env);
+  for i in 0..500 {
+    voting_powers.push_back(500 - i);
+    expected.push_back(i + 1);
+  }
+  assert!(sort_voting_powers(voting_powers) == expected);
+}
+
+#[test]
+pub fn test_median_voting_power() {
+  let env = Env::default();
+
+  assert!(median_voting_power(Vec::new(&env)) == Ok(None));
+  assert!(median_voting_power(vec![&env, 5000, 100, 1000]) == Ok(Some(1000)));
+  // the average of the two middle ones
+  assert!(median_voting_power(vec![&env, 3000, -500, 250, 1500]) == Ok(Some(875)));
+}
@@ -1,7 +1,25 @@
-use soroban_sdk::{contracterror, contracttype, Env, String};
+use soroban_sdk::{contracterror, contracttype, Address, Env, String};
+use voting_fixed_point::{FixedPoint, FixedPointError};
 
 pub type DecimalNumber = (u32, u32);
 
+// raw decimal number which can be negative, -1500 is -1.5, use SignedDecimal for the arithmetic
+// the configuration (activation values) and the external neurons use it
+pub type SignedDecimalNumber = i128;
+
+// the same decimal places as DecimalNumber, so the raw weights and bounds can be mixed with it
+pub const SIGNED_DECIMAL_NUMBER_DECIMALS: u32 = 3;
+pub type SignedDecimal = FixedPoint<SIGNED_DECIMAL_NUMBER_DECIMALS>;
+
+// raw voting power which can be negative, -1_500_000_000 is -1.5, use PreciseDecimal for the
+// arithmetic, the results of the neurons and layers keep all the decimal places until the voting
+// power is returned as a DecimalNumber
+pub type PreciseDecimalNumber = i128;
+
+// the same decimal places as the page ranks of the external data provider
+pub const PRECISE_DECIMALS: u32 = 9;
+pub type PreciseDecimal = FixedPoint<PRECISE_DECIMALS>;
+
 pub static DEFAULT_WEIGHT: DecimalNumber = (1, 0);
 
 pub const QUORUM_SIZE: u32 = 5;
@@ -10,8 +28,19 @@ pub const QUORUM_PARTICIPATION_TRESHOLD: u32 = 3;
 pub const MIN_DELEGATEES: u32 = 5;
 pub const MAX_DELEGATEES: u32 = 10;
 
-pub const INITIAL_VOTING_POWER: (u32, u32) = (0, 0);
-pub const ABSTAIN_VOTING_POWER: (u32, u32) = (0, 0);
+// in ledgers (~5 seconds each), ballots are extended to ~120 days whenever they get below ~30 days
+pub const BALLOT_TTL_THRESHOLD: u32 = 30 * 17280;
+pub const BALLOT_TTL_EXTEND_TO: u32 = 120 * 17280;
+
+// the contract instance (configuration, admin, current round id) is extended the same way by every
+// mutating call, so a deployment which is in use never gets archived
+pub const INSTANCE_TTL_THRESHOLD: u32 = 30 * 17280;
+pub const INSTANCE_TTL_EXTEND_TO: u32 = 120 * 17280;
+
+// returned by the category expertise neuron when the voter is an expert in the submission's category
+pub const CATEGORY_EXPERTISE_BONUS: (u32, u32) = (0, 500);
+
+pub const INITIAL_VOTING_POWER: PreciseDecimalNumber = 0;
 
 #[contracttype]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -30,20 +59,23 @@ pub enum NormalizedVote {
   No,
 }
 
-pub fn vote_from_str(env: &Env, str: String) -> Vote {
+pub fn vote_from_str(env: &Env, str: String) -> Result<Vote, VotingSystemError> {
   if str == String::from_slice(&env, "Yes") {
-    return Vote::Yes;
+    return Ok(Vote::Yes);
   }
   if str == String::from_slice(&env, "No") {
-    return Vote::No;
+    return Ok(Vote::No);
+  }
+  if str == String::from_slice(&env, "Abstain") {
+    return Ok(Vote::Abstain);
   }
   if str == String::from_slice(&env, "Delegate") {
-    return Vote::Delegate;
+    return Ok(Vote::Delegate);
   }
   if str == String::from_slice(&env, "Remove") {
-    return Vote::Remove;
+    return Ok(Vote::Remove);
   }
-  return Vote::Abstain;
+  Err(VotingSystemError::UnknownVote)
 }
 
 pub fn normalized_vote_from_str(
@@ -59,31 +91,115 @@ pub fn normalized_vote_from_str(
   Err(VotingSystemError::UnknownVote)
 }
 
+pub fn normalized_vote_to_str(env: &Env, normalized_vote: NormalizedVote) -> String {
+  match normalized_vote {
+    NormalizedVote::Yes => String::from_slice(&env, "Yes"),
+    NormalizedVote::No => String::from_slice(&env, "No"),
+  }
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RoundState {
+  Draft,
+  Open,
+  Closed,
+  Tallied,
+  Finalized,
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SubmissionStatus {
+  Active,
+  Withdrawn,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum LayerAggregator {
   Unknown,
   Sum,
   Product,
+  Min,
+  Max,
+  Average,
+  Median,
+  // sum of the weighted neuron votes divided by the sum of the neuron weights
+  WeightedAverage,
 }
 
-pub fn layer_aggregator_from_str(env: &Env, str: String) -> LayerAggregator {
+// how the result of the previous layer is combined with the votes of the layer's neurons
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LayerInputMode {
+  // neuron vote + previous layer result
+  Additive,
+  // neuron vote * previous layer result
+  Multiplicative,
+  // the neuron vote is used as it is and the neurons don't get the previous layer result
+  Ignored,
+  // the neuron vote is used as it is, the previous layer result is only an input of the neurons
+  Explicit,
+}
+
+pub fn layer_input_mode_from_str(
+  env: &Env,
+  str: String,
+) -> Result<LayerInputMode, VotingSystemError> {
+  if str == String::from_slice(&env, "Additive") {
+    return Ok(LayerInputMode::Additive);
+  }
+  if str == String::from_slice(&env, "Multiplicative") {
+    return Ok(LayerInputMode::Multiplicative);
+  }
+  if str == String::from_slice(&env, "Ignored") {
+    return Ok(LayerInputMode::Ignored);
+  }
+  if str == String::from_slice(&env, "Explicit") {
+    return Ok(LayerInputMode::Explicit);
+  }
+  Err(VotingSystemError::UnknownLayerInputMode)
+}
+
+pub fn layer_aggregator_from_str(
+  env: &Env,
+  str: String,
+) -> Result<LayerAggregator, VotingSystemError> {
   if str == String::from_slice(&env, "Sum") {
-    return LayerAggregator::Sum;
+    return Ok(LayerAggregator::Sum);
   }
   if str == String::from_slice(&env, "Product") {
-    return LayerAggregator::Product;
+    return Ok(LayerAggregator::Product);
   }
-  LayerAggregator::Unknown
+  if str == String::from_slice(&env, "Min") {
+    return Ok(LayerAggregator::Min);
+  }
+  if str == String::from_slice(&env, "Max") {
+    return Ok(LayerAggregator::Max);
+  }
+  if str == String::from_slice(&env, "Average") {
+    return Ok(LayerAggregator::Average);
+  }
+  if str == String::from_slice(&env, "Median") {
+    return Ok(LayerAggregator::Median);
+  }
+  if str == String::from_slice(&env, "WeightedAverage") {
+    return Ok(LayerAggregator::WeightedAverage);
+  }
+  Err(VotingSystemError::UnknownLayerAggregator)
 }
 
 #[contracttype]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum NeuronType {
   Dummy,
   AssignedReputation,
   PriorVotingHistory,
   TrustGraph,
+  CategoryExpertise,
+  // a neuron deployed as a separate contract, see neurons/external_neuron.rs
+  External(Address),
 }
 
 pub fn neuron_type_from_str(env: &Env, str: String) -> Result<NeuronType, VotingSystemError> {
@@ -99,9 +215,13 @@ pub fn neuron_type_from_str(env: &Env, str: String) -> Result<NeuronType, Voting
   if str == String::from_slice(&env, "TrustGraph") {
     return Ok(NeuronType::TrustGraph);
   }
+  if str == String::from_slice(&env, "CategoryExpertise") {
+    return Ok(NeuronType::CategoryExpertise);
+  }
   Err(VotingSystemError::UnknownNeuronType)
 }
 
+// the contract spec allows at most 50 error cases
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 pub enum VotingSystemError {
@@ -125,4 +245,43 @@ pub enum VotingSystemError {
   UnknownNeuronType = 17,
   UnknownVote = 18,
   UnknownVoter = 19,
+  VoterNotRegistered = 20,
+  VoterAlreadyRegistered = 21,
+  AlreadyInitialized = 22,
+  AdminNotSet = 23,
+  NoPendingAdmin = 24,
+  RoundNotFound = 25,
+  RoundNotOpen = 26,
+  RoundNotClosed = 27,
+  RoundNotFinalized = 28,
+  InvalidRoundStateTransition = 29,
+  RoundNotTallied = 30,
+  VotingWindowNotStarted = 31,
+  VotingWindowEnded = 32,
+  InvalidVotingWindow = 33,
+  UnknownLayerAggregator = 34,
+  SubmissionNotFound = 35,
+  SubmissionWithdrawn = 36,
+  ExternalNeuronFailed = 37,
+  ReducingvotesForMinAggregatorFailed = 38,
+  ReducingvotesForMaxAggregatorFailed = 39,
+  ReducingvotesForAverageAggregatorFailed = 40,
+  ReducingvotesForMedianAggregatorFailed = 41,
+  ReducingvotesForWeightedAverageAggregatorFailed = 42,
+  NeuronWeightsSumToZero = 43,
+  NoSuchNeuron = 44,
+  InvalidActivation = 45,
+  UnknownLayerInputMode = 46,
+  InvalidVotingPowerBounds = 47,
+  ArithmeticOverflow = 48,
+  DivisionByZero = 49,
+}
+
+impl From<FixedPointError> for VotingSystemError {
+  fn from(error: FixedPointError) -> Self {
+    match error {
+      FixedPointError::Overflow => VotingSystemError::ArithmeticOverflow,
+      FixedPointError::DivisionByZero => VotingSystemError::DivisionByZero,
+    }
+  }
 }
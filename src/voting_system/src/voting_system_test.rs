@@ -1,20 +1,91 @@
 use crate::{
+  activation::Activation,
   external_data_provider_contract,
-  types::{LayerAggregator, NeuronType, Vote, DEFAULT_WEIGHT},
+  layer::{NeuronConfig, NeuronExplanation},
+  migration::{
+    ActivationV2, LayerV2, LegacyLayer, LegacyNeuralGovernance, NeuralGovernanceV1,
+    NeuralGovernanceV2, NeuronConfigV2,
+  },
+  neural_governance::{LayerExplanation, VotingPowerBounds},
+  submissions::Submission,
+  tally::VoterContribution,
+  types::{
+    LayerAggregator, LayerInputMode, NeuronType, NormalizedVote, RoundState, SubmissionStatus,
+    Vote, VotingSystemError, BALLOT_TTL_EXTEND_TO, CATEGORY_EXPERTISE_BONUS, DEFAULT_WEIGHT,
+  },
 };
 use soroban_decimal_numbers::DecimalNumberWrapper;
-use soroban_sdk::{vec, Env, Map, String, Vec};
+use soroban_sdk::{
+  contract, contractimpl, symbol_short,
+  testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+  vec, Address, Env, IntoVal, Map, String, Val, Vec,
+};
+
+use crate::{DataKey, VotingSystem, VotingSystemClient};
+
+mod example_neuron_contract {
+  soroban_sdk::contractimport!(
+    file = "../../target/wasm32-unknown-unknown/release/voting_example_neuron.wasm"
+  );
+}
 
-use crate::{VotingSystem, VotingSystemClient};
+// an external neuron which returns the input it gets from the previous layer
+#[contract]
+struct EchoNeuron;
+
+#[contractimpl]
+impl EchoNeuron {
+  pub fn oracle_function(
+    _env: Env,
+    _voter_id: String,
+    _submission_id: String,
+    previous_layer_vote: i128,
+  ) -> i128 {
+    previous_layer_vote
+  }
+}
 
 fn initialize_voting_system(env: &Env) -> VotingSystemClient {
+  env.mock_all_auths();
+
   let voting_system_id = env.register_contract(None, VotingSystem);
   let voting_system_client = VotingSystemClient::new(&env, &voting_system_id);
-  voting_system_client.initialize();
+  voting_system_client.initialize(&Address::generate(env));
+  // every test runs within the first round
+  voting_system_client.create_round();
+  voting_system_client.open_round();
 
   voting_system_client
 }
 
+// the event published last, as returned by env.events().all()
+fn last_event(env: &Env) -> Vec<(Address, Vec<Val>, Val)> {
+  let events = env.events().all();
+  events.slice(events.len() - 1..)
+}
+
+fn register_voters(env: &Env, voting_system_client: &VotingSystemClient, voter_ids: &[&String]) {
+  for voter_id in voter_ids {
+    voting_system_client.register_voter(voter_id, &Address::generate(env));
+  }
+}
+
+fn add_submissions(
+  env: &Env,
+  voting_system_client: &VotingSystemClient,
+  submission_ids: &[&String],
+) {
+  for submission_id in submission_ids {
+    voting_system_client.add_submission(
+      submission_id,
+      submission_id,
+      &String::from_slice(env, "Infrastructure"),
+      &10_000,
+      &Address::generate(env),
+    );
+  }
+}
+
 fn initialize_external_data_provider<'a>(
   env: &Env,
   voting_system_client: &VotingSystemClient,
@@ -23,12 +94,112 @@ fn initialize_external_data_provider<'a>(
     env.register_contract_wasm(None, external_data_provider_contract::WASM);
   let external_data_provider_client =
     external_data_provider_contract::Client::new(&env, &external_data_provider_id);
-  external_data_provider_client.mock_sample_data();
+  external_data_provider_client.initialize(&Address::generate(env));
+  set_sample_data(env, &external_data_provider_client);
+  // calculate_page_rank of the voting system stores the result as the voting system contract
+  external_data_provider_client.add_writer(
+    &external_data_provider_contract::Dataset::PageRankResult,
+    &voting_system_client.address,
+  );
   voting_system_client.set_external_data_provider(&external_data_provider_id);
 
   external_data_provider_client
 }
 
+// mirrors the External Data Provider's mock_sample_data, which is not included in the release build
+fn set_sample_data(
+  env: &Env,
+  external_data_provider_client: &external_data_provider_contract::Client,
+) {
+  let owner = external_data_provider_client.get_owner();
+  let user001 = String::from_slice(env, "user001");
+  let user002 = String::from_slice(env, "user002");
+  let user003 = String::from_slice(env, "user003");
+  let user004 = String::from_slice(env, "user004");
+  let user005 = String::from_slice(env, "user005");
+
+  external_data_provider_client.set_users_rep_categories_vec(
+    &owner,
+    &vec![
+      env,
+      (user001.clone(), String::from_slice(env, "Excellent")),
+      (user002.clone(), String::from_slice(env, "VeryGood")),
+      (user003.clone(), String::from_slice(env, "Good")),
+      (user004.clone(), String::from_slice(env, "Average")),
+      (user005.clone(), String::from_slice(env, "Poor")),
+    ],
+  );
+
+  external_data_provider_client.set_user_prior_voting_history(&owner, &user001, &vec![env, 2, 3]);
+  external_data_provider_client.set_user_prior_voting_history(
+    &owner,
+    &user003,
+    &vec![env, 2, 3, 4],
+  );
+  external_data_provider_client.set_round_bonus_map(
+    &owner,
+    &Map::from_array(
+      env,
+      [(1, (0, 0)), (2, (0, 100)), (3, (0, 200)), (4, (0, 300))],
+    ),
+  );
+
+  let mut delegation_ranks: Vec<(String, u32)> = Vec::new(env);
+  for (user_id, rank) in [
+    ("user001", 1),
+    ("user002", 2),
+    ("user003", 3),
+    ("user004", 4),
+    ("user005", 5),
+    ("user006", 6),
+    ("user007", 7),
+    ("user008", 8),
+  ] {
+    delegation_ranks.push_back((String::from_slice(env, user_id), rank));
+  }
+  external_data_provider_client.set_users_delegation_ranks_vec(&owner, &delegation_ranks);
+
+  let mut trust_map: Map<String, Map<String, ()>> = Map::new(env);
+  trust_map.set(
+    user001.clone(),
+    Map::from_array(env, [(user002.clone(), ()), (user004.clone(), ())]),
+  );
+  trust_map.set(
+    user002.clone(),
+    Map::from_array(env, [(user001.clone(), ())]),
+  );
+  trust_map.set(
+    user003.clone(),
+    Map::from_array(env, [(user001.clone(), ()), (user002.clone(), ())]),
+  );
+  trust_map.set(
+    user004.clone(),
+    Map::from_array(env, [(user003.clone(), ())]),
+  );
+  external_data_provider_client.set_trust_map(&owner, &trust_map);
+
+  external_data_provider_client.set_users_expertise(
+    &owner,
+    &Map::from_array(
+      env,
+      [
+        (
+          user001.clone(),
+          vec![env, String::from_slice(env, "Infrastructure")],
+        ),
+        (
+          user002.clone(),
+          vec![
+            env,
+            String::from_slice(env, "Financial Protocols"),
+            String::from_slice(env, "Infrastructure"),
+          ],
+        ),
+      ],
+    ),
+  );
+}
+
 #[test]
 pub fn test_setting_up_neural_governance() {
   let env = Env::default();
@@ -113,6 +284,7 @@ pub fn test_setting_up_neural_governance() {
       .neurons
       .get(NeuronType::Dummy)
       .unwrap()
+      .weight
       == DecimalNumberWrapper::from(DEFAULT_WEIGHT).as_raw()
   );
   assert!(
@@ -124,6 +296,7 @@ pub fn test_setting_up_neural_governance() {
       .neurons
       .get(NeuronType::AssignedReputation)
       .unwrap()
+      .weight
       == DecimalNumberWrapper::from("4.7").as_raw()
   );
 }
@@ -138,13 +311,21 @@ pub fn test_setting_up_neural_governance_batch() {
   neurons.push_back((String::from_slice(&env, "TrustGraph"), 0));
   neurons.push_back((String::from_slice(&env, "Dummy"), 1100));
 
-  voting_system_client.setup_layer(&String::from_slice(&env, "Sum"), &neurons);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &neurons,
+  );
 
   let mut neurons: Vec<(String, u32)> = Vec::new(&env);
   neurons.push_back((String::from_slice(&env, "AssignedReputation"), 2000));
   neurons.push_back((String::from_slice(&env, "PriorVotingHistory"), 3000));
 
-  voting_system_client.setup_layer(&String::from_slice(&env, "Product"), &neurons);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Product"),
+    &String::from_slice(&env, "Additive"),
+    &neurons,
+  );
 
   let neural_governance = voting_system_client.get_neural_governance();
 
@@ -163,22 +344,50 @@ pub fn test_setting_up_neural_governance_batch() {
 
   if layer0.aggregator == LayerAggregator::Sum {
     assert!(
-      layer0.neurons.get(NeuronType::TrustGraph).unwrap()
+      layer0.neurons.get(NeuronType::TrustGraph).unwrap().weight
         == DecimalNumberWrapper::from(DEFAULT_WEIGHT).as_raw()
     );
-    assert!(layer0.neurons.get(NeuronType::Dummy).unwrap() == 1100);
+    assert!(layer0.neurons.get(NeuronType::Dummy).unwrap().weight == 1100);
 
-    assert!(layer1.neurons.get(NeuronType::AssignedReputation).unwrap() == 2000);
-    assert!(layer1.neurons.get(NeuronType::PriorVotingHistory).unwrap() == 3000);
+    assert!(
+      layer1
+        .neurons
+        .get(NeuronType::AssignedReputation)
+        .unwrap()
+        .weight
+        == 2000
+    );
+    assert!(
+      layer1
+        .neurons
+        .get(NeuronType::PriorVotingHistory)
+        .unwrap()
+        .weight
+        == 3000
+    );
   } else {
     assert!(
-      layer1.neurons.get(NeuronType::TrustGraph).unwrap()
+      layer1.neurons.get(NeuronType::TrustGraph).unwrap().weight
         == DecimalNumberWrapper::from(DEFAULT_WEIGHT).as_raw()
     );
-    assert!(layer1.neurons.get(NeuronType::Dummy).unwrap() == 1100);
+    assert!(layer1.neurons.get(NeuronType::Dummy).unwrap().weight == 1100);
 
-    assert!(layer0.neurons.get(NeuronType::AssignedReputation).unwrap() == 2000);
-    assert!(layer0.neurons.get(NeuronType::PriorVotingHistory).unwrap() == 3000);
+    assert!(
+      layer0
+        .neurons
+        .get(NeuronType::AssignedReputation)
+        .unwrap()
+        .weight
+        == 2000
+    );
+    assert!(
+      layer0
+        .neurons
+        .get(NeuronType::PriorVotingHistory)
+        .unwrap()
+        .weight
+        == 3000
+    );
   }
 }
 
@@ -195,7 +404,8 @@ pub fn test_simple_voting() {
     env.register_contract_wasm(None, external_data_provider_contract::WASM);
   let external_data_provider_client =
     external_data_provider_contract::Client::new(&env, &external_data_provider_id);
-  external_data_provider_client.mock_sample_data();
+  external_data_provider_client.initialize(&Address::generate(&env));
+  set_sample_data(&env, &external_data_provider_client);
   voting_system_client.set_external_data_provider(&external_data_provider_id);
 
   assert!(voting_system_client.add_layer() == 0);
@@ -209,55 +419,46 @@ pub fn test_simple_voting() {
 
   let voter_id = String::from_slice(&env, "user001");
   let voter_id_2 = String::from_slice(&env, "user002");
+  register_voters(&env, &voting_system_client, &[&voter_id, &voter_id_2]);
   let submission_id = String::from_slice(&env, "submission001");
   let submission_id_2 = String::from_slice(&env, "submission002");
 
   assert!(voting_system_client.get_submissions().is_empty());
-  voting_system_client.add_submission(&submission_id_2);
+  add_submissions(&env, &voting_system_client, &[&submission_id_2]);
   assert!(voting_system_client.get_submissions().len() == 1);
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  assert!(voting_system_client.get_submissions().len() == 2);
 
   assert!(voting_system_client.get_voters().is_empty());
-  let current_user_votes =
-    voting_system_client.vote(&voter_id, &submission_id, &String::from_slice(&env, "No"));
+  let current_user_votes = voting_system_client.vote(&voter_id, &submission_id, &Vote::No);
   assert!(current_user_votes.len() == 1);
   assert!(voting_system_client.get_submissions().len() == 2);
   // test overriding the vote
-  let current_user_votes =
-    voting_system_client.vote(&voter_id, &submission_id, &String::from_slice(&env, "Yes"));
+  let current_user_votes = voting_system_client.vote(&voter_id, &submission_id, &Vote::Yes);
   assert!(current_user_votes.len() == 1);
 
-  let current_user_votes = voting_system_client.vote(
-    &voter_id,
-    &submission_id_2,
-    &String::from_slice(&env, "Yes"),
-  );
+  let current_user_votes = voting_system_client.vote(&voter_id, &submission_id_2, &Vote::Yes);
   assert!(current_user_votes.len() == 2);
 
   assert!(voting_system_client.get_voters().len() == 1);
-  let current_user_votes = voting_system_client.vote(
-    &voter_id_2,
-    &submission_id_2,
-    &String::from_slice(&env, "Yes"),
-  );
+  let current_user_votes = voting_system_client.vote(&voter_id_2, &submission_id_2, &Vote::Yes);
   assert!(current_user_votes.len() == 1);
   assert!(voting_system_client.get_voters().len() == 2);
   let current_user_votes = voting_system_client.remove_vote(&voter_id_2, &submission_id_2);
   assert!(current_user_votes.len() == 0);
   assert!(voting_system_client.get_voters().len() == 1);
 
-  voting_system_client.vote(
-    &voter_id,
-    &submission_id_2,
-    &String::from_slice(&env, "Yes"),
-  );
+  voting_system_client.vote(&voter_id, &submission_id_2, &Vote::Yes);
   assert!(voting_system_client.get_votes_for_user(&voter_id).len() == 2);
 
+  voting_system_client.close_round();
   assert!(
     voting_system_client
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (2, 200)
+      .net
+      == 2200
   );
   // change neuron weight
   voting_system_client.set_neuron_weight(&1, &String::from_slice(&env, "Dummy"), &2000);
@@ -266,7 +467,8 @@ pub fn test_simple_voting() {
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (4, 400)
+      .net
+      == 4400
   );
 }
 
@@ -288,22 +490,21 @@ pub fn test_assigned_reputation_neuron() {
 
   let voter_id_1 = String::from_slice(&env, "user001"); // bonus 0,300
   let voter_id_2 = String::from_slice(&env, "user002"); // bonus 0,200
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
   let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.add_submission(&submission_id);
-  voting_system_client.vote(
-    &voter_id_1,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(&voter_id_2, &submission_id, &String::from_slice(&env, "No"));
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::No);
 
+  voting_system_client.close_round();
   assert!(
     voting_system_client
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (0, 100)
+      .net
+      == 100
   );
 
   // change neurons' weights
@@ -319,456 +520,858 @@ pub fn test_assigned_reputation_neuron() {
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (0, 200)
+      .net
+      == 200
   );
 }
 
 #[test]
-pub fn test_prior_voting_history_neuron() {
+pub fn test_layer_input_modes() {
   let env = Env::default();
   env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
+  initialize_external_data_provider(&env, &voting_system_client);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Multiplicative"),
+    &vec![&env, (String::from_slice(&env, "AssignedReputation"), 1000)],
+  );
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &Vec::new(&env),
+  );
+  let echo_neuron_id = env.register_contract(None, EchoNeuron);
+  voting_system_client.add_external_neuron(&1, &echo_neuron_id);
 
-  assert!(voting_system_client.add_layer() == 0);
+  let voter_id = String::from_slice(&env, "user001"); // bonus 0,300
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  // the first layer has no previous layer, so its input mode doesn't matter
+  // and the echo neuron gets 0.3 from it: 0.3 + 0.3
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 600));
 
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "PriorVotingHistory"));
+  voting_system_client.set_layer_input_mode(&1, &String::from_slice(&env, "Multiplicative"));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 90));
+
+  // the neurons get nothing from the previous layer, so the echo neuron votes 0
+  voting_system_client.set_layer_input_mode(&1, &String::from_slice(&env, "Ignored"));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 0));
+
+  // the previous layer result is only passed to the neurons, so the echo neuron votes 0.3
+  voting_system_client.set_layer_input_mode(&1, &String::from_slice(&env, "Explicit"));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 300));
+  assert!(
+    voting_system_client
+      .get_neural_governance()
+      .layers
+      .get(1)
+      .unwrap()
+      .input_mode
+      == LayerInputMode::Explicit
+  );
+
+  // built-in neurons don't take the previous layer result, so they can't be in an Explicit layer
+  assert!(
+    voting_system_client.try_add_neuron(&1, &String::from_slice(&env, "Dummy"))
+      == Err(Ok(VotingSystemError::UnexpectedValue))
+  );
+  assert!(
+    voting_system_client.try_set_neuron_weight(&1, &String::from_slice(&env, "Dummy"), &1000)
+      == Err(Ok(VotingSystemError::UnexpectedValue))
+  );
+  assert!(
+    voting_system_client.try_set_layer_input_mode(&0, &String::from_slice(&env, "Explicit"))
+      == Err(Ok(VotingSystemError::UnexpectedValue))
+  );
+  assert!(
+    voting_system_client.try_setup_layer(
+      &String::from_slice(&env, "Sum"),
+      &String::from_slice(&env, "Explicit"),
+      &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
+    ) == Err(Ok(VotingSystemError::UnexpectedValue))
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 300));
+
+  assert!(
+    voting_system_client.try_set_layer_input_mode(&1, &String::from_slice(&env, "additive"))
+      == Err(Ok(VotingSystemError::UnknownLayerInputMode))
+  );
+  assert!(
+    voting_system_client.try_set_layer_input_mode(&2, &String::from_slice(&env, "Additive"))
+      == Err(Ok(VotingSystemError::NoSuchLayer))
+  );
+}
+
+#[test]
+pub fn test_average_layer_aggregator() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
 
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Average"),
+    &String::from_slice(&env, "Additive"),
+    &vec![
+      &env,
+      (String::from_slice(&env, "Dummy"), 1000),
+      (String::from_slice(&env, "AssignedReputation"), 1000),
+    ],
+  );
   initialize_external_data_provider(&env, &voting_system_client);
 
-  let voter_id_1 = String::from_slice(&env, "user001"); // active rounds: [2, 3], bonusses: [0, 100], [0, 200]
-  let voter_id_2 = String::from_slice(&env, "user003"); // active rounds: [2, 3, 4], bonusses: [0, 100], [0, 200], [0, 300]
+  let voter_id = String::from_slice(&env, "user001"); // bonus 0,300
+  register_voters(&env, &voting_system_client, &[&voter_id]);
   let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::Yes);
+  voting_system_client.close_round();
 
-  voting_system_client.add_submission(&submission_id);
-  voting_system_client.vote(&voter_id_1, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  // (1.1 + 0.3) / 2
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 700
   );
 
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Min"));
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 300
+  );
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Max"));
   assert!(
     voting_system_client
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (0, 300)
+      .net
+      == 1100
   );
 }
 
 #[test]
-pub fn test_graph_bonus() {
+pub fn test_external_neuron() {
   let env = Env::default();
   env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
+  initialize_external_data_provider(&env, &voting_system_client);
 
-  assert!(voting_system_client.add_layer() == 0);
-
-  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "TrustGraph"));
-
-  let external_data_provider_client =
-    initialize_external_data_provider(&env, &voting_system_client);
+  let example_neuron_id = env.register_contract_wasm(None, example_neuron_contract::WASM);
+  let example_neuron_client = example_neuron_contract::Client::new(&env, &example_neuron_id);
+  example_neuron_client.initialize(&Address::generate(&env));
 
   let voter_id_1 = String::from_slice(&env, "user001");
   let voter_id_2 = String::from_slice(&env, "user002");
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
   let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  example_neuron_client.set_voter_vote(&voter_id_1, &2500);
 
-  voting_system_client.add_submission(&submission_id);
-  voting_system_client.vote(
-    &voter_id_1,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  assert!(voting_system_client.add_layer() == 0);
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.add_external_neuron(&0, &example_neuron_id);
+  assert!(
+    voting_system_client
+      .get_neural_governance()
+      .layers
+      .get(0)
+      .unwrap()
+      .neurons
+      .get(NeuronType::External(example_neuron_id.clone()))
+      .map(|neuron_config| neuron_config.weight)
+      == Some(1000)
   );
-  voting_system_client.vote(&voter_id_2, &submission_id, &String::from_slice(&env, "No"));
 
-  let calculated = external_data_provider_client.calculate_page_rank();
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
+  voting_system_client.close_round();
 
+  // 2.5 assigned to user001 and the default 1 of user002
   assert!(
-    calculated
-      == Map::from_array(
-        &env,
-        [
-          (String::from_slice(&env, "user001"), (0, 338)),
-          (String::from_slice(&env, "user002"), (0, 260)),
-          (String::from_slice(&env, "user003"), (0, 190)),
-          (String::from_slice(&env, "user004"), (0, 180)),
-        ]
-      )
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 3500
+  );
+
+  voting_system_client.set_external_neuron_weight(&0, &example_neuron_id, &2000);
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 7000
   );
 
+  // the neuron is combined with the built-in ones like any other neuron, Dummy adds (1, 100) per vote
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 9200
+  );
+
+  // a penalty can cancel a vote, the voting power doesn't go below the floor of 0
+  // user001: 2.5 * 2 + 1.1, user002: -3 * 2 + 1.1
+  example_neuron_client.set_voter_vote(&voter_id_2, &-3000);
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_2, &submission_id) == (0, 0));
   assert!(
     voting_system_client
       .tally()
       .get(submission_id.clone())
       .unwrap()
-      == (0, 78)
+      .net
+      == 6100
   );
+
+  // overflows are errors instead of panics
+  example_neuron_client.set_voter_vote(&voter_id_2, &i128::MAX);
+  assert!(voting_system_client.try_tally() == Err(Ok(VotingSystemError::ArithmeticOverflow)));
+
+  // a contract which does not implement the interface fails the tally with an error
+  let not_a_neuron_id = voting_system_client.get_external_data_provider();
+  voting_system_client.remove_external_neuron(&0, &example_neuron_id);
+  voting_system_client.add_external_neuron(&0, &not_a_neuron_id);
+  assert!(voting_system_client.try_tally() == Err(Ok(VotingSystemError::ExternalNeuronFailed)));
 }
 
 #[test]
-pub fn test_graph_bonus_2() {
+pub fn test_category_expertise_neuron() {
   let env = Env::default();
   env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "CategoryExpertise"), 1000)],
+  );
+  initialize_external_data_provider(&env, &voting_system_client);
 
-  assert!(voting_system_client.add_layer() == 0);
-
-  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  let voter_id_1 = String::from_slice(&env, "user001"); // Infrastructure
+  let voter_id_2 = String::from_slice(&env, "user002"); // Financial Protocols, Infrastructure
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
+  let infrastructure_submission_id = String::from_slice(&env, "submission001");
+  let defi_submission_id = String::from_slice(&env, "submission002");
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&infrastructure_submission_id],
+  );
+  voting_system_client.add_submission(
+    &defi_submission_id,
+    &String::from_slice(&env, "Lending protocol"),
+    &String::from_slice(&env, "Financial Protocols"),
+    &50_000,
+    &Address::generate(&env),
+  );
 
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "TrustGraph"));
+  // the same voter has a different voting power depending on the submission
+  assert!(
+    voting_system_client.voting_power_for_voter(&voter_id_1, &infrastructure_submission_id)
+      == CATEGORY_EXPERTISE_BONUS
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &defi_submission_id) == (0, 0));
+  // submissions which are not registered have no category
+  assert!(
+    voting_system_client
+      .voting_power_for_voter(&voter_id_2, &String::from_slice(&env, "submission003"))
+      == (0, 0)
+  );
 
-  let external_data_provider_client =
-    initialize_external_data_provider(&env, &voting_system_client);
+  for submission_id in [&infrastructure_submission_id, &defi_submission_id] {
+    voting_system_client.vote(&voter_id_1, submission_id, &Vote::Yes);
+    voting_system_client.vote(&voter_id_2, submission_id, &Vote::Yes);
+  }
+  voting_system_client.close_round();
 
-  let voter_id_1 = String::from_slice(&env, "user001");
-  let voter_id_2 = String::from_slice(&env, "user002");
-  let voter_id_3 = String::from_slice(&env, "user003");
-  let voter_id_4 = String::from_slice(&env, "user004");
-  let voter_id_5 = String::from_slice(&env, "user005");
+  let results = voting_system_client.tally();
+  assert!(results.get(infrastructure_submission_id).unwrap().net == 1000);
+  assert!(results.get(defi_submission_id).unwrap().net == 500);
+}
 
-  let mut new_trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+#[test]
+pub fn test_neuron_activation() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
 
-  new_trust_map.set(
-    voter_id_1.clone(),
-    Map::from_array(
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![
       &env,
-      [
-        (voter_id_2.clone(), ()),
-        (voter_id_3.clone(), ()),
-        (voter_id_4.clone(), ()),
-        (voter_id_5.clone(), ()),
-      ],
-    ),
+      (String::from_slice(&env, "Dummy"), 2000),
+      (String::from_slice(&env, "AssignedReputation"), 1000),
+    ],
   );
+  initialize_external_data_provider(&env, &voting_system_client);
 
-  new_trust_map.set(
-    voter_id_2.clone(),
-    Map::from_array(
-      &env,
-      [
-        (voter_id_3.clone(), ()),
-        (voter_id_4.clone(), ()),
-        (voter_id_5.clone(), ()),
-      ],
-    ),
-  );
+  let voter_id = String::from_slice(&env, "user001"); // bonus 0,300
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
 
-  new_trust_map.set(
-    voter_id_3.clone(),
-    Map::from_array(&env, [(voter_id_4.clone(), ()), (voter_id_5.clone(), ())]),
+  // 1.1 * 2 + 0.3
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 500));
+
+  // the activation is applied before the weight and keeps it
+  let dummy = String::from_slice(&env, "Dummy");
+  voting_system_client.set_neuron_activation(&0, &dummy, &Activation::Clamp(0, 1000));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 300));
+  let dummy_config = voting_system_client
+    .get_neural_governance()
+    .layers
+    .get(0)
+    .unwrap()
+    .neurons
+    .get(NeuronType::Dummy)
+    .unwrap();
+  assert!(
+    dummy_config
+      == NeuronConfig {
+        weight: 2000,
+        activation: Activation::Clamp(0, 1000),
+      }
   );
 
-  new_trust_map.set(
-    voter_id_4.clone(),
-    Map::from_array(&env, [(voter_id_5.clone(), ())]),
-  );
+  // and changing the weight keeps the activation
+  voting_system_client.set_neuron_weight(&0, &dummy, &1000);
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (1, 300));
 
-  external_data_provider_client.set_trust_map(&new_trust_map);
+  voting_system_client.set_neuron_activation(&0, &dummy, &Activation::Step(1000, 0, 500));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (0, 800));
 
-  let submission_id = String::from_slice(&env, "submission001");
+  voting_system_client.set_neuron_activation(&0, &dummy, &Activation::Linear);
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (1, 400));
 
-  voting_system_client.add_submission(&submission_id);
-  voting_system_client.vote(&voter_id_1, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  assert!(
+    voting_system_client.try_set_neuron_activation(&0, &dummy, &Activation::Clamp(1000, 0))
+      == Err(Ok(VotingSystemError::InvalidActivation))
   );
-
-  let calculated = external_data_provider_client.calculate_page_rank();
-
   assert!(
-    calculated
-      == Map::from_array(
-        &env,
-        [
-          (String::from_slice(&env, "user001"), (0, 30)),
-          (String::from_slice(&env, "user002"), (0, 36)),
-          (String::from_slice(&env, "user003"), (0, 46)),
-          (String::from_slice(&env, "user004"), (0, 65)),
-          (String::from_slice(&env, "user005"), (0, 120)),
-        ]
-      )
+    voting_system_client.try_set_neuron_activation(
+      &0,
+      &String::from_slice(&env, "TrustGraph"),
+      &Activation::SquareRoot
+    ) == Err(Ok(VotingSystemError::NoSuchNeuron))
   );
-
   assert!(
-    voting_system_client
-      .tally()
-      .get(submission_id.clone())
-      .unwrap()
-      == (0, 6)
+    voting_system_client.try_set_neuron_activation(&1, &dummy, &Activation::SquareRoot)
+      == Err(Ok(VotingSystemError::NoSuchLayer))
   );
 }
 
 #[test]
-pub fn test_delegation_more_yes_votes() {
+pub fn test_neural_governance_migration() {
   let env = Env::default();
+  env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
-
   initialize_external_data_provider(&env, &voting_system_client);
 
-  assert!(voting_system_client.add_layer() == 0);
-  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+  // neural governance stored by a version of the contract without activation functions
+  let mut legacy_neurons: Map<NeuronType, u32> = Map::new(&env);
+  legacy_neurons.set(NeuronType::Dummy, 2000);
+  legacy_neurons.set(NeuronType::AssignedReputation, 1000);
+  let legacy = LegacyNeuralGovernance {
+    layers: vec![
+      &env,
+      LegacyLayer {
+        id: 0,
+        neurons: legacy_neurons,
+        aggregator: LayerAggregator::Sum,
+      },
+    ],
+    current_layer_id: 1,
+  };
+  env.as_contract(&voting_system_client.address, || {
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::NeuralGovernance, &legacy);
+    env
+      .storage()
+      .instance()
+      .remove(&DataKey::NeuralGovernanceVersion);
+  });
+
+  let voter_id = String::from_slice(&env, "user001"); // bonus 0,300
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
 
-  let voter_id_1 = String::from_slice(&env, "user001");
-  let voter_id_2 = String::from_slice(&env, "user002");
-  let voter_id_3 = String::from_slice(&env, "user003");
-  let voter_id_4 = String::from_slice(&env, "user004");
-  let voter_id_5 = String::from_slice(&env, "user005");
-  let voter_id_6 = String::from_slice(&env, "user006");
-  let voter_id_8 = String::from_slice(&env, "user008");
+  // the legacy layers are read as linear ones before they are migrated
+  let expected = legacy.clone().into_current(&env);
+  assert!(expected.layers.get(0).unwrap().input_mode == LayerInputMode::Additive);
+  assert!(voting_system_client.get_neural_governance() == expected);
+  assert!(
+    expected
+      .layers
+      .get(0)
+      .unwrap()
+      .neurons
+      .get(NeuronType::Dummy)
+      == Some(NeuronConfig {
+        weight: 2000,
+        activation: Activation::Linear,
+      })
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 500));
 
-  let submission_id = String::from_slice(&env, "submission001");
+  assert!(voting_system_client.migrate_neural_governance());
+  assert!(voting_system_client.get_neural_governance() == expected);
+  // the second migration has nothing to do
+  assert!(!voting_system_client.migrate_neural_governance());
 
-  voting_system_client.add_submission(&submission_id);
-  let delegatees = vec![
+  voting_system_client.set_neuron_activation(
+    &0,
+    &String::from_slice(&env, "Dummy"),
+    &Activation::Clamp(0, 1000),
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 300));
+
+  // the same layer stored with the unsigned activation values
+  let current = voting_system_client.get_neural_governance();
+  let mut neurons_v2: Map<NeuronType, NeuronConfigV2> = Map::new(&env);
+  neurons_v2.set(
+    NeuronType::Dummy,
+    NeuronConfigV2 {
+      weight: 2000,
+      activation: ActivationV2::Clamp(0, 1000),
+    },
+  );
+  neurons_v2.set(
+    NeuronType::AssignedReputation,
+    NeuronConfigV2 {
+      weight: 1000,
+      activation: ActivationV2::Linear,
+    },
+  );
+  let layers_v2 = vec![
     &env,
-    voter_id_2.clone(),
-    voter_id_3.clone(),
-    voter_id_4.clone(),
-    voter_id_5.clone(),
-    voter_id_6.clone(),
-    voter_id_8.clone(),
+    LayerV2 {
+      id: 0,
+      neurons: neurons_v2,
+      aggregator: LayerAggregator::Sum,
+      input_mode: LayerInputMode::Additive,
+    },
   ];
-  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  assert!(voting_system_client.get_delegatees().len() == 1);
-  voting_system_client.vote(&voter_id_2, &submission_id, &String::from_slice(&env, "No")); // not considered - low rank
-  voting_system_client.vote(&voter_id_3, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(&voter_id_4, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(
-    &voter_id_5,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+
+  // neural governance stored before the voting power bounds were added
+  let v1 = NeuralGovernanceV1 {
+    layers: layers_v2.clone(),
+    current_layer_id: current.current_layer_id,
+  };
+  env.as_contract(&voting_system_client.address, || {
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::NeuralGovernance, &v1);
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::NeuralGovernanceVersion, &1_u32);
+  });
+  assert!(voting_system_client.get_neural_governance() == current);
+  assert!(voting_system_client.migrate_neural_governance());
+  assert!(voting_system_client.get_neural_governance() == current);
+
+  // neural governance stored before the activation values could be negative
+  let v2 = NeuralGovernanceV2 {
+    layers: layers_v2,
+    current_layer_id: current.current_layer_id,
+    voting_power_bounds: current.voting_power_bounds.clone(),
+  };
+  env.as_contract(&voting_system_client.address, || {
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::NeuralGovernance, &v2);
+    env
+      .storage()
+      .instance()
+      .set(&DataKey::NeuralGovernanceVersion, &2_u32);
+  });
+  assert!(voting_system_client.get_neural_governance() == current);
+  assert!(voting_system_client.migrate_neural_governance());
+  assert!(voting_system_client.get_neural_governance() == current);
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 300));
+}
+
+#[test]
+pub fn test_voting_power_bounds() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "AssignedReputation"), 1000)],
   );
-  voting_system_client.vote(
-    &voter_id_6,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  let voter_id_1 = String::from_slice(&env, "user001"); // 0,300
+  let voter_id_2 = String::from_slice(&env, "user002"); // 0,200
+  let voter_id_3 = String::from_slice(&env, "user003"); // 0,200
+  let voter_id_5 = String::from_slice(&env, "user005"); // 0,100
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[&voter_id_1, &voter_id_2, &voter_id_3, &voter_id_5],
   );
-  voting_system_client.vote(
-    &voter_id_8,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Abstain);
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.close_round();
+
+  assert!(
+    voting_system_client
+      .get_neural_governance()
+      .voting_power_bounds
+      == Default::default()
+  );
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 600
   );
 
-  let consensus =
-    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
-  assert!(consensus == Vote::Yes);
+  // the floor
+  voting_system_client.set_voting_power_bounds(&150, &None, &None);
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 650
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_5, &submission_id) == (0, 150));
+
+  // the cap
+  voting_system_client.set_voting_power_bounds(&0, &Some(250), &None);
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 550
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id) == (0, 250));
+
+  // 1.2 times the median of 0.3, 0.2 and 0.1, the abstaining voter is not taken into account
+  voting_system_client.set_voting_power_bounds(&0, &None, &Some(1200));
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 540
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id) == (0, 240));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_3, &submission_id) == (0, 200));
+
+  voting_system_client.set_voting_power_bounds(&150, &Some(250), &Some(1200));
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 590
+  );
+  assert!(
+    voting_system_client
+      .get_neural_governance()
+      .voting_power_bounds
+      == VotingPowerBounds {
+        min: 150,
+        max: Some(250),
+        median_multiple: Some(1200),
+      }
+  );
+
+  assert!(
+    voting_system_client.try_set_voting_power_bounds(&300, &Some(200), &None)
+      == Err(Ok(VotingSystemError::InvalidVotingPowerBounds))
+  );
+  assert!(
+    voting_system_client.try_set_voting_power_bounds(&0, &None, &Some(0))
+      == Err(Ok(VotingSystemError::InvalidVotingPowerBounds))
+  );
 }
 
 #[test]
-pub fn test_delegation_more_no_votes() {
+pub fn test_prior_voting_history_neuron() {
   let env = Env::default();
+  env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
 
-  initialize_external_data_provider(&env, &voting_system_client);
-
   assert!(voting_system_client.add_layer() == 0);
+
   voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
 
-  let voter_id_1 = String::from_slice(&env, "user001");
-  let voter_id_2 = String::from_slice(&env, "user002");
-  let voter_id_3 = String::from_slice(&env, "user003");
-  let voter_id_4 = String::from_slice(&env, "user004");
-  let voter_id_5 = String::from_slice(&env, "user005");
-  let voter_id_6 = String::from_slice(&env, "user006");
-  let voter_id_8 = String::from_slice(&env, "user008");
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "PriorVotingHistory"));
+
+  initialize_external_data_provider(&env, &voting_system_client);
 
+  let voter_id_1 = String::from_slice(&env, "user001"); // active rounds: [2, 3], bonusses: [0, 100], [0, 200]
+  let voter_id_2 = String::from_slice(&env, "user003"); // active rounds: [2, 3, 4], bonusses: [0, 100], [0, 200], [0, 300]
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
   let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.add_submission(&submission_id);
-  let delegatees = vec![
-    &env,
-    voter_id_2.clone(),
-    voter_id_3.clone(),
-    voter_id_4.clone(),
-    voter_id_5.clone(),
-    voter_id_6.clone(),
-    voter_id_8.clone(),
-  ];
-  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  ); // not considered - low rank
-  voting_system_client.vote(
-    &voter_id_3,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(&voter_id_4, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(
-    &voter_id_5,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(&voter_id_6, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(&voter_id_8, &submission_id, &String::from_slice(&env, "No"));
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
 
-  let consensus =
-    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
-  assert!(consensus == Vote::No);
+  voting_system_client.close_round();
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 300
+  );
 }
 
 #[test]
-pub fn test_delegation_too_many_abstain_votes() {
+pub fn test_graph_bonus() {
   let env = Env::default();
+  env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
 
-  initialize_external_data_provider(&env, &voting_system_client);
-
   assert!(voting_system_client.add_layer() == 0);
+
   voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "TrustGraph"));
+
+  let external_data_provider_client =
+    initialize_external_data_provider(&env, &voting_system_client);
 
   let voter_id_1 = String::from_slice(&env, "user001");
   let voter_id_2 = String::from_slice(&env, "user002");
-  let voter_id_3 = String::from_slice(&env, "user003");
-  let voter_id_4 = String::from_slice(&env, "user004");
-  let voter_id_5 = String::from_slice(&env, "user005");
-  let voter_id_6 = String::from_slice(&env, "user006");
-  let voter_id_8 = String::from_slice(&env, "user008");
-
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
   let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.add_submission(&submission_id);
-  let delegatees = vec![
-    &env,
-    voter_id_2.clone(),
-    voter_id_3.clone(),
-    voter_id_4.clone(),
-    voter_id_5.clone(),
-    voter_id_6.clone(),
-    voter_id_8.clone(),
-  ];
-  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  ); // not considered - low rank
-  voting_system_client.vote(
-    &voter_id_3,
-    &submission_id,
-    &String::from_slice(&env, "Abstain"),
-  );
-  voting_system_client.vote(
-    &voter_id_4,
-    &submission_id,
-    &String::from_slice(&env, "Abstain"),
-  );
-  voting_system_client.vote(
-    &voter_id_5,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::No);
+
+  let calculated =
+    external_data_provider_client.calculate_page_rank(&external_data_provider_client.get_owner());
+
+  assert!(
+    calculated
+      == Map::from_array(
+        &env,
+        [
+          (String::from_slice(&env, "user001"), 349_351_720),
+          (String::from_slice(&env, "user002"), 269_095_420),
+          (String::from_slice(&env, "user003"), 195_578_265),
+          (String::from_slice(&env, "user004"), 185_974_577),
+        ]
+      )
   );
-  voting_system_client.vote(&voter_id_6, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(&voter_id_8, &submission_id, &String::from_slice(&env, "No"));
 
-  let consensus =
-    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
-  assert!(consensus == Vote::Abstain);
+  voting_system_client.close_round();
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 80
+  );
 }
 
 #[test]
-pub fn test_delegation_too_many_delegate_votes() {
+pub fn test_graph_bonus_2() {
   let env = Env::default();
+  env.budget().reset_unlimited();
 
   let voting_system_client = initialize_voting_system(&env);
 
-  initialize_external_data_provider(&env, &voting_system_client);
-
   assert!(voting_system_client.add_layer() == 0);
+
   voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "TrustGraph"));
+
+  let external_data_provider_client =
+    initialize_external_data_provider(&env, &voting_system_client);
 
   let voter_id_1 = String::from_slice(&env, "user001");
   let voter_id_2 = String::from_slice(&env, "user002");
   let voter_id_3 = String::from_slice(&env, "user003");
   let voter_id_4 = String::from_slice(&env, "user004");
   let voter_id_5 = String::from_slice(&env, "user005");
-  let voter_id_6 = String::from_slice(&env, "user006");
-  let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
 
-  let voter_id_999 = String::from_slice(&env, "user999");
+  let mut new_trust_map: Map<String, Map<String, ()>> = Map::new(&env);
 
-  let submission_id = String::from_slice(&env, "submission001");
+  new_trust_map.set(
+    voter_id_1.clone(),
+    Map::from_array(
+      &env,
+      [
+        (voter_id_2.clone(), ()),
+        (voter_id_3.clone(), ()),
+        (voter_id_4.clone(), ()),
+        (voter_id_5.clone(), ()),
+      ],
+    ),
+  );
 
-  voting_system_client.add_submission(&submission_id);
-  let delegatees = vec![
-    &env,
+  new_trust_map.set(
     voter_id_2.clone(),
+    Map::from_array(
+      &env,
+      [
+        (voter_id_3.clone(), ()),
+        (voter_id_4.clone(), ()),
+        (voter_id_5.clone(), ()),
+      ],
+    ),
+  );
+
+  new_trust_map.set(
     voter_id_3.clone(),
-    voter_id_4.clone(),
-    voter_id_5.clone(),
-    voter_id_6.clone(),
-    voter_id_8.clone(),
-  ];
-  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+    Map::from_array(&env, [(voter_id_4.clone(), ()), (voter_id_5.clone(), ())]),
   );
-  voting_system_client.delegate(
-    &voter_id_3,
-    &submission_id,
-    &vec![
-      &env,
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-    ],
+
+  new_trust_map.set(
+    voter_id_4.clone(),
+    Map::from_array(&env, [(voter_id_5.clone(), ())]),
   );
-  voting_system_client.delegate(
-    &voter_id_4,
-    &submission_id,
-    &vec![
-      &env,
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-    ],
+
+  external_data_provider_client
+    .set_trust_map(&external_data_provider_client.get_owner(), &new_trust_map);
+
+  let submission_id = String::from_slice(&env, "submission001");
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
+
+  let calculated =
+    external_data_provider_client.calculate_page_rank(&external_data_provider_client.get_owner());
+
+  assert!(
+    calculated
+      == Map::from_array(
+        &env,
+        [
+          (String::from_slice(&env, "user001"), 99_127_493),
+          (String::from_slice(&env, "user002"), 120_192_075),
+          (String::from_slice(&env, "user003"), 154_246_509),
+          (String::from_slice(&env, "user004"), 219_801_328),
+          (String::from_slice(&env, "user005"), 406_632_553),
+        ]
+      )
   );
-  voting_system_client.delegate(
-    &voter_id_5,
-    &submission_id,
-    &vec![
-      &env,
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-    ],
+
+  voting_system_client.close_round();
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 21
   );
-  voting_system_client.vote(&voter_id_6, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(&voter_id_8, &submission_id, &String::from_slice(&env, "No"));
+}
 
-  let consensus =
-    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
-  assert!(consensus == Vote::Abstain);
+#[test]
+pub fn test_graph_bonus_personalized() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  assert!(voting_system_client.add_layer() == 0);
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "TrustGraph"));
+
+  let external_data_provider_client =
+    initialize_external_data_provider(&env, &voting_system_client);
+  let owner = external_data_provider_client.get_owner();
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
+
+  // user002 and user004 only trust each other, like user001 and user003
+  let mut new_trust_map: Map<String, Map<String, ()>> = Map::new(&env);
+  new_trust_map.set(
+    voter_id_1.clone(),
+    Map::from_array(&env, [(voter_id_3.clone(), ())]),
+  );
+  new_trust_map.set(
+    voter_id_3.clone(),
+    Map::from_array(&env, [(voter_id_1.clone(), ())]),
+  );
+  new_trust_map.set(
+    voter_id_2.clone(),
+    Map::from_array(&env, [(voter_id_4.clone(), ())]),
+  );
+  new_trust_map.set(
+    voter_id_4.clone(),
+    Map::from_array(&env, [(voter_id_2.clone(), ())]),
+  );
+  external_data_provider_client.set_trust_map(&owner, &new_trust_map);
+  external_data_provider_client
+    .set_trust_anchors(&owner, &Vec::from_array(&env, [voter_id_1.clone()]));
+  external_data_provider_client
+    .set_page_rank_mode(&external_data_provider_contract::PageRankMode::Personalized);
+
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
+
+  voting_system_client.calculate_page_rank();
+  assert!(external_data_provider_client.get_page_rank_result_for_user(&voter_id_2) == 0);
+
+  // only the voter trusted by the anchor counts
+  voting_system_client.close_round();
+  let result = voting_system_client.tally().get(submission_id).unwrap();
+  assert!(result.no_power == (0, 540));
+  assert!(result.yes_power == (0, 0));
+  assert!(result.net == -540);
 }
 
 #[test]
-pub fn test_delegation_yes_no_equal() {
+pub fn test_delegation_more_yes_votes() {
   let env = Env::default();
 
   let voting_system_client = initialize_voting_system(&env);
@@ -786,12 +1389,23 @@ pub fn test_delegation_yes_no_equal() {
   let voter_id_5 = String::from_slice(&env, "user005");
   let voter_id_6 = String::from_slice(&env, "user006");
   let voter_id_8 = String::from_slice(&env, "user008");
-
-  let voter_id_999 = String::from_slice(&env, "user999");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
 
   let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.add_submission(&submission_id);
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
   let delegatees = vec![
     &env,
     voter_id_2.clone(),
@@ -802,43 +1416,264 @@ pub fn test_delegation_yes_no_equal() {
     voter_id_8.clone(),
   ];
   voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  assert!(voting_system_client.get_delegatees().len() == 1);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::No); // not considered - low rank
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::Yes);
+
+  let consensus =
+    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
+  assert!(consensus == Vote::Yes);
+}
+
+#[test]
+pub fn test_tally_result() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  initialize_external_data_provider(&env, &voting_system_client);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
+  );
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[&voter_id_1, &voter_id_2, &voter_id_3, &voter_id_4],
+  );
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+
+  voting_system_client.vote(&voter_id_1, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::Abstain);
+
+  voting_system_client.close_round();
+  let results = voting_system_client.tally();
+  let result = results.get(submission_id.clone()).unwrap();
+  assert!(result.yes_power == (1, 100));
+  assert!(result.no_power == (2, 200));
+  // more no than yes power is a negative net
+  assert!(result.net == -1100);
+  assert!(result.yes_count == 1);
+  assert!(result.no_count == 2);
+  assert!(result.abstain_count == 1);
+  assert!(result.delegated_count == 0);
+  assert!(result.approval_ratio == (0, 333));
+  assert!(voting_system_client.get_round_results(&1) == results);
+}
+
+#[test]
+pub fn test_tally_detailed() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  initialize_external_data_provider(&env, &voting_system_client);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
   );
+
+  let voter_ids: Vec<String> = vec![
+    &env,
+    String::from_slice(&env, "user001"),
+    String::from_slice(&env, "user002"),
+    String::from_slice(&env, "user003"),
+    String::from_slice(&env, "user004"),
+    String::from_slice(&env, "user005"),
+    String::from_slice(&env, "user006"),
+    String::from_slice(&env, "user008"),
+  ];
+  for voter_id in voter_ids.iter() {
+    register_voters(&env, &voting_system_client, &[&voter_id]);
+  }
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+
+  // the quorum of user001 are the 5 best ranked delegatees: user003 - user008, its consensus is Yes
   voting_system_client.delegate(
-    &voter_id_3,
+    &voter_ids.get(0).unwrap(),
     &submission_id,
+    &voter_ids.slice(1..),
+  );
+  for (index, vote) in [
+    (1, Vote::No),
+    (2, Vote::No),
+    (3, Vote::Abstain),
+    (4, Vote::Yes),
+    (5, Vote::Yes),
+    (6, Vote::Yes),
+  ] {
+    voting_system_client.vote(&voter_ids.get(index).unwrap(), &submission_id, &vote);
+  }
+
+  assert!(voting_system_client.try_tally_detailed() == Err(Ok(VotingSystemError::RoundNotClosed)));
+  voting_system_client.close_round();
+
+  // every voter has the (1, 100) of the Dummy neuron
+  let submission_tally = voting_system_client
+    .tally_detailed()
+    .get(submission_id.clone())
+    .unwrap();
+  assert!(submission_tally.yes_power == (4, 400));
+  assert!(submission_tally.no_power == (2, 200));
+  assert!(submission_tally.abstain_count == 1);
+  assert!(submission_tally.delegated_count == 1);
+  assert!(submission_tally.contributions.len() == 6);
+  assert!(
+    submission_tally
+      .contributions
+      .get(voter_ids.get(0).unwrap())
+      == Some(VoterContribution {
+        vote: NormalizedVote::Yes,
+        delegated: true,
+        voting_power: (1, 100),
+      })
+  );
+  assert!(
+    submission_tally
+      .contributions
+      .get(voter_ids.get(1).unwrap())
+      == Some(VoterContribution {
+        vote: NormalizedVote::No,
+        delegated: false,
+        voting_power: (1, 100),
+      })
+  );
+  assert!(!submission_tally
+    .contributions
+    .contains_key(voter_ids.get(3).unwrap()));
+  // the detailed tally doesn't store anything
+  assert!(
+    voting_system_client.try_get_round_results(&1) == Err(Ok(VotingSystemError::RoundNotTallied))
+  );
+  assert!(
+    voting_system_client
+      .tally()
+      .get(submission_id.clone())
+      .unwrap()
+      .net
+      == 2200
+  );
+
+  // the contributions are bounded
+  voting_system_client.set_voting_power_bounds(&0, &Some(1000), &None);
+  let submission_tally = voting_system_client
+    .tally_detailed()
+    .get(submission_id)
+    .unwrap();
+  assert!(submission_tally.yes_power == (4, 0));
+  assert!(submission_tally.no_power == (2, 0));
+  assert!(
+    submission_tally
+      .contributions
+      .get(voter_ids.get(0).unwrap())
+      .unwrap()
+      .voting_power
+      == (1, 0)
+  );
+}
+
+#[test]
+pub fn test_explain_voting_power() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  initialize_external_data_provider(&env, &voting_system_client);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
     &vec![
       &env,
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
-      voter_id_999.clone(),
+      (String::from_slice(&env, "Dummy"), 2000),
+      (String::from_slice(&env, "AssignedReputation"), 1000),
     ],
   );
-  voting_system_client.vote(
-    &voter_id_4,
-    &submission_id,
-    &String::from_slice(&env, "Abstain"),
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
   );
-  voting_system_client.vote(
-    &voter_id_5,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
+  voting_system_client.set_neuron_activation(
+    &1,
+    &String::from_slice(&env, "Dummy"),
+    &Activation::Clamp(0, 3000),
   );
-  voting_system_client.vote(&voter_id_6, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(&voter_id_8, &submission_id, &String::from_slice(&env, "No"));
+  voting_system_client.set_voting_power_bounds(&0, &Some(2500), &None);
 
-  let consensus =
-    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
-  assert!(consensus == Vote::Abstain);
+  let voter_id = String::from_slice(&env, "user001"); // bonus 0,300
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+
+  let explanation = voting_system_client.explain_voting_power(&voter_id, &submission_id);
+  assert!(explanation.layers.len() == 2);
+
+  // 1.1 * 2 + 0.3
+  let layer0 = explanation.layers.get(0).unwrap();
+  assert!(layer0.layer_id == 0);
+  assert!(layer0.aggregator == LayerAggregator::Sum);
+  assert!(layer0.result == 2_500_000_000);
+  let find_neuron = |layer: &LayerExplanation, neuron: NeuronType| {
+    layer
+      .neurons
+      .iter()
+      .find(|neuron_explanation| neuron_explanation.neuron == neuron)
+      .unwrap()
+  };
+  assert!(
+    find_neuron(&layer0, NeuronType::Dummy)
+      == NeuronExplanation {
+        neuron: NeuronType::Dummy,
+        raw_output: 1_100_000_000,
+        activated_output: 1_100_000_000,
+        weighted_output: 2_200_000_000,
+      }
+  );
+  assert!(
+    find_neuron(&layer0, NeuronType::AssignedReputation)
+      == NeuronExplanation {
+        neuron: NeuronType::AssignedReputation,
+        raw_output: 300_000_000,
+        activated_output: 300_000_000,
+        weighted_output: 300_000_000,
+      }
+  );
+
+  // 1.1 + 2.5 clamped to 3
+  let layer1 = explanation.layers.get(1).unwrap();
+  assert!(
+    find_neuron(&layer1, NeuronType::Dummy)
+      == NeuronExplanation {
+        neuron: NeuronType::Dummy,
+        raw_output: 1_100_000_000,
+        activated_output: 3_000_000_000,
+        weighted_output: 3_000_000_000,
+      }
+  );
+  assert!(layer1.result == 3_000_000_000);
+
+  // and capped at 2.5
+  assert!(explanation.unbounded_voting_power == 3_000_000_000);
+  assert!(explanation.voting_power == (2, 500));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id, &submission_id) == (2, 500));
 }
 
 #[test]
-pub fn test_delegation_in_practice() {
+pub fn test_delegation_more_no_votes() {
   let env = Env::default();
 
   let voting_system_client = initialize_voting_system(&env);
@@ -856,10 +1691,23 @@ pub fn test_delegation_in_practice() {
   let voter_id_5 = String::from_slice(&env, "user005");
   let voter_id_6 = String::from_slice(&env, "user006");
   let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
 
   let submission_id = String::from_slice(&env, "submission001");
 
-  voting_system_client.add_submission(&submission_id);
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
   let delegatees = vec![
     &env,
     voter_id_2.clone(),
@@ -870,68 +1718,335 @@ pub fn test_delegation_in_practice() {
     voter_id_8.clone(),
   ];
   voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
-  voting_system_client.vote(&voter_id_2, &submission_id, &String::from_slice(&env, "No"));
-  voting_system_client.vote(
-    &voter_id_3,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(
-    &voter_id_4,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(
-    &voter_id_5,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(
-    &voter_id_6,
-    &submission_id,
-    &String::from_slice(&env, "Yes"),
-  );
-  voting_system_client.vote(
-    &voter_id_8,
-    &submission_id,
-    &String::from_slice(&env, "Abstain"),
-  );
-
-  let result = voting_system_client
-    .tally()
-    .get(submission_id.clone())
-    .unwrap();
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes); // not considered - low rank
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::No);
 
-  assert!(result == (4, 400));
+  let consensus =
+    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
+  assert!(consensus == Vote::No);
 }
 
 #[test]
-pub fn test_multiple_voting_operations() {
+pub fn test_delegation_too_many_abstain_votes() {
   let env = Env::default();
 
   let voting_system_client = initialize_voting_system(&env);
 
-  assert!(voting_system_client.add_layer() == 0);
-  assert!(voting_system_client.add_layer() == 1);
+  initialize_external_data_provider(&env, &voting_system_client);
 
+  assert!(voting_system_client.add_layer() == 0);
   voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
-  voting_system_client.set_layer_aggregator(&1, &String::from_slice(&env, "Product"));
-
   voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
-  voting_system_client.add_neuron(&1, &String::from_slice(&env, "Dummy"));
 
-  let voter_id = String::from_slice(&env, "user001");
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  let voter_id_5 = String::from_slice(&env, "user005");
+  let voter_id_6 = String::from_slice(&env, "user006");
+  let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
+
   let submission_id = String::from_slice(&env, "submission001");
-  let submission_id_2 = String::from_slice(&env, "submission002");
-  let submission_id_3 = String::from_slice(&env, "submission003");
-  let current_user_votes = voting_system_client.multiple_vote_operations(
-    &voter_id,
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let delegatees = vec![
+    &env,
+    voter_id_2.clone(),
+    voter_id_3.clone(),
+    voter_id_4.clone(),
+    voter_id_5.clone(),
+    voter_id_6.clone(),
+    voter_id_8.clone(),
+  ];
+  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes); // not considered - low rank
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::Abstain);
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::Abstain);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::No);
+
+  let consensus =
+    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
+  assert!(consensus == Vote::Abstain);
+}
+
+#[test]
+pub fn test_delegation_too_many_delegate_votes() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  assert!(voting_system_client.add_layer() == 0);
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  let voter_id_5 = String::from_slice(&env, "user005");
+  let voter_id_6 = String::from_slice(&env, "user006");
+  let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
+
+  let voter_id_999 = String::from_slice(&env, "user999");
+
+  let submission_id = String::from_slice(&env, "submission001");
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let delegatees = vec![
+    &env,
+    voter_id_2.clone(),
+    voter_id_3.clone(),
+    voter_id_4.clone(),
+    voter_id_5.clone(),
+    voter_id_6.clone(),
+    voter_id_8.clone(),
+  ];
+  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
+  voting_system_client.delegate(
+    &voter_id_3,
+    &submission_id,
+    &vec![
+      &env,
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+    ],
+  );
+  voting_system_client.delegate(
+    &voter_id_4,
+    &submission_id,
+    &vec![
+      &env,
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+    ],
+  );
+  voting_system_client.delegate(
+    &voter_id_5,
+    &submission_id,
+    &vec![
+      &env,
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+    ],
+  );
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::No);
+
+  let consensus =
+    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
+  assert!(consensus == Vote::Abstain);
+}
+
+#[test]
+pub fn test_delegation_yes_no_equal() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  assert!(voting_system_client.add_layer() == 0);
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  let voter_id_5 = String::from_slice(&env, "user005");
+  let voter_id_6 = String::from_slice(&env, "user006");
+  let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
+
+  let voter_id_999 = String::from_slice(&env, "user999");
+
+  let submission_id = String::from_slice(&env, "submission001");
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let delegatees = vec![
+    &env,
+    voter_id_2.clone(),
+    voter_id_3.clone(),
+    voter_id_4.clone(),
+    voter_id_5.clone(),
+    voter_id_6.clone(),
+    voter_id_8.clone(),
+  ];
+  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::Yes);
+  voting_system_client.delegate(
+    &voter_id_3,
+    &submission_id,
+    &vec![
+      &env,
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+      voter_id_999.clone(),
+    ],
+  );
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::Abstain);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::No);
+
+  let consensus =
+    voting_system_client.calculate_quorum_consensus(&voter_id_1, &submission_id.clone());
+  assert!(consensus == Vote::Abstain);
+}
+
+#[test]
+pub fn test_delegation_in_practice() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  assert!(voting_system_client.add_layer() == 0);
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  let voter_id_3 = String::from_slice(&env, "user003");
+  let voter_id_4 = String::from_slice(&env, "user004");
+  let voter_id_5 = String::from_slice(&env, "user005");
+  let voter_id_6 = String::from_slice(&env, "user006");
+  let voter_id_8 = String::from_slice(&env, "user008");
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[
+      &voter_id_1,
+      &voter_id_2,
+      &voter_id_3,
+      &voter_id_4,
+      &voter_id_5,
+      &voter_id_6,
+      &voter_id_8,
+    ],
+  );
+
+  let submission_id = String::from_slice(&env, "submission001");
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let delegatees = vec![
+    &env,
+    voter_id_2.clone(),
+    voter_id_3.clone(),
+    voter_id_4.clone(),
+    voter_id_5.clone(),
+    voter_id_6.clone(),
+    voter_id_8.clone(),
+  ];
+  voting_system_client.delegate(&voter_id_1, &submission_id, &delegatees);
+  voting_system_client.vote(&voter_id_2, &submission_id, &Vote::No);
+  voting_system_client.vote(&voter_id_3, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_4, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_5, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_6, &submission_id, &Vote::Yes);
+  voting_system_client.vote(&voter_id_8, &submission_id, &Vote::Abstain);
+
+  voting_system_client.close_round();
+  let result = voting_system_client
+    .tally()
+    .get(submission_id.clone())
+    .unwrap();
+
+  assert!(result.net == 4400);
+}
+
+#[test]
+pub fn test_multiple_voting_operations() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  assert!(voting_system_client.add_layer() == 0);
+  assert!(voting_system_client.add_layer() == 1);
+
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  voting_system_client.set_layer_aggregator(&1, &String::from_slice(&env, "Product"));
+
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+  voting_system_client.add_neuron(&1, &String::from_slice(&env, "Dummy"));
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+  let submission_id_2 = String::from_slice(&env, "submission002");
+  let submission_id_3 = String::from_slice(&env, "submission003");
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&submission_id, &submission_id_2, &submission_id_3],
+  );
+  let current_user_votes = voting_system_client.multiple_vote_operations(
+    &voter_id,
     &Map::from_array(
       &env,
       [
-        (submission_id.clone(), String::from_slice(&env, "No")),
-        (submission_id_2.clone(), String::from_slice(&env, "Yes")),
-        (submission_id_3.clone(), String::from_slice(&env, "Yes")),
+        (submission_id.clone(), Vote::No),
+        (submission_id_2.clone(), Vote::Yes),
+        (submission_id_3.clone(), Vote::Yes),
       ],
     ),
   );
@@ -972,9 +2087,9 @@ pub fn test_multiple_voting_operations() {
     &Map::from_array(
       &env,
       [
-        (submission_id.clone(), String::from_slice(&env, "Remove")),
-        (submission_id_2.clone(), String::from_slice(&env, "Remove")),
-        (submission_id_3.clone(), String::from_slice(&env, "No")),
+        (submission_id.clone(), Vote::Remove),
+        (submission_id_2.clone(), Vote::Remove),
+        (submission_id_3.clone(), Vote::No),
       ],
     ),
   );
@@ -997,9 +2112,9 @@ pub fn test_multiple_voting_operations() {
     &Vec::from_array(
       &env,
       [
-        (submission_id.clone(), String::from_slice(&env, "Remove")),
-        (submission_id_2.clone(), String::from_slice(&env, "Remove")),
-        (submission_id_3.clone(), String::from_slice(&env, "No")),
+        (submission_id.clone(), Vote::Remove),
+        (submission_id_2.clone(), Vote::Remove),
+        (submission_id_3.clone(), Vote::No),
       ],
     ),
   );
@@ -1019,10 +2134,7 @@ pub fn test_multiple_voting_operations() {
 
   let current_user_votes = voting_system_client.multiple_vote_operations(
     &voter_id,
-    &Map::from_array(
-      &env,
-      [(submission_id_3.clone(), String::from_slice(&env, "Remove"))],
-    ),
+    &Map::from_array(&env, [(submission_id_3.clone(), Vote::Remove)]),
   );
   assert!(current_user_votes.is_empty());
   assert!(voting_system_client.get_voters().is_empty());
@@ -1037,27 +2149,21 @@ pub fn test_decomposed_tally() {
 
   let voter_id_1 = String::from_slice(&env, "user001");
   let voter_id_2 = String::from_slice(&env, "user002");
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
 
   let submission_1_id = String::from_slice(&env, "submission001");
   let submission_2_id = String::from_slice(&env, "submission002");
-
-  voting_system_client.vote(
-    &voter_id_1,
-    &submission_1_id,
-    &String::from_slice(&env, "Yes"),
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&submission_1_id, &submission_2_id],
   );
 
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_1_id,
-    &String::from_slice(&env, "No"),
-  );
+  voting_system_client.vote(&voter_id_1, &submission_1_id, &Vote::Yes);
 
-  voting_system_client.vote(
-    &voter_id_2,
-    &submission_2_id,
-    &String::from_slice(&env, "Yes"),
-  );
+  voting_system_client.vote(&voter_id_2, &submission_1_id, &Vote::No);
+
+  voting_system_client.vote(&voter_id_2, &submission_2_id, &Vote::Yes);
 
   let n_layers = 5;
 
@@ -1078,7 +2184,9 @@ pub fn test_decomposed_tally() {
 
   voting_system_client.calculate_page_rank();
 
-  let normalized_votes: Map<String, Map<String, String>> = voting_system_client.normalize_votes();
+  voting_system_client.close_round();
+  let normalized_votes: Map<String, Map<String, NormalizedVote>> =
+    voting_system_client.normalize_votes();
   let mut voters_voting_powers: Map<String, u32> = Map::new(&env);
   for (submission_id, submission_votes) in normalized_votes.clone() {
     for (voter_id, _normalized_vote) in submission_votes {
@@ -1099,8 +2207,8 @@ pub fn test_decomposed_tally() {
 
   let fetched_voting_powers = voting_system_client.get_voting_powers();
 
-  assert!(fetched_voting_powers.get(voter_id_1.clone()).unwrap() == (1080, 416));
-  assert!(fetched_voting_powers.get(voter_id_2.clone()).unwrap() == (6, 490));
+  assert!(fetched_voting_powers.get(voter_id_1.clone()).unwrap() == (1190, 748));
+  assert!(fetched_voting_powers.get(voter_id_2.clone()).unwrap() == (7, 297));
 
   voting_system_client.set_voting_power_for_user(&voter_id_1.clone(), &(1, 0));
 
@@ -1112,8 +2220,8 @@ pub fn test_decomposed_tally() {
       == (1, 0)
   );
 
-  assert!(final_voting_powers.get(submission_1_id).unwrap() == (1073, 926));
-  assert!(final_voting_powers.get(submission_2_id).unwrap() == (6, 490));
+  assert!(final_voting_powers.get(submission_1_id).unwrap().net == 1183451);
+  assert!(final_voting_powers.get(submission_2_id).unwrap().net == 7297);
 
   let mut voters_voting_powers_vec: Vec<(String, u32)> = Vec::new(&env);
   let mut normalized_votes_vec: Vec<(String, String, String)> = Vec::new(&env);
@@ -1122,7 +2230,8 @@ pub fn test_decomposed_tally() {
     voters_voting_powers_vec.push_back((voter_id, power));
   }
 
-  for (submission_id, normalized_votes_map) in normalized_votes {
+  // the string variant is what the CLI uses
+  for (submission_id, normalized_votes_map) in voting_system_client.normalize_votes_str() {
     for (voter_id, normalized_vote) in normalized_votes_map {
       normalized_votes_vec.push_back((submission_id.clone(), voter_id, normalized_vote));
     }
@@ -1133,3 +2242,1059 @@ pub fn test_decomposed_tally() {
 
   assert!(final_voting_powers == final_voting_powers_from_vec);
 }
+
+#[test]
+pub fn test_decomposed_tally_with_median_cap() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "AssignedReputation"), 1000)],
+  );
+  initialize_external_data_provider(&env, &voting_system_client);
+  voting_system_client.set_voting_power_bounds(&0, &None, &Some(1200));
+
+  let voter_id_1 = String::from_slice(&env, "user001"); // 0,300
+  let voter_id_2 = String::from_slice(&env, "user002"); // 0,200
+  let voter_id_3 = String::from_slice(&env, "user003"); // 0,200
+  let voter_id_5 = String::from_slice(&env, "user005"); // 0,100
+  register_voters(
+    &env,
+    &voting_system_client,
+    &[&voter_id_1, &voter_id_2, &voter_id_3, &voter_id_5],
+  );
+  let submission_id_1 = String::from_slice(&env, "submission001");
+  let submission_id_2 = String::from_slice(&env, "submission002");
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&submission_id_1, &submission_id_2],
+  );
+  voting_system_client.vote(&voter_id_1, &submission_id_1, &Vote::Yes);
+  voting_system_client.vote(&voter_id_3, &submission_id_1, &Vote::Yes);
+  voting_system_client.vote(&voter_id_5, &submission_id_1, &Vote::Yes);
+  voting_system_client.vote(&voter_id_1, &submission_id_2, &Vote::Yes);
+  voting_system_client.vote(&voter_id_2, &submission_id_2, &Vote::No);
+
+  // the median can only be stored once the votes can't change, but the voting power is capped by it
+  // while the round is open as well, 1.2 times the median of 0.3, 0.2 and 0.1
+  assert!(
+    voting_system_client.try_calculate_median_voting_power(&submission_id_1)
+      == Err(Ok(VotingSystemError::RoundNotClosed))
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id_1) == (0, 240));
+  voting_system_client.close_round();
+
+  // the median is stored explicitly for the first submission, tally stores both of them
+  assert!(
+    voting_system_client.calculate_median_voting_power(&submission_id_1) == Some(200_000_000)
+  );
+  let normalized_votes = voting_system_client.normalize_votes();
+  let tally_results = voting_system_client.tally();
+  for (submission_id, submission_votes) in normalized_votes {
+    let mut voters_voting_powers: Map<String, u32> = Map::new(&env);
+    for voter_id in submission_votes.keys() {
+      let voting_power = voting_system_client.voting_power_for_voter(&voter_id, &submission_id);
+      voters_voting_powers.set(voter_id, DecimalNumberWrapper::from(voting_power).as_raw());
+    }
+    let submission_results = voting_system_client.submissions_voting_powers(
+      &voters_voting_powers,
+      &Map::from_array(&env, [(submission_id.clone(), submission_votes)]),
+    );
+    assert!(
+      submission_results.get(submission_id.clone()).unwrap().net
+        == tally_results.get(submission_id).unwrap().net
+    );
+  }
+  // 1.2 times the median of 0.3, 0.2 and 0.1, and of 0.3 and 0.2
+  assert!(tally_results.get(submission_id_1.clone()).unwrap().net == 540);
+  assert!(tally_results.get(submission_id_2.clone()).unwrap().net == 100);
+
+  // voting_power_for_voter only runs the governance of the voter and reads the stored median
+  env.as_contract(&voting_system_client.address, || {
+    let version: u32 = env
+      .storage()
+      .instance()
+      .get(&DataKey::MedianVotingPowerVersion)
+      .unwrap();
+    assert!(
+      env
+        .storage()
+        .persistent()
+        .get::<_, (u32, i128)>(&DataKey::MedianVotingPower(1, submission_id_2.clone()))
+        == Some((version, 250_000_000))
+    );
+    env.storage().persistent().set(
+      &DataKey::MedianVotingPower(1, submission_id_1.clone()),
+      &(version, 100_000_000_i128),
+    );
+  });
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id_1) == (0, 120));
+
+  // a change of the neural governance discards the stored medians, even if the bounds are the same
+  voting_system_client.set_voting_power_bounds(&0, &None, &Some(1200));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id_1) == (0, 240));
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id_2) == (0, 300));
+  assert!(
+    voting_system_client.calculate_median_voting_power(&submission_id_1) == Some(200_000_000)
+  );
+  assert!(voting_system_client.voting_power_for_voter(&voter_id_1, &submission_id_1) == (0, 240));
+}
+
+#[test]
+pub fn test_voter_authorization() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  let voter_id = String::from_slice(&env, "user001");
+  let unregistered_voter_id = String::from_slice(&env, "user002");
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let vote = Vote::Yes;
+
+  let voter_address = Address::generate(&env);
+  let impostor_address = Address::generate(&env);
+  voting_system_client.register_voter(&voter_id, &voter_address);
+  assert!(voting_system_client.get_voter_address(&voter_id) == voter_address);
+
+  // the voter id cannot be taken over by another address
+  assert!(
+    voting_system_client.try_register_voter(&voter_id, &impostor_address)
+      == Err(Ok(VotingSystemError::VoterAlreadyRegistered))
+  );
+  assert!(
+    voting_system_client.try_vote(&unregistered_voter_id, &submission_id, &vote)
+      == Err(Ok(VotingSystemError::VoterNotRegistered))
+  );
+
+  // impostor signs a vote on behalf of the voter
+  env.mock_auths(&[MockAuth {
+    address: &impostor_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "vote",
+      args: (voter_id.clone(), submission_id.clone(), vote.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_vote(&voter_id, &submission_id, &vote)
+    .is_err());
+  assert!(voting_system_client
+    .get_votes_for_user(&voter_id)
+    .is_empty());
+
+  env.mock_auths(&[MockAuth {
+    address: &voter_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "vote",
+      args: (voter_id.clone(), submission_id.clone(), vote.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  voting_system_client.vote(&voter_id, &submission_id, &vote);
+  assert!(
+    voting_system_client
+      .get_votes_for_user(&voter_id)
+      .get(submission_id.clone())
+      == Some(Vote::Yes)
+  );
+
+  // impostor tries to remove the vote
+  env.mock_auths(&[MockAuth {
+    address: &impostor_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "remove_vote",
+      args: (voter_id.clone(), submission_id.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_remove_vote(&voter_id, &submission_id)
+    .is_err());
+
+  // impostor tries to override the vote with a batch
+  let votes = Map::from_array(&env, [(submission_id.clone(), Vote::No)]);
+  env.mock_auths(&[MockAuth {
+    address: &impostor_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "multiple_vote_operations",
+      args: (voter_id.clone(), votes.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_multiple_vote_operations(&voter_id, &votes)
+    .is_err());
+
+  // impostor tries to delegate the vote
+  let delegatees = vec![
+    &env,
+    String::from_slice(&env, "user003"),
+    String::from_slice(&env, "user004"),
+    String::from_slice(&env, "user005"),
+    String::from_slice(&env, "user006"),
+    String::from_slice(&env, "user007"),
+  ];
+  env.mock_auths(&[MockAuth {
+    address: &impostor_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "delegate",
+      args: (voter_id.clone(), submission_id.clone(), delegatees.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_delegate(&voter_id, &submission_id, &delegatees)
+    .is_err());
+
+  // the voter delegates, both the delegatees and the vote are authorized with one signature
+  env.mock_auths(&[MockAuth {
+    address: &voter_address,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "delegate",
+      args: (voter_id.clone(), submission_id.clone(), delegatees.clone()).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  voting_system_client.delegate(&voter_id, &submission_id, &delegatees);
+  assert!(
+    voting_system_client
+      .get_votes_for_user(&voter_id)
+      .get(submission_id.clone())
+      == Some(Vote::Delegate)
+  );
+  assert!(voting_system_client.get_delegatees().get(voter_id.clone()) == Some(delegatees));
+}
+
+#[test]
+pub fn test_admin_access_control() {
+  let env = Env::default();
+
+  let voting_system_client = initialize_voting_system(&env);
+
+  let admin = voting_system_client.get_admin();
+  let new_admin = Address::generate(&env);
+  let outsider = Address::generate(&env);
+
+  assert!(
+    voting_system_client.try_initialize(&outsider)
+      == Err(Ok(VotingSystemError::AlreadyInitialized))
+  );
+  assert!(voting_system_client.get_admin() == admin);
+
+  // configuration can't be changed by anyone but the admin
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "add_layer",
+      args: ().into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client.try_add_layer().is_err());
+  assert!(voting_system_client
+    .get_neural_governance()
+    .layers
+    .is_empty());
+
+  let neurons: Vec<(String, u32)> = vec![&env, (String::from_slice(&env, "Dummy"), 1000)];
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "setup_layer",
+      args: (
+        String::from_slice(&env, "Sum"),
+        String::from_slice(&env, "Additive"),
+        neurons.clone(),
+      )
+        .into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_setup_layer(
+      &String::from_slice(&env, "Sum"),
+      &String::from_slice(&env, "Additive"),
+      &neurons,
+    )
+    .is_err());
+
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "set_external_data_provider",
+      args: (outsider.clone(),).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client
+    .try_set_external_data_provider(&outsider)
+    .is_err());
+
+  env.mock_auths(&[MockAuth {
+    address: &admin,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "setup_layer",
+      args: (
+        String::from_slice(&env, "Sum"),
+        String::from_slice(&env, "Additive"),
+        neurons.clone(),
+      )
+        .into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &neurons,
+  );
+  assert!(voting_system_client.get_neural_governance().layers.len() == 1);
+
+  // two-step admin transfer
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "propose_admin",
+      args: (outsider.clone(),).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client.try_propose_admin(&outsider).is_err());
+  assert!(voting_system_client.try_accept_admin() == Err(Ok(VotingSystemError::NoPendingAdmin)));
+
+  env.mock_auths(&[MockAuth {
+    address: &admin,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "propose_admin",
+      args: (new_admin.clone(),).into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  voting_system_client.propose_admin(&new_admin);
+  assert!(voting_system_client.get_pending_admin() == Some(new_admin.clone()));
+  // proposing does not hand over the admin role yet
+  assert!(voting_system_client.get_admin() == admin);
+
+  env.mock_auths(&[MockAuth {
+    address: &outsider,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "accept_admin",
+      args: ().into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client.try_accept_admin().is_err());
+
+  env.mock_auths(&[MockAuth {
+    address: &new_admin,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "accept_admin",
+      args: ().into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  voting_system_client.accept_admin();
+  assert!(voting_system_client.get_admin() == new_admin);
+  assert!(voting_system_client.get_pending_admin().is_none());
+
+  // the previous admin lost access
+  env.mock_auths(&[MockAuth {
+    address: &admin,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "add_layer",
+      args: ().into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client.try_add_layer().is_err());
+
+  env.mock_auths(&[MockAuth {
+    address: &new_admin,
+    invoke: &MockAuthInvoke {
+      contract: &voting_system_client.address,
+      fn_name: "add_layer",
+      args: ().into_val(&env),
+      sub_invokes: &[],
+    },
+  }]);
+  assert!(voting_system_client.add_layer() == 1);
+}
+
+#[test]
+pub fn test_round_lifecycle() {
+  let env = Env::default();
+  env.mock_all_auths();
+
+  let voting_system_id = env.register_contract(None, VotingSystem);
+  let voting_system_client = VotingSystemClient::new(&env, &voting_system_id);
+  voting_system_client.initialize(&Address::generate(&env));
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
+  );
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+  let yes = Vote::Yes;
+
+  // there is no round yet
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id, &yes)
+      == Err(Ok(VotingSystemError::RoundNotFound))
+  );
+  assert!(voting_system_client.try_tally() == Err(Ok(VotingSystemError::RoundNotFound)));
+  assert!(voting_system_client.get_votes().is_empty());
+
+  assert!(voting_system_client.create_round() == 1);
+  assert!(voting_system_client.get_current_round().state == RoundState::Draft);
+  // submissions can be added to a draft round, but votes can't be casted yet
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id, &yes)
+      == Err(Ok(VotingSystemError::RoundNotOpen))
+  );
+  assert!(
+    voting_system_client.try_finalize_round()
+      == Err(Ok(VotingSystemError::InvalidRoundStateTransition))
+  );
+
+  voting_system_client.open_round();
+  voting_system_client.vote(&voter_id, &submission_id, &yes);
+  assert!(voting_system_client.try_tally() == Err(Ok(VotingSystemError::RoundNotClosed)));
+  assert!(voting_system_client.try_create_round() == Err(Ok(VotingSystemError::RoundNotFinalized)));
+
+  voting_system_client.close_round();
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id, &yes)
+      == Err(Ok(VotingSystemError::RoundNotOpen))
+  );
+  assert!(
+    voting_system_client.try_add_submission(
+      &String::from_slice(&env, "submission002"),
+      &String::from_slice(&env, "Submission 2"),
+      &String::from_slice(&env, "Infrastructure"),
+      &10_000,
+      &Address::generate(&env),
+    ) == Err(Ok(VotingSystemError::RoundNotOpen))
+  );
+  assert!(
+    voting_system_client.try_get_round_results(&1) == Err(Ok(VotingSystemError::RoundNotTallied))
+  );
+
+  let results = voting_system_client.tally();
+  assert!(results.get(submission_id.clone()).is_some());
+  assert!(voting_system_client.get_current_round().state == RoundState::Tallied);
+  assert!(voting_system_client.get_round_results(&1) == results);
+
+  voting_system_client.finalize_round();
+  assert!(
+    voting_system_client.try_tally() == Err(Ok(VotingSystemError::InvalidRoundStateTransition))
+  );
+
+  // the next round starts empty and the history of the previous one is preserved
+  assert!(voting_system_client.create_round() == 2);
+  assert!(voting_system_client.get_current_round().id == 2);
+  assert!(voting_system_client.get_votes().is_empty());
+  assert!(voting_system_client
+    .get_votes_for_user(&voter_id)
+    .is_empty());
+  // submissions are registered per round
+  assert!(voting_system_client.get_submissions().is_empty());
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.open_round();
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::No);
+
+  assert!(voting_system_client.get_round(&1).state == RoundState::Finalized);
+  assert!(
+    voting_system_client
+      .get_votes_for_round(&1)
+      .get(submission_id.clone())
+      .unwrap()
+      .get(voter_id.clone())
+      == Some(Vote::Yes)
+  );
+  assert!(voting_system_client.get_round_results(&1) == results);
+  assert!(
+    voting_system_client
+      .get_votes()
+      .get(submission_id.clone())
+      .unwrap()
+      .get(voter_id.clone())
+      == Some(Vote::No)
+  );
+}
+
+#[test]
+pub fn test_voting_window() {
+  let env = Env::default();
+  env.ledger().with_mut(|li| li.sequence_number = 100);
+
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
+  );
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  let yes = Vote::Yes;
+
+  assert!(
+    voting_system_client.try_set_voting_window(&200, &200)
+      == Err(Ok(VotingSystemError::InvalidVotingWindow))
+  );
+  voting_system_client.set_voting_window(&200, &300);
+
+  // too early
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id, &yes)
+      == Err(Ok(VotingSystemError::VotingWindowNotStarted))
+  );
+
+  env.ledger().with_mut(|li| li.sequence_number = 200);
+  voting_system_client.vote(&voter_id, &submission_id, &yes);
+  assert!(voting_system_client.try_tally() == Err(Ok(VotingSystemError::RoundNotClosed)));
+
+  env.ledger().with_mut(|li| li.sequence_number = 299);
+  voting_system_client.remove_vote(&voter_id, &submission_id);
+  voting_system_client.vote(&voter_id, &submission_id, &yes);
+
+  // too late, the round is closed without the admin calling close_round
+  env.ledger().with_mut(|li| li.sequence_number = 300);
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id, &yes)
+      == Err(Ok(VotingSystemError::VotingWindowEnded))
+  );
+  assert!(
+    voting_system_client.try_remove_vote(&voter_id, &submission_id)
+      == Err(Ok(VotingSystemError::VotingWindowEnded))
+  );
+  assert!(
+    voting_system_client.try_multiple_vote_operations(
+      &voter_id,
+      &Map::from_array(&env, [(submission_id.clone(), yes.clone())])
+    ) == Err(Ok(VotingSystemError::VotingWindowEnded))
+  );
+  assert!(voting_system_client.get_current_round().state == RoundState::Closed);
+
+  assert!(voting_system_client
+    .tally()
+    .get(submission_id.clone())
+    .is_some());
+  assert!(voting_system_client.get_current_round().state == RoundState::Tallied);
+}
+
+#[test]
+pub fn test_voting_window_opens_draft_round() {
+  let env = Env::default();
+  env.mock_all_auths();
+  env.ledger().with_mut(|li| li.sequence_number = 10);
+
+  let voting_system_id = env.register_contract(None, VotingSystem);
+  let voting_system_client = VotingSystemClient::new(&env, &voting_system_id);
+  voting_system_client.initialize(&Address::generate(&env));
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+
+  voting_system_client.create_round();
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  voting_system_client.set_voting_window(&20, &30);
+  assert!(voting_system_client.get_current_round().state == RoundState::Draft);
+
+  env.ledger().with_mut(|li| li.sequence_number = 20);
+  assert!(voting_system_client.get_current_round().state == RoundState::Open);
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::No);
+  assert!(voting_system_client.get_votes_for_user(&voter_id).len() == 1);
+}
+
+#[test]
+pub fn test_ballot_storage() {
+  let env = Env::default();
+  let voting_system_client = initialize_voting_system(&env);
+
+  let voter_id_1 = String::from_slice(&env, "user001");
+  let voter_id_2 = String::from_slice(&env, "user002");
+  register_voters(&env, &voting_system_client, &[&voter_id_1, &voter_id_2]);
+  let submission_id_1 = String::from_slice(&env, "submission001");
+  let submission_id_2 = String::from_slice(&env, "submission002");
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&submission_id_1, &submission_id_2],
+  );
+
+  voting_system_client.vote(&voter_id_1, &submission_id_1, &Vote::Yes);
+  voting_system_client.vote(&voter_id_1, &submission_id_2, &Vote::No);
+  voting_system_client.vote(&voter_id_2, &submission_id_1, &Vote::No);
+
+  // every ballot is a separate persistent entry, none of them is kept in the instance storage
+  env.as_contract(&voting_system_client.address, || {
+    assert!(
+      env.storage().persistent().get(&DataKey::Ballot(
+        1,
+        submission_id_1.clone(),
+        voter_id_2.clone()
+      )) == Some(Vote::No)
+    );
+    assert!(!env.storage().persistent().has(&DataKey::Ballot(
+      1,
+      submission_id_2.clone(),
+      voter_id_2.clone()
+    )));
+  });
+
+  assert!(voting_system_client.get_submissions().len() == 2);
+  assert!(voting_system_client.get_voters().len() == 2);
+  assert!(voting_system_client.get_votes_for_user(&voter_id_1).len() == 2);
+  assert!(
+    voting_system_client
+      .get_votes_for_submission(&submission_id_1)
+      .len()
+      == 2
+  );
+  assert!(
+    voting_system_client
+      .get_votes_for_submission(&submission_id_2)
+      .len()
+      == 1
+  );
+
+  voting_system_client.remove_vote(&voter_id_2, &submission_id_1);
+  assert!(voting_system_client.get_voters() == vec![&env, voter_id_1.clone()]);
+  assert!(
+    voting_system_client
+      .get_votes_for_submission(&submission_id_1)
+      .len()
+      == 1
+  );
+  env.as_contract(&voting_system_client.address, || {
+    assert!(!env.storage().persistent().has(&DataKey::Ballot(
+      1,
+      submission_id_1.clone(),
+      voter_id_2.clone()
+    )));
+  });
+
+  // ballots and the instance outlive the default TTL once extended
+  env
+    .ledger()
+    .with_mut(|li| li.sequence_number = BALLOT_TTL_EXTEND_TO - 1);
+  voting_system_client.extend_round_ttl(&1);
+  env
+    .ledger()
+    .with_mut(|li| li.sequence_number = 2 * BALLOT_TTL_EXTEND_TO - 2);
+  assert!(voting_system_client.get_votes_for_user(&voter_id_1).len() == 2);
+  assert!(voting_system_client.get_round(&1).id == 1);
+}
+
+#[test]
+pub fn test_string_votes() {
+  let env = Env::default();
+  let voting_system_client = initialize_voting_system(&env);
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id_1 = String::from_slice(&env, "submission001");
+  let submission_id_2 = String::from_slice(&env, "submission002");
+  add_submissions(
+    &env,
+    &voting_system_client,
+    &[&submission_id_1, &submission_id_2],
+  );
+
+  voting_system_client.vote_str(
+    &voter_id,
+    &submission_id_1,
+    &String::from_slice(&env, "Yes"),
+  );
+  assert!(
+    voting_system_client
+      .get_votes_for_user(&voter_id)
+      .get(submission_id_1.clone())
+      == Some(Vote::Yes)
+  );
+
+  // typos are rejected instead of being counted as abstain
+  for unknown_vote in ["yes", "Yea", ""] {
+    assert!(
+      voting_system_client.try_vote_str(
+        &voter_id,
+        &submission_id_1,
+        &String::from_slice(&env, unknown_vote),
+      ) == Err(Ok(VotingSystemError::UnknownVote))
+    );
+  }
+  assert!(
+    voting_system_client
+      .get_votes_for_user(&voter_id)
+      .get(submission_id_1.clone())
+      == Some(Vote::Yes)
+  );
+
+  // a batch with a single unknown vote is rejected as a whole
+  let votes = Map::from_array(
+    &env,
+    [
+      (submission_id_1.clone(), String::from_slice(&env, "No")),
+      (submission_id_2.clone(), String::from_slice(&env, "Nope")),
+    ],
+  );
+  assert!(
+    voting_system_client.try_multiple_vote_operations_str(&voter_id, &votes)
+      == Err(Ok(VotingSystemError::UnknownVote))
+  );
+  let user_votes = voting_system_client.get_votes_for_user(&voter_id);
+  assert!(user_votes.len() == 1);
+  assert!(user_votes.get(submission_id_1.clone()) == Some(Vote::Yes));
+
+  assert!(
+    voting_system_client.try_set_layer_aggregator(&0, &String::from_slice(&env, "Summ"))
+      == Err(Ok(VotingSystemError::UnknownLayerAggregator))
+  );
+}
+
+#[test]
+pub fn test_submission_registry() {
+  let env = Env::default();
+  let voting_system_client = initialize_voting_system(&env);
+  voting_system_client.setup_layer(
+    &String::from_slice(&env, "Sum"),
+    &String::from_slice(&env, "Additive"),
+    &vec![&env, (String::from_slice(&env, "Dummy"), 1000)],
+  );
+  initialize_external_data_provider(&env, &voting_system_client);
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id_1 = String::from_slice(&env, "submission001");
+  let submission_id_2 = String::from_slice(&env, "submission002");
+  let unregistered_submission_id = String::from_slice(&env, "submission003");
+  let title = String::from_slice(&env, "Soroban wallet");
+  let category = String::from_slice(&env, "Financial Protocols");
+  let proposer = Address::generate(&env);
+
+  voting_system_client.add_submission(&submission_id_1, &title, &category, &25_000, &proposer);
+  add_submissions(&env, &voting_system_client, &[&submission_id_2]);
+  assert!(
+    voting_system_client.get_submission(&submission_id_1)
+      == Submission {
+        id: submission_id_1.clone(),
+        title: title.clone(),
+        category: category.clone(),
+        requested_budget: 25_000,
+        proposer: proposer.clone(),
+        status: SubmissionStatus::Active,
+      }
+  );
+  assert!(
+    voting_system_client.try_get_submission(&unregistered_submission_id)
+      == Err(Ok(VotingSystemError::SubmissionNotFound))
+  );
+  assert!(
+    voting_system_client.try_add_submission(&submission_id_1, &title, &category, &1, &proposer)
+      == Err(Ok(VotingSystemError::SubmissionAlreadyAdded))
+  );
+
+  // votes for submissions which were never registered are rejected instead of creating them
+  assert!(
+    voting_system_client.try_vote(&voter_id, &unregistered_submission_id, &Vote::Yes)
+      == Err(Ok(VotingSystemError::SubmissionNotFound))
+  );
+  assert!(
+    voting_system_client.try_multiple_vote_operations(
+      &voter_id,
+      &Map::from_array(
+        &env,
+        [
+          (submission_id_1.clone(), Vote::Yes),
+          (unregistered_submission_id.clone(), Vote::Yes),
+        ],
+      ),
+    ) == Err(Ok(VotingSystemError::SubmissionNotFound))
+  );
+  assert!(voting_system_client
+    .get_votes_for_user(&voter_id)
+    .is_empty());
+  assert!(voting_system_client.get_submissions().len() == 2);
+
+  voting_system_client.vote(&voter_id, &submission_id_1, &Vote::Yes);
+  voting_system_client.vote(&voter_id, &submission_id_2, &Vote::Yes);
+
+  voting_system_client.set_submission_status(&submission_id_2, &SubmissionStatus::Withdrawn);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          voting_system_client.address.clone(),
+          (symbol_short!("sub"), symbol_short!("status")).into_val(&env),
+          (1_u32, submission_id_2.clone(), SubmissionStatus::Withdrawn).into_val(&env),
+        )
+      ]
+  );
+  assert!(
+    voting_system_client.get_submission(&submission_id_2).status == SubmissionStatus::Withdrawn
+  );
+  assert!(
+    voting_system_client.try_vote(&voter_id, &submission_id_2, &Vote::No)
+      == Err(Ok(VotingSystemError::SubmissionWithdrawn))
+  );
+  assert!(
+    voting_system_client
+      .try_set_submission_status(&unregistered_submission_id, &SubmissionStatus::Withdrawn)
+      == Err(Ok(VotingSystemError::SubmissionNotFound))
+  );
+
+  // withdrawn submissions are not tallied
+  voting_system_client.close_round();
+  let results = voting_system_client.tally();
+  assert!(results.len() == 1);
+  assert!(results.get(submission_id_1).is_some());
+  assert!(results.get(submission_id_2).is_none());
+}
+
+#[test]
+pub fn test_events() {
+  let env = Env::default();
+  env.budget().reset_unlimited();
+
+  let voting_system_client = initialize_voting_system(&env);
+  let contract_id = voting_system_client.address.clone();
+
+  let voter_id = String::from_slice(&env, "user001");
+  register_voters(&env, &voting_system_client, &[&voter_id]);
+  let submission_id = String::from_slice(&env, "submission001");
+
+  add_submissions(&env, &voting_system_client, &[&submission_id]);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("sub"), symbol_short!("added")).into_val(&env),
+          (1_u32, submission_id.clone()).into_val(&env),
+        )
+      ]
+  );
+
+  // neural governance changes
+  voting_system_client.add_layer();
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("layer"), symbol_short!("added")).into_val(&env),
+          0_u32.into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.set_layer_aggregator(&0, &String::from_slice(&env, "Sum"));
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("layer"), symbol_short!("aggr")).into_val(&env),
+          (0_u32, LayerAggregator::Sum).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.set_layer_input_mode(&0, &String::from_slice(&env, "Ignored"));
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("layer"), symbol_short!("input")).into_val(&env),
+          (0_u32, LayerInputMode::Ignored).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.add_neuron(&0, &String::from_slice(&env, "Dummy"));
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("neuron"), symbol_short!("added")).into_val(&env),
+          (0_u32, NeuronType::Dummy).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.set_neuron_weight(&0, &String::from_slice(&env, "Dummy"), &2000);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("neuron"), symbol_short!("weight")).into_val(&env),
+          (0_u32, NeuronType::Dummy, (2_u32, 0_u32)).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.set_neuron_activation(
+    &0,
+    &String::from_slice(&env, "Dummy"),
+    &Activation::SquareRoot,
+  );
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("neuron"), symbol_short!("activ")).into_val(&env),
+          (0_u32, NeuronType::Dummy, Activation::SquareRoot).into_val(&env),
+        )
+      ]
+  );
+
+  let external_data_provider_client =
+    initialize_external_data_provider(&env, &voting_system_client);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("provider"), symbol_short!("set")).into_val(&env),
+          external_data_provider_client.address.into_val(&env),
+        )
+      ]
+  );
+
+  // votes
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::No);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("vote"), symbol_short!("cast")).into_val(&env),
+          (1_u32, submission_id.clone(), voter_id.clone(), Vote::No).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::Yes);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("vote"), symbol_short!("changed")).into_val(&env),
+          (1_u32, submission_id.clone(), voter_id.clone(), Vote::Yes).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.remove_vote(&voter_id, &submission_id);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("vote"), symbol_short!("removed")).into_val(&env),
+          (1_u32, submission_id.clone(), voter_id.clone()).into_val(&env),
+        )
+      ]
+  );
+
+  let delegatees = vec![
+    &env,
+    String::from_slice(&env, "user002"),
+    String::from_slice(&env, "user003"),
+    String::from_slice(&env, "user004"),
+    String::from_slice(&env, "user005"),
+    String::from_slice(&env, "user006"),
+  ];
+  voting_system_client.set_delegatees(&voter_id, &delegatees);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("delegate"), symbol_short!("set")).into_val(&env),
+          (1_u32, voter_id.clone(), delegatees.clone()).into_val(&env),
+        )
+      ]
+  );
+
+  voting_system_client.vote(&voter_id, &submission_id, &Vote::Yes);
+  voting_system_client.close_round();
+  let results = voting_system_client.tally();
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("tally"), symbol_short!("results")).into_val(&env),
+          (1_u32, results).into_val(&env),
+        )
+      ]
+  );
+
+  voting_system_client.remove_neuron(&0, &String::from_slice(&env, "Dummy"));
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id.clone(),
+          (symbol_short!("neuron"), symbol_short!("removed")).into_val(&env),
+          (0_u32, NeuronType::Dummy).into_val(&env),
+        )
+      ]
+  );
+  voting_system_client.remove_layer(&0);
+  assert!(
+    last_event(&env)
+      == vec![
+        &env,
+        (
+          contract_id,
+          (symbol_short!("layer"), symbol_short!("removed")).into_val(&env),
+          0_u32.into_val(&env),
+        )
+      ]
+  );
+}